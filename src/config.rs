@@ -1,6 +1,8 @@
 // Configuration file, All measurements in real-world meters (1 unit = 1 meter)
 // This controls the initial generation parameter settings
 
+use std::ops::RangeInclusive;
+
 // Current parameter settings are based on values as documented in my report
 
 // Point generation boundaries (meters)
@@ -10,16 +12,50 @@ pub const CANVAS_HEIGHT: f32 = 500.0; // Working area height; not settlement siz
 pub const INITIAL_SEED: u64 = 1512086461918454205;
 pub const POINT_COUNT: usize = 30;
 pub const SPIRAL_SPREAD: f32 = 3.0;  // adjust initial point distribution here
+pub const RELAXATION_STEPS: usize = 4;        // lloyd's relaxation passes over the regular generators
+pub const RELAXATION_EPSILON: f32 = 0.0;      // relaxation stops once a pass moves the generators less than this in total (m), 0 runs every step
+pub const DENSITY_GRADIENT: f32 = 0.0;        // generator sampling density, > 0 denser in the middle, < 0 denser at the edge
+pub const DENSITY_MIN_WEIGHT: f32 = 0.1;      // floor on the density rejection weight, sampling always gets its points
+pub const POISSON_ATTEMPTS: usize = 30;        // candidates tried around a poisson point before it is retired
+pub const POISSON_SPACING_FACTOR: f32 = 0.85;  // poisson min distance as a fraction of the even spacing, leaves room for count points
+pub const GENERATOR_BOUNDS_MARGIN: f32 = 10.0; // generators are clamped to the boundary's bounds plus this margin
 
 // Building parameters (from morphological analysis)
 pub const BUILDING_AREA_MIN: f32 = 15.0;  // Minimum building footprint area (m2)
 pub const BUILDING_AREA_MAX: f32 = 40.0;  // Maximum building footprint area (m2)
+pub const BUILDING_AREA_LIMIT: f32 = 150.0; // Hard ceiling on footprint area (m2), larger plots are always cut
+pub const MIN_BLOCK_AREA: f32 = 20.0;     // Blocks below this area (m2) are not subdivided
+pub const BLOCK_MERGE_PROB: f32 = 0.0;    // chance that two neighbouring cells become one block
+pub const MAX_ASPECT_RATIO: f32 = 0.0;    // Plots longer than this times their width are re-cut or dropped, 0 = no limit
+pub const COURTYARD_PROB: f32 = 0.0;      // chance a large block is built as a ring around an empty courtyard
+pub const COURTYARD_MIN_AREA: f32 = 600.0; // Only blocks at least this large (m2) can get a courtyard
+pub const COURTYARD_DEPTH: f32 = 8.0;     // Depth (m) of the built ring around a courtyard
+pub const BLOCK_SETBACK: f32 = 0.0;       // Blocks are inset this far (m) from their cell edges, neighbours end up twice this apart
+pub const BLOCK_MERGE_SALT: u64 = 0x006d_6572_6765; // mixed into the seed, keeps the merge draws apart from the block rngs
+pub const BLOCK_SEED_PRECISION: f32 = 0.01; // Block centroids are rounded to this (m) before seeding the block rng
+pub const BLOCK_REROLL_SALT: u64 = 0x9e37_79b9_7f4a_7c15; // scaled by a block's re-roll count, 0 re-rolls leaves the block seed as is
+pub const IRREGULAR_FOOTPRINT_PROB: f32 = 0.2; // chance a plot gets an L/U notch, when enabled
+pub const MIN_EDGE_LENGTH: f32 = 0.1;     // Footprint edges shorter than this (m) are collapsed before meshing
+pub const SETBACK_MARGIN: f32 = 0.0;      // Buildings are inset this far (m) into their plot, 0 fills the plot
+pub const SETBACK_JITTER: f32 = 0.5;      // How much the setback varies and the building shifts within it
+pub const INTERIOR_MIN_AREA: f32 = 60.0;  // Only footprints at least this large (m2) get interior rooms
+pub const ROOM_MIN_AREA: f32 = 10.0;      // Rooms stop splitting below roughly this area (m2)
+pub const ROOM_MAX_DEPTH: usize = 4;      // Room subdivision depth, caps the room count per building
+pub const ROOM_WALL_HEIGHT: f32 = 1.0;    // Interior walls are drawn this high (m), low enough to see over
+pub const ROOM_SALT: u64 = 0x726f_6f6d;   // mixed into the room rng seeds, keeps them apart from the block rngs
 
 // Subdivision control parameters
 pub const CIRCUMCENTER_MERGE_THRESHOLD: f32 = 0.01;  // merge circumcenters closer than this distance
+pub const MIN_CLIPPED_CELL_AREA: f32 = 0.5;          // cells clipped to the boundary smaller than this are dropped
+pub const GENERATOR_WEIGHT: f32 = 1.0;               // power diagram weight of a generator nobody changed
+pub const GENERATOR_WEIGHT_SCALE: f32 = 100.0;       // m² of power distance per unit of weight difference
+pub const MIN_GENERATOR_SPACING: f32 = 2.0;          // minimum distance between regular generators after relaxation
+pub const SPACING_ITERATIONS: usize = 32;            // max push-apart passes for generator spacing
+pub const CORE_BIAS: f32 = 0.0;                      // generator pull toward the settlement core, 0 = none
 
 // Morphological variation parameters (dimensionless ratios)
 pub const GRID_CHAOS: f32 = 0.35;     // Geometric irregularity factor
+pub const SPLIT_BIAS: f32 = 0.5;      // Target cut position along an edge, 0.5 = halves, 0.618 = golden ratio
 pub const SIZE_CHAOS: f32 = 0.25;     // Building size variation factor  
 pub const EMPTY_PROB: f32 = 0.05;     // Probability of a plot being empty
 
@@ -27,9 +63,14 @@ pub const EMPTY_PROB: f32 = 0.05;     // Probability of a plot being empty
 pub const BOUNDARY_GENERATOR_SPACING: f32 = 12.0;       // Generator spacing along boundary edges
 pub const BOUNDARY_GENERATOR_INNER_OFFSET: f32 = 1.0;   // Inner boundary generator offset
 pub const BOUNDARY_GENERATOR_OUTER_OFFSET: f32 = 2.0;   // Outer boundary generator offset
+pub const BOUNDARY_ASPECT: f32 = 1.0;                   // Boundary X:Z stretch, 1.0 = round
+pub const BOUNDARY_NOISE_FREQ: usize = 4;               // Boundary wobble lumps per turn
+pub const BOUNDARY_NOISE_AMP: f32 = 0.2;                // Boundary wobble, fraction of the radius
+pub const HULL_MARGIN: f32 = 2.0;                       // Fitted hull boundary is pushed out by this much
 
 // Subdivision parameters
 pub const MAX_RECURSION_DEPTH: usize = 10;
+pub const FORCED_SPLIT_DEPTH: usize = 12; // extra recursion levels allowed to cut plots above max_sq
 
 // Alley parameters
 pub const ALLEY_WIDTH_MIN: f32 = 0.5;   // Minimum alley width
@@ -42,12 +83,139 @@ pub const ROAD_GENERATOR_SPACING: f32 = 7.0;   // Generator spacing along roads
 pub const ROAD_GENERATOR_OFFSET: f32 = 0.1;    // Road generator offset
 pub const CORNER_CONSTRAINT_DISTANCE: f32 = 2.0; // Corner constraint distance
 pub const ROAD_WIDTH: f32 = 4.0; // Road corridor width
+pub const CURB_HEIGHT: f32 = 0.15; // Raised curb height along the road corridor
+pub const CURB_WIDTH: f32 = 0.3;   // Curb strip width, measured inward from the corridor edge
+pub const ROAD_SMOOTHING: usize = 0; // Catmull-Rom points inserted per road segment
+pub const ADJACENCY_GAP: f32 = 2.0; // Buildings in a block closer than this are adjacent (covers alleys)
 
 // 3D building parameters, these are custom
 pub const MIN_WALL_HEIGHT: f32 = 2.0;   // Minimum wall height
 pub const MAX_WALL_HEIGHT: f32 = 6.0;   // Maximum wall height
+pub const CENTER_HEIGHT_FALLOFF: f32 = 0.0; // Extra wall height at the core, as a fraction, fading out toward the boundary
 pub const MIN_ROOF_HEIGHT: f32 = 0.7;   // Minimum roof height
 pub const MAX_ROOF_HEIGHT: f32 = 1.0;   // Maximum roof height
+pub const HIP_ROOF_ASPECT: f32 = 1.25;  // Footprints shorter than this times their width get a hip roof
+pub const BASE_EMBED: f32 = 0.0;        // How far walls extend below ground
+pub const EAVE_OVERHANG: f32 = 0.3;     // How far roofs reach past the walls
+pub const EAVE_THICKNESS: f32 = 0.1;    // Thickness of the overhanging roof slab
+pub const EAVE_CLAMP_STEPS: usize = 5;  // Halvings tried before an overhang that folds over is dropped
+
+// Wall openings, recessed windows and one door per building
+pub const WINDOW_DENSITY: f32 = 0.5;   // Chance a window slot gets a window
+pub const WINDOW_WIDTH: f32 = 0.8;
+pub const WINDOW_HEIGHT: f32 = 1.1;
+pub const WINDOW_SILL: f32 = 0.9;      // Sill height above each storey's floor
+pub const WINDOW_SPACING: f32 = 2.0;   // Width of one window slot along a wall
+pub const WINDOW_DEPTH: f32 = 0.15;    // How far openings are recessed into the wall
+pub const DOOR_WIDTH: f32 = 1.0;
+pub const DOOR_STEP: f32 = 0.05;       // Door bottom above ground, keeps it off the wall's base edge
+pub const OPENING_MARGIN: f32 = 0.3;   // Solid wall kept at corners, under the eaves and between a door and windows
+
+// Population estimate
+pub const FLOOR_HEIGHT: f32 = 2.8;       // Floor-to-floor height (m), counts storeys and spaces window rows
+pub const OCCUPANT_DENSITY: f32 = 0.08;  // Occupants per m2 of floor area
+
+// Ghost town alpha, previous generation kept for comparison
+pub const GHOST_ALPHA: f32 = 0.25;
+
+// Texturing parameters
+pub const UV_TILE_SIZE: f32 = 4.0; // world-planar texture tile size (m)
+pub const TEXTURE_METERS_PER_TILE: f32 = 1.0; // wall texture repeat size (m), 1.0 keeps UVs in meters
+
+// Wall textures, drawn at startup so there are no asset files to ship
+// buildings pick one per building when wall textures are on
+pub const WALL_TEXTURE_NAMES: [&str; 4] = ["plaster", "brick", "wood", "corrugated"];
+pub const WALL_TEXTURE_SIZE: u32 = 64; // pixels per side, one texture covers one wall tile
+
+// Area histogram
+pub const HISTOGRAM_BINS: usize = 20;
+pub const AREA_TIER_CLASSES: usize = 5; // area tier overlay, equal-width classes between the smallest and largest building
+
+// Export parameters
+pub const EXPORT_PRECISION: usize = 4; // decimal places for exported coordinates
+pub const PLAN_RESOLUTION: u32 = 2048;  // plan PNG size in pixels, always square
+pub const PLAN_MARGIN: f32 = 1.1;       // plan framing, boundary extent * this
+pub const MORPH_STEPS: usize = 24;      // seed morph export, frames between the two seeds
+pub const EXPORT_TILE_SIZE: f32 = 50.0; // tiled export, tile edge length (m)
+
+// Camera parameters
+pub const CAMERA_BOUNDS_MARGIN: f32 = 40.0; // the camera can pan this far (m) past the boundary's bounding box
+
+// Editing parameters
+pub const EDIT_HISTORY_LIMIT: usize = 50; // undo steps kept, each is a full skeleton snapshot
+
+// Import parameters
+pub const IMPORT_DEDUPE_DISTANCE: f32 = 0.01; // imported generators closer than this (m) are duplicates
+
+// Parameter presets, saved in the working directory
+pub const PRESETS_FILE: &str = "presets.ron";
+
+// Lighting parameters
+pub const AMBIENT_BRIGHTNESS: f32 = 80.0;       // ambient light brightness (cd/m2), bevy default
+pub const FILL_LIGHT_ILLUMINANCE: f32 = 400.0;  // secondary fill light illuminance (lux)
+
+// Frame pacing
+pub const MAX_FPS: f32 = 60.0; // frame limiter target, only used when the limiter is on
+
+// Building level of detail
+pub const LOD_DISTANCE: f32 = 250.0; // camera distance (m) past which buildings are drawn as plain boxes
 
-// roof heights are currently deprecated, 
-// I used to use them for moving the roof centroid up to make pyramids
\ No newline at end of file
+// UI slider ranges
+// shared by the config panel and parameter randomization
+pub const MIN_SQ_RANGE: RangeInclusive<f32> = 10.0..=25.0;
+pub const MIN_BLOCK_AREA_RANGE: RangeInclusive<f32> = 0.0..=200.0;
+pub const BLOCK_MERGE_PROB_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const IRREGULAR_FOOTPRINT_PROB_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const SETBACK_MARGIN_RANGE: RangeInclusive<f32> = 0.0..=3.0;
+pub const BLOCK_SETBACK_RANGE: RangeInclusive<f32> = 0.0..=3.0;
+pub const MAX_ASPECT_RATIO_RANGE: RangeInclusive<f32> = 0.0..=8.0;
+pub const COURTYARD_PROB_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const SETBACK_JITTER_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const MIN_EDGE_LENGTH_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const INTERIOR_MIN_AREA_RANGE: RangeInclusive<f32> = 20.0..=300.0;
+pub const MAX_SQ_RANGE: RangeInclusive<f32> = 40.0..=400.0;
+pub const GRID_CHAOS_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const SPLIT_BIAS_RANGE: RangeInclusive<f32> = 0.5..=0.8;
+pub const SIZE_CHAOS_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const EMPTY_PROB_RANGE: RangeInclusive<f32> = 0.0..=0.6;
+pub const ALLEY_WIDTH_RANGE: RangeInclusive<f32> = ALLEY_WIDTH_MIN..=ALLEY_WIDTH_MAX;
+pub const ALLEY_CHANCE_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const WALL_HEIGHT_RANGE: RangeInclusive<f32> = 2.0..=8.0;
+pub const WALL_HEIGHT_MARGIN: f32 = 0.5; // min gap between min and max wall height
+pub const ROOF_HEIGHT_RANGE: RangeInclusive<f32> = 0.1..=1.5;
+pub const ROOF_HEIGHT_MARGIN: f32 = 0.1; // min gap between min and max roof height
+pub const BASE_EMBED_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const CENTER_HEIGHT_FALLOFF_RANGE: RangeInclusive<f32> = 0.0..=2.0;
+pub const EAVE_OVERHANG_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const WINDOW_DENSITY_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+pub const FLOOR_HEIGHT_RANGE: RangeInclusive<f32> = 2.2..=5.0; // low end still fits a window row per floor
+pub const MAX_RECURSION_RANGE: RangeInclusive<usize> = 1..=14;
+pub const GENERATOR_COUNT_RANGE: RangeInclusive<usize> = 0..=80;
+pub const RELAXATION_STEPS_RANGE: RangeInclusive<usize> = 0..=12;
+pub const DENSITY_GRADIENT_RANGE: RangeInclusive<f32> = -1.0..=1.0;
+pub const RELAXATION_EPSILON_RANGE: RangeInclusive<f32> = 0.0..=5.0;
+pub const MERGE_THRESHOLD_RANGE: RangeInclusive<f32> = 0.01..=3.0;
+pub const CORE_BIAS_RANGE: RangeInclusive<f32> = 0.0..=3.0;
+pub const GENERATOR_SPACING_RANGE: RangeInclusive<f32> = 0.0..=8.0;
+pub const GENERATOR_WEIGHT_RANGE: RangeInclusive<f32> = 0.0..=4.0;
+pub const BOUNDARY_VERTEX_RANGE: RangeInclusive<usize> = 3..=12;
+pub const BOUNDARY_ASPECT_RANGE: RangeInclusive<f32> = 0.25..=4.0;
+pub const BOUNDARY_NOISE_FREQ_RANGE: RangeInclusive<usize> = 1..=16;
+pub const BOUNDARY_NOISE_AMP_RANGE: RangeInclusive<f32> = 0.0..=0.5;
+pub const BOUNDARY_SCALE_RANGE: RangeInclusive<f32> = 30.0..=150.0;
+pub const BOUNDARY_SPACING_RANGE: RangeInclusive<f32> = 6.0..=24.0;
+pub const BOUNDARY_INNER_OFFSET_RANGE: RangeInclusive<f32> = 0.5..=2.0;
+pub const CURB_HEIGHT_RANGE: RangeInclusive<f32> = 0.05..=0.5;
+pub const ROAD_SMOOTHING_RANGE: RangeInclusive<usize> = 0..=8;
+pub const ROAD_WIDTH_RANGE: RangeInclusive<f32> = 1.0..=12.0;
+pub const UV_TILE_SIZE_RANGE: RangeInclusive<f32> = 0.5..=20.0;
+pub const TEXTURE_METERS_PER_TILE_RANGE: RangeInclusive<f32> = 0.25..=10.0;
+pub const AMBIENT_BRIGHTNESS_RANGE: RangeInclusive<f32> = 0.0..=1000.0;
+pub const FILL_LIGHT_RANGE: RangeInclusive<f32> = 0.0..=2000.0;
+pub const MAX_FPS_RANGE: RangeInclusive<f32> = 15.0..=240.0;
+pub const LOD_DISTANCE_RANGE: RangeInclusive<f32> = 50.0..=1000.0;
+pub const GRID_ROTATION_RANGE: RangeInclusive<f32> = -45.0..=45.0; // degrees, the grid repeats every 90
+pub const EXPORT_PRECISION_RANGE: RangeInclusive<usize> = 1..=8;
+pub const PLAN_RESOLUTION_RANGE: RangeInclusive<u32> = 512..=8192;
+pub const MORPH_STEPS_RANGE: RangeInclusive<usize> = 1..=120;
+pub const EXPORT_TILE_SIZE_RANGE: RangeInclusive<f32> = 10.0..=500.0;
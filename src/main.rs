@@ -3,7 +3,7 @@ use bevy::math::bounding::Aabb2d;
 use bevy::pbr::wireframe::{WireframePlugin, WireframeConfig};
 use bevy::window::{WindowPlugin, PrimaryWindow};
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
-use bevy::gizmos::config::{GizmoConfigStore, DefaultGizmoConfigGroup};
+use bevy::gizmos::config::{GizmoConfigStore, DefaultGizmoConfigGroup, GizmoLineJoint};
 use bevy_egui::EguiPlugin;
 use bevy_rts_camera::*;
 
@@ -16,6 +16,7 @@ pub mod test;
 // import modules here
 use systems::grid::GridPlugin;
 use systems::mesh::BuildingGenerationPlugin;
+use systems::render::RenderSettingsPlugin;
 
 use crate::systems::interaction;
 use crate::systems::ui::UIPlugin;
@@ -40,6 +41,7 @@ fn main() -> bevy::app::AppExit {
         .add_plugins(GridPlugin)
         .add_plugins(BuildingGenerationPlugin)
         .add_plugins(UIPlugin)
+        .add_plugins(RenderSettingsPlugin)
 
         .insert_resource(WireframeConfig {
             global: true,
@@ -47,7 +49,7 @@ fn main() -> bevy::app::AppExit {
         })
         .insert_resource(ClearColor(Color::BLACK)) // world color
         .add_systems(Startup, (start, setup_gizmos, maximize_window))
-        .add_systems(Update, (handle_exit, interaction::handle_mouse_interaction))
+        .add_systems(Update, (handle_exit, interaction::handle_mouse_interaction, interaction::handle_undo_redo, interaction::handle_building_picking, interaction::handle_jump_to_issue, interaction::fit_camera_bounds))
         .run()
 }
 
@@ -56,6 +58,7 @@ fn setup_gizmos(
 ) {
     let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
     config.depth_bias = -1.0; // render on top of everything else
+    config.line.joints = GizmoLineJoint::Miter; // linestrips join cleanly instead of leaving notches at corners
 }
 
 fn maximize_window(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
@@ -71,6 +74,7 @@ fn start(
     // spawn camera
     commands.spawn((
         RtsCamera {
+            // placeholder, fit_camera_bounds scopes this to the settlement boundary
             bounds: Aabb2d::new(
                 Vec2::ZERO, 
                 Vec2::new(200.0, 200.0),
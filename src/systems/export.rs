@@ -1,25 +1,303 @@
-// saves the model as an obj file
+// saves the model as an obj (or binary stl) file
 // by iterating through all the meshes
 
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::gizmos::config::GizmoConfigStore;
+use bevy::render::camera::{RenderTarget, ScalingMode};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
 use std::io::{BufWriter, Write};
 
+use crate::systems::mesh::poly;
+use crate::systems::mesh::{BuildingAdjacency, Params, SkeletonData};
+use crate::systems::mesh::town::{AlleyMesh, Block, Building, BuildingFootprint, BuildingMesh, BuildingMeshLod, Ghost, LodCulled, RoadMesh, Town};
+use crate::systems::ui::NotificationEvent;
+
 // export event
+// precision = decimal places written for coordinates, OBJ only since STL stores raw floats
 #[derive(Event)]
 pub struct ExportEvent {
     pub filename: String,
+    pub format: ExportFormat,
+    pub precision: usize,
+    pub filter: MeshFilter,
+}
+
+// mesh file format, the export file name takes its extension
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    Obj,
+    Stl,  // binary, for 3D printing
+    Gltf, // binary .glb, keeps the town -> block -> building hierarchy
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Obj => "obj",
+            ExportFormat::Stl => "stl",
+            ExportFormat::Gltf => "glb",
+        }
+    }
+}
+
+// which meshes an OBJ export writes
+// visible_only follows the hierarchy, so 2D view (3D buildings hidden) exports just the flat plans
+// footprints are the flat y=0 plans, also present under the buildings in 3D view
+#[derive(Clone, Copy)]
+pub struct MeshFilter {
+    pub visible_only: bool,
+    pub footprints: bool,
+}
+
+impl MeshFilter {
+    // a building mesh swapped out for its lod box is still on screen as far as exports go
+    fn keeps(&self, visibility: &InheritedVisibility, is_footprint: bool, lod_culled: bool) -> bool {
+        (!self.visible_only || visibility.get() || lod_culled) && (self.footprints || !is_footprint)
+    }
+}
+
+// mesh entities an OBJ export can write, the ghost of the previous generation is never included
+// neither are the lod boxes, the full building meshes are always written
+// the global transform already has the parent building's transform composed in
+pub type ExportMeshes<'w, 's> = Query<'w, 's, (
+    &'static Mesh3d,
+    &'static GlobalTransform,
+    &'static InheritedVisibility,
+    Has<BuildingFootprint>,
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Has<LodCulled>,
+), (Without<Ghost>, Without<BuildingMeshLod>)>;
+
+// a mesh with the world transform its vertices are written in,
+// and its material colour when it has one (OBJ writes it to the .mtl)
+pub type PlacedMesh<'a> = (&'a Mesh, GlobalTransform, Option<Color>);
+
+// a mirroring transform (negative determinant) turns the winding inside out,
+// flipped back when writing so faces keep pointing outward
+fn flips_winding(transform: &GlobalTransform) -> bool {
+    transform.affine().matrix3.determinant() < 0.0
+}
+
+// building centroid export event
+#[derive(Event)]
+pub struct ExportCentroidsEvent {
+    pub filename: String,
+    pub precision: usize,
+}
+
+// building data (footprints + adjacency) export event
+#[derive(Event)]
+pub struct ExportJsonEvent {
+    pub filename: String,
+    pub precision: usize,
+}
+
+// seed morph export event, one OBJ per step from from_seed to to_seed
+// files are named <prefix>_000.obj .. <prefix>_<steps>.obj
+#[derive(Event)]
+pub struct ExportMorphEvent {
+    pub from_seed: u64,
+    pub to_seed: u64,
+    pub steps: usize,
+    pub prefix: String,
+    pub precision: usize,
+}
+
+// GeoJSON footprint export event, one polygon feature per building plus the boundary
+#[derive(Event)]
+pub struct ExportGeoJsonEvent {
+    pub filename: String,
+    pub precision: usize,
+}
+
+// tiled OBJ export event, buildings are binned by footprint centroid into tile_size squares
+// writes <directory>/tile_<x>_<z>.obj per non-empty tile plus <directory>/manifest.json
+#[derive(Event)]
+pub struct ExportTilesEvent {
+    pub directory: String,
+    pub tile_size: f32,
+    pub precision: usize,
+    pub filter: MeshFilter,
+}
+
+// top-down orthographic plan image export event
+// resolution = width and height of the square PNG
+#[derive(Event)]
+pub struct ExportPlanEvent {
+    pub filename: String,
+    pub resolution: u32,
+}
+
+// export options set from the UI
+#[derive(Resource)]
+pub struct ExportSettings {
+    pub precision: usize,
+    pub plan_resolution: u32,
+    pub morph_target: String, // target seed as typed, decimal or 0x hex
+    pub morph_steps: usize,
+    pub tile_size: f32,
+    pub visible_only: bool,       // skip hidden meshes, e.g. the 3D buildings in 2D view
+    pub include_footprints: bool, // write the flat footprint meshes
+    pub base_name: String,    // file name prefix, "slum" when left empty
+    pub deterministic: bool,  // tag files with the seed instead of the time
 }
 
-// export all meshes in scene
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            precision: crate::config::EXPORT_PRECISION,
+            plan_resolution: crate::config::PLAN_RESOLUTION,
+            morph_target: String::new(),
+            morph_steps: crate::config::MORPH_STEPS,
+            tile_size: crate::config::EXPORT_TILE_SIZE,
+            visible_only: false,
+            include_footprints: true,
+            base_name: String::new(),
+            deterministic: false,
+        }
+    }
+}
+
+impl ExportSettings {
+    pub fn mesh_filter(&self) -> MeshFilter {
+        MeshFilter { visible_only: self.visible_only, footprints: self.include_footprints }
+    }
+
+    // export file name without the extension, written to the current directory
+    // deterministic names are <base>[_<kind>]_<seed>, reruns of a seed overwrite the same file and can be diffed
+    // otherwise <base>_<kind>_<unix time>, where the OBJ keeps its old "export" kind
+    pub fn file_stem(&self, kind: Option<&str>, seed: u64) -> String {
+        let base = match self.base_name.trim() {
+            "" => "slum",
+            base => base,
+        };
+        if self.deterministic {
+            return match kind {
+                Some(kind) => format!("{}_{}_{}", base, kind, seed),
+                None => format!("{}_{}", base, seed),
+            };
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        format!("{}_{}_{}", base, kind.unwrap_or("export"), timestamp)
+    }
+}
+
+// in-flight plan capture
+// the plan camera lives for a couple of frames, then gets removed once the screenshot is saved
+#[derive(Resource, Default)]
+pub struct PlanCapture {
+    camera: Option<Entity>,
+    image: Handle<Image>,
+    filename: String,
+    frames: u32,
+    requested: bool,
+    gizmos_enabled: Vec<(TypeId, bool)>, // restored after the capture
+}
+
+// one writer per export kind, plus the export settings they share
+#[derive(SystemParam)]
+pub struct ExportEvents<'w> {
+    pub obj: EventWriter<'w, ExportEvent>,
+    pub centroids: EventWriter<'w, ExportCentroidsEvent>,
+    pub json: EventWriter<'w, ExportJsonEvent>,
+    pub geojson: EventWriter<'w, ExportGeoJsonEvent>,
+    pub plan: EventWriter<'w, ExportPlanEvent>,
+    pub morph: EventWriter<'w, ExportMorphEvent>,
+    pub tiles: EventWriter<'w, ExportTilesEvent>,
+    pub settings: ResMut<'w, ExportSettings>,
+}
+
+// success toast, shows the absolute path when it can be resolved
+fn export_notification(filename: &str) -> NotificationEvent {
+    let path = std::fs::canonicalize(filename)
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| filename.to_string());
+    NotificationEvent { message: format!("Exported to {}", path), is_error: false }
+}
+
+// export all meshes in scene that pass the filter, the ghost of the previous generation is skipped
 pub fn export_obj(
     meshes: &Assets<Mesh>,
-    mesh_entities: &Query<&Mesh3d>,
+    materials: &Assets<StandardMaterial>,
+    mesh_entities: &ExportMeshes,
     filename: &str,
+    precision: usize,
+    filter: MeshFilter,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let kept = mesh_entities.iter()
+        .filter(|(_, _, visibility, is_footprint, _, lod_culled)| filter.keeps(visibility, *is_footprint, *lod_culled))
+        .filter_map(|(mesh3d, transform, _, _, material, _)| Some((meshes.get(&mesh3d.0)?, *transform, material_color(materials, material))));
+    let mesh_count = write_obj(filename, kept, precision)?;
+    println!("Exported {} meshes to {}", mesh_count, filename);
+    
+    Ok(())
+}
+
+// base colour of a mesh's material, None when it has no material or it isn't loaded
+fn material_color(materials: &Assets<StandardMaterial>, material: Option<&MeshMaterial3d<StandardMaterial>>) -> Option<Color> {
+    materials.get(&material?.0).map(|material| material.base_color)
+}
+
+// MTL material name for a colour, the 8 bit srgba hex
+// colours that round to the same bytes share a name, which is what dedupes them
+fn mtl_name(color: Color) -> String {
+    let [r, g, b, a] = color.to_srgba().to_u8_array();
+    format!("color_{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+}
+
+// write the materials used by an OBJ, one newmtl per distinct colour
+fn write_mtl(filename: &std::path::Path, colors: &BTreeMap<String, Color>) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "# Exported from Slum Generator")?;
+    for (name, color) in colors {
+        let color = color.to_srgba();
+        writeln!(writer)?;
+        writeln!(writer, "newmtl {}", name)?;
+        writeln!(writer, "Kd {:.4} {:.4} {:.4}", color.red, color.green, color.blue)?;
+        writeln!(writer, "d {:.4}", color.alpha)?;
+        writeln!(writer, "illum 1")?; // diffuse only
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+// write meshes into a single OBJ file, one object per mesh
+// vertices are written in world space, no normals are written so there are none to transform
+// when any mesh has a colour, a sibling .mtl with the same stem is written and referenced
+// returns the number of meshes written
+#[allow(clippy::collapsible_if, clippy::collapsible_match)]
+pub fn write_obj<'a>(
+    filename: &str,
+    meshes: impl Iterator<Item = PlacedMesh<'a>>,
+    precision: usize,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let meshes: Vec<PlacedMesh> = meshes.collect();
+    let colors: BTreeMap<String, Color> = meshes.iter()
+        .filter_map(|(_, _, color)| *color)
+        .map(|color| (mtl_name(color), color))
+        .collect();
+
     let file = File::create(filename)?;
     let mut writer = BufWriter::new(file);
 
+    // material library, the OBJ refers to it by file name so both have to stay side by side
+    if !colors.is_empty() {
+        let mtl_path = std::path::Path::new(filename).with_extension("mtl");
+        write_mtl(&mtl_path, &colors)?;
+        let library = mtl_path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        writeln!(writer, "mtllib {}", library)?;
+    }
+
     // OBJ header
     writeln!(writer, "# Exported from Slum Generator")?;
     writeln!(writer, "Written by Marcel Putra 2025")?;
@@ -28,64 +306,398 @@ pub fn export_obj(
     let mut vertex_offset = 1; 
     let mut mesh_count = 0;
 
-    // export all mesh entities
-    for mesh3d in mesh_entities.iter() {
-        if let Some(mesh) = meshes.get(&mesh3d.0) {
-            writeln!(writer, "# Mesh {}", mesh_count)?;
-            writeln!(writer, "o Mesh_{}", mesh_count)?;
+    // export all meshes
+    for (mesh, transform, color) in meshes {
+        writeln!(writer, "# Mesh {}", mesh_count)?;
+        writeln!(writer, "o Mesh_{}", mesh_count)?;
 
-            // extract vertices from the mesh
-            if let Some(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
-                if let bevy::render::mesh::VertexAttributeValues::Float32x3(vertices) = positions {
+        // extract vertices from the mesh
+        if let Some(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            if let bevy::render::mesh::VertexAttributeValues::Float32x3(vertices) = positions {
 
-                    // write vertices
-                    for vertex in vertices {
-                        writeln!(writer, "v {} {} {}", vertex[0], vertex[1], vertex[2])?;
-                    }
+                // write vertices
+                for vertex in vertices {
+                    let vertex = transform.transform_point(Vec3::from_array(*vertex));
+                    writeln!(writer, "v {:.*} {:.*} {:.*}", precision, vertex.x, precision, vertex.y, precision, vertex.z)?;
+                }
+
+                if let Some(color) = color {
+                    writeln!(writer, "usemtl {}", mtl_name(color))?;
+                }
+
+                // corner order of each face
+                let [a, b, c] = if flips_winding(&transform) { [0, 2, 1] } else { [0, 1, 2] };
 
-                    // write faces using the mesh indices
-                    if let Some(indices) = mesh.indices() {
-                        match indices {
-                            bevy::render::mesh::Indices::U16(indices) => {
-                                for chunk in indices.chunks(3) {
-                                    if chunk.len() == 3 {
-                                        writeln!(
-                                            writer,
-                                            "f {} {} {}",
-                                            vertex_offset + chunk[0] as u32,
-                                            vertex_offset + chunk[1] as u32,
-                                            vertex_offset + chunk[2] as u32
-                                        )?;
-                                    }
+                // write faces using the mesh indices
+                if let Some(indices) = mesh.indices() {
+                    match indices {
+                        bevy::render::mesh::Indices::U16(indices) => {
+                            for chunk in indices.chunks(3) {
+                                if chunk.len() == 3 {
+                                    writeln!(
+                                        writer,
+                                        "f {} {} {}",
+                                        vertex_offset + chunk[a] as u32,
+                                        vertex_offset + chunk[b] as u32,
+                                        vertex_offset + chunk[c] as u32
+                                    )?;
                                 }
                             }
-                            bevy::render::mesh::Indices::U32(indices) => {
-                                for chunk in indices.chunks(3) {
-                                    if chunk.len() == 3 {
-                                        writeln!(
-                                            writer,
-                                            "f {} {} {}",
-                                            vertex_offset + chunk[0],
-                                            vertex_offset + chunk[1],
-                                            vertex_offset + chunk[2]
-                                        )?;
-                                    }
+                        }
+                        bevy::render::mesh::Indices::U32(indices) => {
+                            for chunk in indices.chunks(3) {
+                                if chunk.len() == 3 {
+                                    writeln!(
+                                        writer,
+                                        "f {} {} {}",
+                                        vertex_offset + chunk[a],
+                                        vertex_offset + chunk[b],
+                                        vertex_offset + chunk[c]
+                                    )?;
                                 }
                             }
                         }
                     }
-
-                    vertex_offset += vertices.len() as u32;
-                    writeln!(writer)?;
-                    mesh_count += 1;
                 }
+
+                vertex_offset += vertices.len() as u32;
+                writeln!(writer)?;
+                mesh_count += 1;
             }
         }
     }
 
     writer.flush()?;
-    println!("Exported {} meshes to {}", mesh_count, filename);
-    
+
+    Ok(mesh_count)
+}
+
+// export all meshes in scene that pass the filter as a binary STL, same selection as export_obj
+pub fn export_stl(
+    meshes: &Assets<Mesh>,
+    mesh_entities: &ExportMeshes,
+    filename: &str,
+    filter: MeshFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // STL has no colours
+    let kept = mesh_entities.iter()
+        .filter(|(_, _, visibility, is_footprint, _, lod_culled)| filter.keeps(visibility, *is_footprint, *lod_culled))
+        .filter_map(|(mesh3d, transform, _, _, _, _)| Some((meshes.get(&mesh3d.0)?, *transform, None)));
+    let triangle_count = write_stl(filename, kept)?;
+    println!("Exported {} triangles to {}", triangle_count, filename);
+
+    Ok(())
+}
+
+// triangles of a mesh as world space vertex positions
+// meshes without indices are read as a plain triangle list, meshes without positions give nothing
+fn mesh_triangles((mesh, transform, _): PlacedMesh) -> Vec<[Vec3; 3]> {
+    let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(vertices)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        return Vec::new();
+    };
+    // Indices::iter widens U16 and U32 alike to usize
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..vertices.len()).collect(),
+    };
+    let [a, b, c] = if flips_winding(&transform) { [0, 2, 1] } else { [0, 1, 2] };
+    indices.chunks_exact(3)
+        .filter(|chunk| chunk.iter().all(|&index| index < vertices.len()))
+        .map(|chunk| [chunk[a], chunk[b], chunk[c]].map(|index| transform.transform_point(Vec3::from_array(vertices[index]))))
+        .collect()
+}
+
+// write meshes into a single binary STL file
+// STL is z-up for slicers, so y-up bevy coordinates are rotated +90 degrees about x,
+// a proper rotation, the winding (and so the facet normals) stays outward
+// facet normals come from the world space triangles, so they follow each mesh's transform
+// returns the number of triangles written
+pub fn write_stl<'a>(
+    filename: &str,
+    meshes: impl Iterator<Item = PlacedMesh<'a>>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    // the header holds the triangle count, so gather everything first
+    let triangles: Vec<[Vec3; 3]> = meshes
+        .flat_map(mesh_triangles)
+        .map(|triangle| triangle.map(|v| Vec3::new(v.x, -v.z, v.y)))
+        .collect();
+
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    // 80 byte header, must not start with "solid" or readers take it for ASCII STL
+    let mut header = [0u8; 80];
+    let title = b"Exported from Slum Generator";
+    header[..title.len()].copy_from_slice(title);
+    writer.write_all(&header)?;
+    writer.write_all(&(triangles.len() as u32).to_le_bytes())?;
+
+    for [a, b, c] in &triangles {
+        // facet normal from the winding, degenerate triangles get a zero normal which slicers recompute
+        let normal = (*b - *a).cross(*c - *a).normalize_or_zero();
+        for v in [normal, *a, *b, *c] {
+            for component in v.to_array() {
+                writer.write_all(&component.to_le_bytes())?;
+            }
+        }
+        writer.write_all(&0u16.to_le_bytes())?; // attribute byte count, unused
+    }
+
+    writer.flush()?;
+
+    Ok(triangles.len())
+}
+
+// entity tree the glTF export walks, from the town down to the mesh children
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct GltfHierarchy<'w, 's> {
+    towns: Query<'w, 's, (Entity, &'static Town)>,
+    nodes: Query<'w, 's, (&'static Transform, Option<&'static Children>, Option<&'static Block>, Option<&'static Building>)>,
+    mesh_kinds: Query<'w, 's, (Has<BuildingFootprint>, Has<BuildingMesh>, Has<RoadMesh>, Has<AlleyMesh>)>,
+}
+
+// glTF component types and buffer view targets
+const GLTF_UNSIGNED_SHORT: u32 = 5123;
+const GLTF_UNSIGNED_INT: u32 = 5125;
+const GLTF_FLOAT: u32 = 5126;
+const GLTF_ARRAY_BUFFER: u32 = 34962;
+const GLTF_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+// glTF document being assembled, json objects are kept as strings like the other exporters
+// everything lives in one binary buffer, written as the GLB's BIN chunk
+#[derive(Default)]
+struct GltfBuilder {
+    bin: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+    meshes: Vec<String>,
+    materials: Vec<String>,
+    nodes: Vec<String>,
+    mesh_ids: HashMap<AssetId<Mesh>, usize>,   // meshes shared between entities are written once
+    material_ids: HashMap<String, usize>,       // by mtl_name, identical colours share a material
+}
+
+impl GltfBuilder {
+    // append bytes as a buffer view, views start on 4 byte boundaries as the spec requires
+    fn push_view(&mut self, bytes: &[u8], target: u32) -> usize {
+        self.bin.resize(self.bin.len().next_multiple_of(4), 0);
+        self.buffer_views.push(format!(
+            "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{}}}",
+            self.bin.len(), bytes.len(), target
+        ));
+        self.bin.extend_from_slice(bytes);
+        self.buffer_views.len() - 1
+    }
+
+    // accessor over a whole buffer view, bounds are required for positions
+    fn push_accessor(&mut self, view: usize, component_type: u32, count: usize, kind: &str, bounds: Option<(Vec3, Vec3)>) -> usize {
+        let bounds = bounds
+            .map(|(min, max)| format!(",\"min\":[{},{},{}],\"max\":[{},{},{}]", min.x, min.y, min.z, max.x, max.y, max.z))
+            .unwrap_or_default();
+        self.accessors.push(format!(
+            "{{\"bufferView\":{},\"componentType\":{},\"count\":{},\"type\":\"{}\"{}}}",
+            view, component_type, count, kind, bounds
+        ));
+        self.accessors.len() - 1
+    }
+
+    fn push_floats<const N: usize>(&mut self, values: &[[f32; N]], kind: &str, bounds: Option<(Vec3, Vec3)>) -> usize {
+        let bytes: Vec<u8> = values.iter().flatten().flat_map(|value| value.to_le_bytes()).collect();
+        let view = self.push_view(&bytes, GLTF_ARRAY_BUFFER);
+        self.push_accessor(view, GLTF_FLOAT, values.len(), kind, bounds)
+    }
+
+    // material for a StandardMaterial, the base colour factor is linear
+    fn material(&mut self, material: &StandardMaterial) -> usize {
+        let name = mtl_name(material.base_color);
+        if let Some(&index) = self.material_ids.get(&name) {
+            return index;
+        }
+        let color = material.base_color.to_linear();
+        let blend = if color.alpha < 1.0 { ",\"alphaMode\":\"BLEND\"" } else { "" };
+        self.materials.push(format!(
+            "{{\"name\":\"{}\",\"pbrMetallicRoughness\":{{\"baseColorFactor\":[{},{},{},{}],\"metallicFactor\":{},\"roughnessFactor\":{}}}{}}}",
+            name, color.red, color.green, color.blue, color.alpha, material.metallic, material.perceptual_roughness, blend
+        ));
+        self.material_ids.insert(name, self.materials.len() - 1);
+        self.materials.len() - 1
+    }
+
+    // one single-primitive glTF mesh per bevy mesh, positions/normals/uvs/indices copied as is
+    // None when the mesh has no Float32x3 positions, like the OBJ writer skips it
+    fn mesh(&mut self, id: AssetId<Mesh>, mesh: &Mesh, material: Option<usize>) -> Option<usize> {
+        use bevy::render::mesh::{Indices, VertexAttributeValues};
+
+        if let Some(&index) = self.mesh_ids.get(&id) {
+            return Some(index);
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return None;
+        };
+        if positions.is_empty() {
+            return None; // no bounds to write
+        }
+        let (min, max) = positions.iter()
+            .map(|position| Vec3::from_array(*position))
+            .fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(min, max), position| (min.min(position), max.max(position)));
+        let mut attributes = vec![format!("\"POSITION\":{}", self.push_floats(positions, "VEC3", Some((min, max))))];
+        if let Some(VertexAttributeValues::Float32x3(normals)) = mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            attributes.push(format!("\"NORMAL\":{}", self.push_floats(normals, "VEC3", None)));
+        }
+        if let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            attributes.push(format!("\"TEXCOORD_0\":{}", self.push_floats(uvs, "VEC2", None)));
+        }
+
+        let mut primitive = format!("{{\"attributes\":{{{}}}", attributes.join(","));
+        let indices = match mesh.indices() {
+            Some(Indices::U16(indices)) => {
+                let bytes: Vec<u8> = indices.iter().flat_map(|index| index.to_le_bytes()).collect();
+                Some((bytes, GLTF_UNSIGNED_SHORT, indices.len()))
+            }
+            Some(Indices::U32(indices)) => {
+                let bytes: Vec<u8> = indices.iter().flat_map(|index| index.to_le_bytes()).collect();
+                Some((bytes, GLTF_UNSIGNED_INT, indices.len()))
+            }
+            None => None,
+        };
+        if let Some((bytes, component_type, count)) = indices {
+            let view = self.push_view(&bytes, GLTF_ELEMENT_ARRAY_BUFFER);
+            primitive += &format!(",\"indices\":{}", self.push_accessor(view, component_type, count, "SCALAR", None));
+        }
+        if let Some(material) = material {
+            primitive += &format!(",\"material\":{}", material);
+        }
+        primitive += "}";
+
+        self.meshes.push(format!("{{\"primitives\":[{}]}}", primitive));
+        self.mesh_ids.insert(id, self.meshes.len() - 1);
+        Some(self.meshes.len() - 1)
+    }
+
+    // node with its local transform, children are node indices
+    fn node(&mut self, name: &str, transform: &Transform, mesh: Option<usize>, children: &[usize]) -> usize {
+        let Transform { translation: t, rotation: r, scale: s } = transform;
+        let mut node = format!(
+            "{{\"name\":{:?},\"translation\":[{},{},{}],\"rotation\":[{},{},{},{}],\"scale\":[{},{},{}]",
+            name, t.x, t.y, t.z, r.x, r.y, r.z, r.w, s.x, s.y, s.z
+        );
+        if let Some(mesh) = mesh {
+            node += &format!(",\"mesh\":{}", mesh);
+        }
+        if !children.is_empty() {
+            let children: Vec<String> = children.iter().map(usize::to_string).collect();
+            node += &format!(",\"children\":[{}]", children.join(","));
+        }
+        node += "}";
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+}
+
+// export the town as a binary glTF, one node per town, block and building, mesh children below them
+// nodes keep their local transforms so the hierarchy composes the same way it does in bevy
+pub fn export_gltf(
+    meshes: &Assets<Mesh>,
+    materials: &Assets<StandardMaterial>,
+    mesh_entities: &ExportMeshes,
+    hierarchy: &GltfHierarchy,
+    filename: &str,
+    filter: MeshFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut gltf = GltfBuilder::default();
+
+    // depth first, children before parents since a node lists its children by index
+    // entities without a transform (rooms) and filtered or empty branches are left out
+    fn visit(
+        entity: Entity,
+        gltf: &mut GltfBuilder,
+        meshes: &Assets<Mesh>,
+        materials: &Assets<StandardMaterial>,
+        mesh_entities: &ExportMeshes,
+        hierarchy: &GltfHierarchy,
+        filter: MeshFilter,
+    ) -> Option<usize> {
+        let (transform, children, block, building) = hierarchy.nodes.get(entity).ok()?;
+        let child_nodes: Vec<usize> = children.into_iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|child| visit(child, gltf, meshes, materials, mesh_entities, hierarchy, filter))
+            .collect();
+
+        let mesh = mesh_entities.get(entity).ok()
+            .filter(|(_, _, visibility, is_footprint, _, lod_culled)| filter.keeps(visibility, *is_footprint, *lod_culled))
+            .and_then(|(mesh3d, _, _, _, material, _)| {
+                let material = material.and_then(|material| materials.get(&material.0)).map(|material| gltf.material(material));
+                gltf.mesh(mesh3d.0.id(), meshes.get(&mesh3d.0)?, material)
+            });
+
+        let name = match (block, building) {
+            (_, Some(building)) => format!("Building_{}", building.id),
+            (Some(block), _) => match block.id {
+                Some(id) => format!("Block_{}", id),
+                None => "Block".to_string(),
+            },
+            _ => match hierarchy.mesh_kinds.get(entity) {
+                Ok((true, _, _, _)) => "Footprint".to_string(),
+                Ok((_, true, _, _)) => "Walls".to_string(),
+                Ok((_, _, true, _)) => "Road".to_string(),
+                Ok((_, _, _, true)) => "Alley".to_string(),
+                _ => "Mesh".to_string(),
+            },
+        };
+
+        // blocks and buildings stay as (possibly empty) groups, bare mesh nodes only with a mesh
+        if mesh.is_none() && child_nodes.is_empty() && block.is_none() && building.is_none() {
+            return None;
+        }
+        Some(gltf.node(&name, transform, mesh, &child_nodes))
+    }
+
+    let mut roots = Vec::new();
+    for (town_entity, town) in hierarchy.towns.iter() {
+        let (transform, children, _, _) = hierarchy.nodes.get(town_entity)?;
+        let child_nodes: Vec<usize> = children.into_iter()
+            .flat_map(|children| children.iter())
+            .filter_map(|child| visit(child, &mut gltf, meshes, materials, mesh_entities, hierarchy, filter))
+            .collect();
+        roots.push(gltf.node(&town.name, transform, None, &child_nodes));
+    }
+    if roots.is_empty() {
+        return Err("no town to export".into());
+    }
+
+    let roots: Vec<String> = roots.iter().map(usize::to_string).collect();
+    let mut json = format!(
+        "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"Slum Generator\"}},\"scene\":0,\"scenes\":[{{\"nodes\":[{}]}}],\"nodes\":[{}],\"meshes\":[{}],\"accessors\":[{}],\"bufferViews\":[{}],\"buffers\":[{{\"byteLength\":{}}}]",
+        roots.join(","), gltf.nodes.join(","), gltf.meshes.join(","), gltf.accessors.join(","), gltf.buffer_views.join(","), gltf.bin.len()
+    );
+    if !gltf.materials.is_empty() {
+        json += &format!(",\"materials\":[{}]", gltf.materials.join(","));
+    }
+    json += "}";
+
+    // GLB container: 12 byte header, JSON chunk padded with spaces, BIN chunk padded with zeros
+    let mut json = json.into_bytes();
+    json.resize(json.len().next_multiple_of(4), b' ');
+    let mut bin = gltf.bin;
+    bin.resize(bin.len().next_multiple_of(4), 0);
+
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+    let total_length = 12 + 8 + json.len() + 8 + bin.len();
+    writer.write_all(b"glTF")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&(total_length as u32).to_le_bytes())?;
+    writer.write_all(&(json.len() as u32).to_le_bytes())?;
+    writer.write_all(b"JSON")?;
+    writer.write_all(&json)?;
+    writer.write_all(&(bin.len() as u32).to_le_bytes())?;
+    writer.write_all(b"BIN\0")?;
+    writer.write_all(&bin)?;
+    writer.flush()?;
+
+    println!("Exported {} nodes and {} meshes to {}", gltf.nodes.len(), gltf.meshes.len(), filename);
+
     Ok(())
 }
 
@@ -93,16 +705,511 @@ pub fn export_obj(
 pub fn handle_export(
     mut events: EventReader<ExportEvent>,
     meshes: Res<Assets<Mesh>>,
-    mesh_entities: Query<&Mesh3d>,
+    materials: Res<Assets<StandardMaterial>>,
+    mesh_entities: ExportMeshes,
+    hierarchy: GltfHierarchy,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    for event in events.read() {
+        let result = match event.format {
+            ExportFormat::Obj => export_obj(&meshes, &materials, &mesh_entities, &event.filename, event.precision, event.filter),
+            ExportFormat::Stl => export_stl(&meshes, &mesh_entities, &event.filename, event.filter),
+            ExportFormat::Gltf => export_gltf(&meshes, &materials, &mesh_entities, &hierarchy, &event.filename, event.filter),
+        };
+        match result {
+            Ok(()) => {
+                println!("Export successful: {}", event.filename);
+                notifications.write(export_notification(&event.filename));
+            }
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Export failed: {}", e), is_error: true });
+            }
+        }
+    }
+}
+
+// export building footprint centroids as a CSV point list
+// yaw is the angle (radians, around +Y) of the footprint's longest edge,
+// which is a decent guess for the building's front orientation
+pub fn export_centroids(
+    buildings: &Query<&Building>,
+    filename: &str,
+    precision: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(writer, "id,x,y,z,yaw")?;
+
+    let mut building_count = 0;
+    for building in buildings.iter() {
+        let footprint = &building.footprint;
+        if footprint.len() < 3 {
+            continue;
+        }
+
+        let centroid = poly::utils::polygon_centroid(footprint, poly::utils::polygon_area(footprint));
+        let yaw = match poly::subdivision::vlongest_edge(footprint) {
+            Some((idx, start, _)) => {
+                let dir = footprint[(idx + 1) % footprint.len()] - start;
+                dir.y.atan2(dir.x)
+            }
+            None => 0.0,
+        };
+
+        writeln!(
+            writer,
+            "{},{:.*},{:.*},{:.*},{:.*}",
+            building.id, precision, centroid.x, precision, 0.0, precision, centroid.y, precision, yaw
+        )?;
+        building_count += 1;
+    }
+
+    writer.flush()?;
+    println!("Exported {} building centroids to {}", building_count, filename);
+
+    Ok(())
+}
+
+// handle centroid export events
+pub fn handle_export_centroids(
+    mut events: EventReader<ExportCentroidsEvent>,
+    buildings: Query<&Building>,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    for event in events.read() {
+        match export_centroids(&buildings, &event.filename, event.precision) {
+            Ok(()) => {
+                println!("Export successful: {}", event.filename);
+                notifications.write(export_notification(&event.filename));
+            }
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Export failed: {}", e), is_error: true });
+            }
+        }
+    }
+}
+
+// export building data as JSON
+// footprints in the XZ plane plus the adjacency graph as id pairs
+pub fn export_json(
+    buildings: &Query<&Building>,
+    adjacency: &BuildingAdjacency,
+    filename: &str,
+    precision: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    // sorted by id so the output is stable between runs
+    let mut sorted: Vec<&Building> = buildings.iter().collect();
+    sorted.sort_by_key(|building| building.id);
+
+    writeln!(writer, "{{")?;
+    writeln!(writer, "  \"buildings\": [")?;
+    for (i, building) in sorted.iter().enumerate() {
+        let footprint = building.footprint.iter()
+            .map(|p| format!("[{:.*}, {:.*}]", precision, p.x, precision, p.y))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let separator = if i + 1 < sorted.len() { "," } else { "" };
+        writeln!(
+            writer,
+            "    {{ \"id\": {}, \"wall_height\": {:.*}, \"population\": {}, \"footprint\": [{}] }}{}",
+            building.id, precision, building.wall_height, building.population, footprint, separator
+        )?;
+    }
+    writeln!(writer, "  ],")?;
+
+    let edges = adjacency.edges.iter()
+        .map(|(a, b)| format!("[{}, {}]", a, b))
+        .collect::<Vec<_>>()
+        .join(", ");
+    writeln!(writer, "  \"adjacency\": [{}]", edges)?;
+    writeln!(writer, "}}")?;
+
+    writer.flush()?;
+    println!("Exported {} buildings and {} adjacency edges to {}", sorted.len(), adjacency.edges.len(), filename);
+
+    Ok(())
+}
+
+// handle JSON export events
+pub fn handle_export_json(
+    mut events: EventReader<ExportJsonEvent>,
+    buildings: Query<&Building>,
+    adjacency: Res<BuildingAdjacency>,
+    mut notifications: EventWriter<NotificationEvent>,
 ) {
     for event in events.read() {
-        match export_obj(&meshes, &mesh_entities, &event.filename) {
+        match export_json(&buildings, &adjacency, &event.filename, event.precision) {
             Ok(()) => {
                 println!("Export successful: {}", event.filename);
+                notifications.write(export_notification(&event.filename));
             }
             Err(e) => {
                 eprintln!("Export failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Export failed: {}", e), is_error: true });
             }
         }
     }
-}
\ No newline at end of file
+}
+
+// GeoJSON polygon ring as [x, z] pairs, closed (first point repeated)
+// exterior rings are counter-clockwise per RFC 7946
+fn geojson_ring(polygon: &[Vec2], precision: usize) -> String {
+    let mut ring: Vec<Vec2> = polygon.to_vec();
+    if poly::utils::polygon_area(&ring) < 0.0 {
+        ring.reverse();
+    }
+    ring.push(ring[0]);
+    ring.iter()
+        .map(|p| format!("[{:.*}, {:.*}]", precision, p.x, precision, p.y))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// export building footprints as a GeoJSON FeatureCollection for GIS tools
+// coordinates are plain [x, z] metres, there is no geographic reference.
+// the boundary is its own feature (kind "boundary") so consumers know the extent
+pub fn export_geojson(
+    buildings: &Query<&Building>,
+    boundary_polygon: &[Vec2],
+    filename: &str,
+    precision: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = File::create(filename)?;
+    let mut writer = BufWriter::new(file);
+
+    // sorted by id so the output is stable between runs
+    let mut sorted: Vec<&Building> = buildings.iter()
+        .filter(|building| building.footprint.len() >= 3)
+        .collect();
+    sorted.sort_by_key(|building| building.id);
+
+    let mut features = Vec::with_capacity(sorted.len() + 1);
+    if boundary_polygon.len() >= 3 {
+        features.push(format!(
+            "    {{ \"type\": \"Feature\", \"properties\": {{ \"kind\": \"boundary\" }}, \"geometry\": {{ \"type\": \"Polygon\", \"coordinates\": [[{}]] }} }}",
+            geojson_ring(boundary_polygon, precision)
+        ));
+    }
+    for building in &sorted {
+        features.push(format!(
+            "    {{ \"type\": \"Feature\", \"id\": {}, \"properties\": {{ \"kind\": \"building\", \"id\": {}, \"wall_height\": {:.*}, \"population\": {} }}, \"geometry\": {{ \"type\": \"Polygon\", \"coordinates\": [[{}]] }} }}",
+            building.id, building.id, precision, building.wall_height, building.population, geojson_ring(&building.footprint, precision)
+        ));
+    }
+
+    writeln!(writer, "{{")?;
+    writeln!(writer, "  \"type\": \"FeatureCollection\",")?;
+    writeln!(writer, "  \"features\": [")?;
+    writeln!(writer, "{}", features.join(",\n"))?;
+    writeln!(writer, "  ]")?;
+    writeln!(writer, "}}")?;
+
+    writer.flush()?;
+    println!("Exported {} building footprints to {}", sorted.len(), filename);
+
+    Ok(())
+}
+
+// handle GeoJSON export events
+pub fn handle_export_geojson(
+    mut events: EventReader<ExportGeoJsonEvent>,
+    buildings: Query<&Building>,
+    skeleton_data: Res<SkeletonData>,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    for event in events.read() {
+        match export_geojson(&buildings, &skeleton_data.boundary_polygon, &event.filename, event.precision) {
+            Ok(()) => {
+                println!("Export successful: {}", event.filename);
+                notifications.write(export_notification(&event.filename));
+            }
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Export failed: {}", e), is_error: true });
+            }
+        }
+    }
+}
+
+// export the scene split into square tiles, one OBJ per tile with the meshes of its buildings
+// tile x/z are the floored centroid / tile_size, the manifest lists every written tile
+// returns the number of tiles written
+pub fn export_tiles(
+    meshes: &Assets<Mesh>,
+    materials: &Assets<StandardMaterial>,
+    buildings: &Query<(&Building, &Children)>,
+    mesh_entities: &ExportMeshes,
+    event: &ExportTilesEvent,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let tile_size = event.tile_size.max(f32::EPSILON);
+
+    // sorted tiles and building ids, so reruns write the same files
+    let mut tiles: BTreeMap<(i32, i32), Vec<(&Building, &Children)>> = BTreeMap::new();
+    for (building, children) in buildings.iter() {
+        let centroid = poly::utils::polygon_centroid(&building.footprint, poly::utils::polygon_area(&building.footprint));
+        let tile = ((centroid.x / tile_size).floor() as i32, (centroid.y / tile_size).floor() as i32);
+        tiles.entry(tile).or_default().push((building, children));
+    }
+    if tiles.is_empty() {
+        return Err("no buildings to export".into());
+    }
+
+    std::fs::create_dir_all(&event.directory)?;
+    let manifest_file = File::create(format!("{}/manifest.json", event.directory))?;
+    let mut manifest = BufWriter::new(manifest_file);
+    writeln!(manifest, "{{")?;
+    writeln!(manifest, "  \"tile_size\": {},", tile_size)?;
+    writeln!(manifest, "  \"tiles\": [")?;
+
+    let tile_count = tiles.len();
+    for (i, ((x, z), mut tile_buildings)) in tiles.into_iter().enumerate() {
+        tile_buildings.sort_by_key(|(building, _)| building.id);
+
+        let filename = format!("tile_{}_{}.obj", x, z);
+        let tile_meshes = tile_buildings.iter()
+            .flat_map(|(_, children)| children.iter())
+            .filter_map(|child| mesh_entities.get(child).ok())
+            .filter(|(_, _, visibility, is_footprint, _, lod_culled)| event.filter.keeps(visibility, *is_footprint, *lod_culled))
+            .filter_map(|(mesh3d, transform, _, _, material, _)| Some((meshes.get(&mesh3d.0)?, *transform, material_color(materials, material))));
+        write_obj(&format!("{}/{}", event.directory, filename), tile_meshes, event.precision)?;
+
+        let ids = tile_buildings.iter()
+            .map(|(building, _)| building.id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let separator = if i + 1 < tile_count { "," } else { "" };
+        writeln!(
+            manifest,
+            "    {{ \"x\": {}, \"z\": {}, \"file\": \"{}\", \"min\": [{}, {}], \"max\": [{}, {}], \"buildings\": [{}] }}{}",
+            x, z, filename,
+            x as f32 * tile_size, z as f32 * tile_size, (x + 1) as f32 * tile_size, (z + 1) as f32 * tile_size,
+            ids, separator
+        )?;
+    }
+
+    writeln!(manifest, "  ]")?;
+    writeln!(manifest, "}}")?;
+    manifest.flush()?;
+    println!("Exported {} tiles to {}", tile_count, event.directory);
+
+    Ok(tile_count)
+}
+
+// handle tiled export events
+pub fn handle_export_tiles(
+    mut events: EventReader<ExportTilesEvent>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    buildings: Query<(&Building, &Children)>,
+    mesh_entities: ExportMeshes,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    for event in events.read() {
+        match export_tiles(&meshes, &materials, &buildings, &mesh_entities, event) {
+            Ok(_) => {
+                println!("Export successful: {}", event.directory);
+                notifications.write(export_notification(&event.directory));
+            }
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Export failed: {}", e), is_error: true });
+            }
+        }
+    }
+}
+
+// export the morph between two seeds as a numbered OBJ sequence
+// generators of both seeds are interpolated, each step is run through vpoly and subdivision headlessly.
+// subdivision always uses from_seed so plots stay stable between frames
+pub fn export_morph(
+    params: &Params,
+    data: &SkeletonData,
+    event: &ExportMorphEvent,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let from = crate::systems::mesh::town::generate_generators(params, &data.boundary_polygon, &data.road_path, event.from_seed);
+    let to = crate::systems::mesh::town::generate_generators(params, &data.boundary_polygon, &data.road_path, event.to_seed);
+
+    // only the regular generators morph, the road and boundary generators are the same for both seeds
+    let (road_count, boundary_count) = (to.road.len(), to.boundary.len());
+    let fixed = &to.points[to.road.start..];
+
+    let steps = event.steps.max(1);
+    let mut frame = data.clone();
+    for step in 0..=steps {
+        let t = step as f32 / steps as f32;
+        let mut generators = poly::point_gen::interpolate_generators(&from.points[..from.road.start], &to.points[..to.road.start], t);
+        let regular_count = generators.len();
+        generators.extend_from_slice(fixed);
+        // paths cross mid-morph, keep generators from landing on top of each other
+        let generators = poly::point_gen::enforce_min_spacing(generators, regular_count, params.min_generator_spacing);
+
+        let voronoi_data = poly::voronoi::vpoly(generators.clone(), None, &data.boundary_polygon, params.circumcenter_merge_threshold);
+        let road = regular_count..regular_count + road_count;
+        let boundary = road.end..road.end + boundary_count;
+        frame.set_generators(crate::systems::mesh::town::Generators { points: generators, road, boundary, relaxation_steps: to.relaxation_steps });
+        frame.apply_voronoi(voronoi_data);
+
+        let meshes = crate::systems::mesh::town::town_building_meshes(event.from_seed, params, &frame);
+        // headless meshes are never spawned, they are already in world space
+        let placed = meshes.iter().map(|mesh| (mesh, GlobalTransform::IDENTITY, None));
+        write_obj(&format!("{}_{:03}.obj", event.prefix, step), placed, event.precision)?;
+    }
+
+    println!("Exported {} morph frames to {}_*.obj", steps + 1, event.prefix);
+
+    Ok(steps + 1)
+}
+
+// handle seed morph export events
+pub fn handle_export_morph(
+    mut events: EventReader<ExportMorphEvent>,
+    params: Res<Params>,
+    skeleton_data: Res<SkeletonData>,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    for event in events.read() {
+        match export_morph(&params, &skeleton_data, event) {
+            Ok(frames) => {
+                println!("Export successful: {}_*.obj", event.prefix);
+                notifications.write(NotificationEvent { message: format!("Exported {} frames to {}_*.obj", frames, event.prefix), is_error: false });
+            }
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Export failed: {}", e), is_error: true });
+            }
+        }
+    }
+}
+
+// export a top-down orthographic plan of the settlement as PNG
+// a separate camera renders into an offscreen image framed to the boundary,
+// so the user's view and the egui panel are left alone. gizmos are switched off meanwhile
+pub fn handle_export_plan(
+    mut commands: Commands,
+    mut events: EventReader<ExportPlanEvent>,
+    mut images: ResMut<Assets<Image>>,
+    mut config_store: ResMut<GizmoConfigStore>,
+    mut plan: ResMut<PlanCapture>,
+    skeleton_data: Res<SkeletonData>,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    // capture in progress, give the camera a frame to render before grabbing the image
+    if plan.camera.is_some() {
+        events.clear();
+        if !plan.requested {
+            plan.frames += 1;
+            if plan.frames >= 2 {
+                let filename = plan.filename.clone();
+                commands.spawn(Screenshot::image(plan.image.clone()))
+                    .observe(move |trigger: Trigger<ScreenshotCaptured>,
+                                   commands: Commands,
+                                   config_store: ResMut<GizmoConfigStore>,
+                                   plan: ResMut<PlanCapture>,
+                                   notifications: EventWriter<NotificationEvent>| {
+                        finish_plan_capture(trigger, &filename, commands, config_store, plan, notifications);
+                    });
+                plan.requested = true;
+            }
+        }
+        return;
+    }
+
+    let Some(event) = events.read().last() else { return };
+
+    let boundary = &skeleton_data.boundary_polygon;
+    if boundary.len() < 3 {
+        notifications.write(NotificationEvent { message: "Plan export failed: no boundary".to_string(), is_error: true });
+        return;
+    }
+    let (min, max) = boundary.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), v| (min.min(*v), max.max(*v)),
+    );
+    let center = (min + max) * 0.5;
+    let extent = (max - min).max_element() * crate::config::PLAN_MARGIN;
+
+    // offscreen render target
+    let size = Extent3d { width: event.resolution, height: event.resolution, depth_or_array_layers: 1 };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC
+        | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+
+    // straight down, north (-Z) at the top of the image
+    let camera = commands.spawn((
+        Camera3d::default(),
+        Camera {
+            target: RenderTarget::Image(image.clone().into()),
+            order: -1,
+            ..default()
+        },
+        Projection::from(OrthographicProjection {
+            scaling_mode: ScalingMode::Fixed { width: extent, height: extent },
+            ..OrthographicProjection::default_3d()
+        }),
+        Transform::from_xyz(center.x, 500.0, center.y).looking_at(Vec3::new(center.x, 0.0, center.y), Vec3::NEG_Z),
+    )).id();
+
+    // clean capture, no debug overlays
+    plan.gizmos_enabled = config_store.iter_mut()
+        .map(|(type_id, config, _)| {
+            let enabled = config.enabled;
+            config.enabled = false;
+            (*type_id, enabled)
+        })
+        .collect();
+
+    plan.camera = Some(camera);
+    plan.image = image;
+    plan.filename = event.filename.clone();
+    plan.frames = 0;
+    plan.requested = false;
+}
+
+// save the captured plan, then tear the capture down again
+fn finish_plan_capture(
+    trigger: Trigger<ScreenshotCaptured>,
+    filename: &str,
+    mut commands: Commands,
+    mut config_store: ResMut<GizmoConfigStore>,
+    mut plan: ResMut<PlanCapture>,
+    mut notifications: EventWriter<NotificationEvent>,
+) {
+    let result = trigger.event().0.clone().try_into_dynamic()
+        .map_err(|e| e.to_string())
+        .and_then(|image| image.to_rgb8().save(filename).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => {
+            println!("Export successful: {}", filename);
+            notifications.write(export_notification(filename));
+        }
+        Err(e) => {
+            eprintln!("Export failed: {}", e);
+            notifications.write(NotificationEvent { message: format!("Export failed: {}", e), is_error: true });
+        }
+    }
+
+    if let Some(camera) = plan.camera {
+        commands.entity(camera).try_despawn();
+    }
+    for (type_id, enabled) in std::mem::take(&mut plan.gizmos_enabled) {
+        if let Some((config, _)) = config_store.get_config_mut_dyn(&type_id) {
+            config.enabled = enabled;
+        }
+    }
+    *plan = PlanCapture::default();
+}
@@ -1,7 +1,8 @@
 use bevy::prelude::*;
-use bevy::render::camera::Camera;
 use bevy::gizmos::config::{GizmoConfigGroup, GizmoConfigStore};
 
+use crate::systems::interaction::MainCamera;
+
 
 // this is a camera-based infinite grid
 // so user can determine their bearings in 3d space, and for sense of scales
@@ -29,6 +30,10 @@ pub struct GridConfig {
     pub minor_color: Color,
     pub grid_size: f32,
     pub enabled: bool,
+    // grid space placement, lets the grid line up with a rotated settlement
+    pub origin: Vec2,   // world xz of a grid intersection
+    pub rotation: f32,  // radians, counter clockwise seen from above
+    pub snap: bool,     // snap dragged and placed points to minor intersections
 }
 
 impl Default for GridConfig {
@@ -40,10 +45,31 @@ impl Default for GridConfig {
             minor_color: Color::srgba(0.3, 0.3, 0.3, 0.05),
             grid_size: 1000.0,
             enabled: true,
+            origin: Vec2::ZERO,
+            rotation: 0.0,
+            snap: false,
         }
     }
 }
 
+impl GridConfig {
+    // world xz -> grid space, the inverse of to_world
+    pub fn to_grid(&self, world: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.rotation).rotate(world - self.origin)
+    }
+
+    // grid space -> world xz
+    pub fn to_world(&self, grid: Vec2) -> Vec2 {
+        self.origin + Vec2::from_angle(self.rotation).rotate(grid)
+    }
+
+    // nearest minor intersection, rounded in grid space so it follows the rotation
+    pub fn snap_point(&self, world: Vec2) -> Vec2 {
+        let spacing = self.minor_spacing.max(f32::EPSILON);
+        self.to_world((self.to_grid(world) / spacing).round() * spacing)
+    }
+}
+
 fn setup_gizmos(
     mut config_store: ResMut<GizmoConfigStore>
 ) {
@@ -54,26 +80,35 @@ fn setup_gizmos(
 fn draw_grid(
     mut gizmos: Gizmos<GridGizmoGroup>,
     params: Res<GridConfig>,
-    camera_query: Query<&Transform, With<Camera>>,
+    mut main_camera: MainCamera,
 ) {
     if !params.enabled {
         return;
     }
 
-    let Ok(camera_transform) = camera_query.single() else {
+    // follows the rts camera, a second camera doesn't knock the grid out
+    let Some((_, camera_transform)) = main_camera.get("draw_grid") else {
         return;
     };
 
-    let camera_pos = camera_transform.translation;
+    // lines are laid out in grid space around the camera, then rotated back into the world
+    let camera_translation = camera_transform.translation();
+    let camera_pos = params.to_grid(Vec2::new(camera_translation.x, camera_translation.z));
     let grid_size = params.grid_size;
     let major_spacing = params.major_spacing;
     let minor_spacing = params.minor_spacing;
 
+    let mut line = |a: Vec2, b: Vec2, y: f32, color: Color| {
+        let a = params.to_world(a);
+        let b = params.to_world(b);
+        gizmos.line(Vec3::new(a.x, y, a.y), Vec3::new(b.x, y, b.y), color);
+    };
+
     // get grid bounds relative to camera position
     let min_x = camera_pos.x - grid_size;
     let max_x = camera_pos.x + grid_size;
-    let min_z = camera_pos.z - grid_size;
-    let max_z = camera_pos.z + grid_size;
+    let min_z = camera_pos.y - grid_size;
+    let max_z = camera_pos.y + grid_size;
 
     // minor grid lines
     let start_x = (min_x / minor_spacing).floor() * minor_spacing;
@@ -83,11 +118,7 @@ fn draw_grid(
     while x <= max_x {
         // skip the ones that would be major
         if (x % major_spacing).abs() > f32::EPSILON {
-            gizmos.line(
-                Vec3::new(x, -0.01, min_z),
-                Vec3::new(x, -0.01, max_z),
-                params.minor_color,
-            );
+            line(Vec2::new(x, min_z), Vec2::new(x, max_z), -0.01, params.minor_color);
         }
         x += minor_spacing;
     }
@@ -96,11 +127,7 @@ fn draw_grid(
     while z <= max_z {
         // skip the lines that would be major
         if (z % major_spacing).abs() > f32::EPSILON {
-            gizmos.line(
-                Vec3::new(min_x, -0.02, z),
-                Vec3::new(max_x, -0.02, z),
-                params.minor_color,
-            );
+            line(Vec2::new(min_x, z), Vec2::new(max_x, z), -0.02, params.minor_color);
         }
         z += minor_spacing;
     }
@@ -111,21 +138,13 @@ fn draw_grid(
 
     let mut x = major_start_x;
     while x <= max_x {
-        gizmos.line(
-            Vec3::new(x, -0.02, min_z),
-            Vec3::new(x, -0.02, max_z),
-            params.major_color,
-        );
+        line(Vec2::new(x, min_z), Vec2::new(x, max_z), -0.02, params.major_color);
         x += major_spacing;
     }
 
     let mut z = major_start_z;
     while z <= max_z {
-        gizmos.line(
-            Vec3::new(min_x, -0.02, z),
-            Vec3::new(max_x, -0.02, z),
-            params.major_color,
-        );
+        line(Vec2::new(min_x, z), Vec2::new(max_x, z), -0.02, params.major_color);
         z += major_spacing;
     }
-}
\ No newline at end of file
+}
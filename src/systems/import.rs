@@ -0,0 +1,233 @@
+// loads externally computed generator points and site outlines
+// generator points replace pgen's output, the diagram is then built around them as usual
+// an outline replaces the generated boundary polygon
+
+use bevy::prelude::*;
+
+use crate::systems::mesh::poly;
+use crate::systems::mesh::{BlockOverrides, BlockSeeds, Params, RegenerateEvent, Seed, SkeletonData};
+use crate::systems::ui::NotificationEvent;
+
+// generator import event
+// relax = run the same relaxation pgen's points get before building the diagram
+#[derive(Event)]
+pub struct ImportGeneratorsEvent {
+    pub filename: String,
+    pub relax: bool,
+}
+
+// boundary import event
+#[derive(Event)]
+pub struct ImportBoundaryEvent {
+    pub filename: String,
+}
+
+// import options set from the UI
+#[derive(Resource)]
+pub struct ImportSettings {
+    pub generators_file: String,
+    pub relax_generators: bool,
+    pub boundary_file: String,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            generators_file: "generators.csv".to_string(),
+            relax_generators: false,
+            boundary_file: "boundary.csv".to_string(),
+        }
+    }
+}
+
+// what was dropped while validating, reported back in the toast
+pub struct GeneratorImport {
+    pub points: Vec<Vec2>,
+    pub duplicates: usize,
+    pub outside: usize,
+}
+
+// parse an "x,z" point list, one point per line, commas or whitespace between the coordinates (csv or .poly)
+// blank lines and # comments are skipped, a non-numeric first row is treated as a header
+pub fn parse_points_csv(text: &str) -> Result<Vec<Vec2>, Box<dyn std::error::Error>> {
+    let mut points = Vec::new();
+    let mut header_skipped = false;
+    for (line_idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let columns: Vec<&str> = line.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|column| !column.is_empty())
+            .collect();
+        let parsed = match columns.as_slice() {
+            [x, z] => x.parse::<f32>().ok().zip(z.parse::<f32>().ok()),
+            _ => None,
+        };
+        match parsed {
+            Some((x, z)) if x.is_finite() && z.is_finite() => points.push(Vec2::new(x, z)),
+            Some(_) => return Err(format!("line {}: coordinates must be finite", line_idx + 1).into()),
+            None if points.is_empty() && !header_skipped => header_skipped = true,
+            None => return Err(format!("line {}: expected \"x,z\", got \"{}\"", line_idx + 1, line).into()),
+        }
+    }
+    Ok(points)
+}
+
+// read and validate a generator file against the current boundary
+// points outside the boundary and near-duplicates (closer than IMPORT_DEDUPE_DISTANCE) are dropped
+pub fn read_generators(filename: &str, boundary_polygon: &[Vec2]) -> Result<GeneratorImport, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(filename)?;
+    let parsed = parse_points_csv(&text)?;
+
+    let mut points: Vec<Vec2> = Vec::with_capacity(parsed.len());
+    let (mut duplicates, mut outside) = (0, 0);
+    for point in parsed {
+        if boundary_polygon.len() >= 3 && !poly::utils::point_in_polygon(&point, boundary_polygon) {
+            outside += 1;
+        } else if points.iter().any(|kept| kept.distance(point) < crate::config::IMPORT_DEDUPE_DISTANCE) {
+            duplicates += 1;
+        } else {
+            points.push(point);
+        }
+    }
+
+    if points.len() < 3 {
+        return Err(format!("need at least 3 distinct points inside the boundary, found {}", points.len()).into());
+    }
+    Ok(GeneratorImport { points, duplicates, outside })
+}
+
+// replace the generators with the imported points and rebuild the diagram
+#[allow(clippy::too_many_arguments)]
+pub fn handle_import_generators(
+    mut events: EventReader<ImportGeneratorsEvent>,
+    mut regen_events: EventWriter<RegenerateEvent>,
+    mut notifications: EventWriter<NotificationEvent>,
+    mut skeleton_data: ResMut<SkeletonData>,
+    mut block_overrides: ResMut<BlockOverrides>,
+    mut block_seeds: ResMut<BlockSeeds>,
+    params: Res<Params>,
+    seed: Res<Seed>,
+) {
+    for event in events.read() {
+        let import = match read_generators(&event.filename, &skeleton_data.boundary_polygon) {
+            Ok(import) => import,
+            Err(e) => {
+                eprintln!("Generator import failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Import failed: {}", e), is_error: true });
+                continue;
+            }
+        };
+
+        let mut generators: Vec<Vec3> = import.points.iter()
+            .map(|p| Vec3::new(p.x, 0.0, p.y))
+            .collect();
+        let mut relaxation_steps = 0;
+        if event.relax {
+            // nothing is fixed, every imported point is free to move
+            let bounds = poly::point_gen::generator_bounds(&skeleton_data.boundary_polygon, crate::config::GENERATOR_BOUNDS_MARGIN);
+            (generators, relaxation_steps) = poly::point_gen::prelax(generators, Vec::new(), params.relaxation_steps, params.relaxation_epsilon, bounds);
+        }
+
+        // imported points are all regular, none of them follow the road or the boundary
+        let count = generators.len();
+        skeleton_data.set_generators(crate::systems::mesh::town::Generators { points: generators, road: count..count, boundary: count..count, relaxation_steps });
+        let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), None, &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+        skeleton_data.apply_voronoi(voronoi_data);
+
+        // block indices refer to the old cells
+        block_overrides.0.clear();
+        block_seeds.0.clear();
+
+        let message = format!(
+            "Imported {} generators ({} duplicates, {} outside the boundary dropped)",
+            import.points.len(), import.duplicates, import.outside
+        );
+        println!("{} from {}", message, event.filename);
+        notifications.write(NotificationEvent { message, is_error: false });
+
+        regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+    }
+}
+
+// read a site outline, one vertex per line in order around the site
+// a closing vertex repeating the first is dropped, the outline must not cross itself
+// outlines with more vertices than BOUNDARY_VERTEX_RANGE allows are simplified down to it
+pub fn read_boundary(filename: &str) -> Result<crate::systems::mesh::Polygon, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(filename)?;
+    let mut boundary: Vec<Vec2> = Vec::new();
+    for point in parse_points_csv(&text)? {
+        if boundary.last().is_none_or(|last| last.distance(point) >= crate::config::IMPORT_DEDUPE_DISTANCE) {
+            boundary.push(point);
+        }
+    }
+    if boundary.len() > 1 && boundary[0].distance(boundary[boundary.len() - 1]) < crate::config::IMPORT_DEDUPE_DISTANCE {
+        boundary.pop();
+    }
+
+    if boundary.len() < 3 {
+        return Err(format!("need at least 3 distinct vertices, found {}", boundary.len()).into());
+    }
+    if !poly::ops::is_simple(&boundary) {
+        return Err("the outline crosses itself".into());
+    }
+
+    // the boundary is stored as a base polygon of boundary_vertex_count vertices plus offsets
+    let range = crate::config::BOUNDARY_VERTEX_RANGE;
+    let boundary = poly::ops::limit_vertices(&boundary, range.clone());
+    if !poly::ops::is_simple(&boundary) {
+        return Err(format!("the outline crosses itself once cut down to {} vertices", range.end()).into());
+    }
+    Ok(boundary)
+}
+
+// replace the boundary with an imported outline and rebuild the town inside it
+// the outline is stored as base polygon + offsets, like any other edited boundary
+#[allow(clippy::too_many_arguments)]
+pub fn handle_import_boundary(
+    mut events: EventReader<ImportBoundaryEvent>,
+    mut regen_events: EventWriter<RegenerateEvent>,
+    mut notifications: EventWriter<NotificationEvent>,
+    mut skeleton_data: ResMut<SkeletonData>,
+    mut block_overrides: ResMut<BlockOverrides>,
+    mut block_seeds: ResMut<BlockSeeds>,
+    mut params: ResMut<Params>,
+    seed: Res<Seed>,
+) {
+    for event in events.read() {
+        let boundary = match read_boundary(&event.filename) {
+            Ok(boundary) => boundary,
+            Err(e) => {
+                eprintln!("Boundary import failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Boundary import failed: {}", e), is_error: true });
+                continue;
+            }
+        };
+
+        params.boundary_vertex_count = boundary.len();
+        let base = poly::point_gen::generate_boundary_polygon(boundary.len(), params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, seed.0);
+        skeleton_data.boundary_vertex_offsets = boundary.iter()
+            .zip(base.iter())
+            .map(|(vertex, base)| *vertex - *base)
+            .collect();
+        skeleton_data.boundary_polygon = boundary;
+
+        // same pipeline as a boundary-mode regeneration
+        let generators = crate::systems::mesh::town::generate_generators(&params, &skeleton_data.boundary_polygon, &skeleton_data.road_path, seed.0);
+        skeleton_data.set_generators(generators);
+        let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), None, &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+        skeleton_data.apply_voronoi(voronoi_data);
+
+        // block indices refer to the old cells
+        block_overrides.0.clear();
+        block_seeds.0.clear();
+
+        let message = format!("Imported a boundary with {} vertices", skeleton_data.boundary_polygon.len());
+        println!("{} from {}", message, event.filename);
+        notifications.write(NotificationEvent { message, is_error: false });
+
+        regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+    }
+}
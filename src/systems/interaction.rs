@@ -1,12 +1,176 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::ecs::query::QuerySingleError;
+use bevy::math::bounding::Aabb2d;
 use bevy::window::{Window, PrimaryWindow};
+use bevy_egui::EguiContexts;
 use bevy_rts_camera::RtsCamera;
 
 use crate::systems::mesh::*;
-use crate::systems::ui::indicator::ModeChangeEvent;
+use crate::systems::ui::indicator::{ModeChangeEvent, NotificationEvent};
+
+// what the skeleton editor sends out: regenerations, mode changes, toasts and block rerolls
+#[derive(SystemParam)]
+pub struct InteractionEvents<'w> {
+    pub regen: EventWriter<'w, RegenerateEvent>,
+    pub mode: EventWriter<'w, ModeChangeEvent>,
+    pub notifications: EventWriter<'w, NotificationEvent>,
+    pub reroll_block: EventWriter<'w, RerollBlockEvent>,
+}
+
+// selection, hover, drag and undo state of the point editor
+#[derive(SystemParam)]
+pub struct EditState<'w> {
+    pub drag: ResMut<'w, DragState>,
+    pub hovered: ResMut<'w, HoveredPoint>,
+    pub selected: ResMut<'w, SelectedPoint>,
+    pub selected_group: ResMut<'w, SelectedPoints>,
+    pub history: ResMut<'w, EditHistory>,
+}
+
+// ground position (x, z) of an editable point in the given mode, None for blocks or a stale index
+fn point_position(skeleton_data: &SkeletonData, edit_mode: EditMode, idx: usize) -> Option<Vec2> {
+    let xz = |point: &Vec3| Vec2::new(point.x, point.z);
+    match edit_mode {
+        EditMode::Generators => skeleton_data.generator_points.get(idx).map(xz),
+        EditMode::Circumcenters => skeleton_data.points.get(idx).map(xz),
+        EditMode::Roads => skeleton_data.road_path.get(idx).map(xz),
+        EditMode::Boundary => skeleton_data.get_boundary_vertex(idx),
+        EditMode::Blocks => None,
+    }
+}
+
+fn point_count(skeleton_data: &SkeletonData, edit_mode: EditMode) -> usize {
+    match edit_mode {
+        EditMode::Generators => skeleton_data.generator_points.len(),
+        EditMode::Circumcenters => skeleton_data.points.len(),
+        EditMode::Roads => skeleton_data.road_path.len(),
+        EditMode::Boundary => skeleton_data.boundary_polygon.len(),
+        EditMode::Blocks => 0,
+    }
+}
+
+// skeleton with a new boundary of a different vertex count, rebuilt like boundary-mode regeneration would
+// the base polygon changes with the vertex count, so offsets are re-derived against the new base
+fn with_boundary(skeleton_data: &SkeletonData, boundary_polygon: crate::systems::mesh::Polygon, params: &Params, seed: u64) -> SkeletonData {
+    let mut candidate = skeleton_data.clone();
+    let base_polygon = crate::systems::mesh::poly::point_gen::generate_boundary_polygon(
+        boundary_polygon.len(),
+        params.boundary_scale,
+        params.boundary_aspect,
+        params.boundary_noise_freq,
+        params.boundary_noise_amp,
+        seed
+    );
+    candidate.boundary_vertex_offsets = boundary_polygon.iter()
+        .zip(base_polygon.iter())
+        .map(|(vertex, base)| *vertex - *base)
+        .collect();
+    candidate.boundary_polygon = boundary_polygon;
+
+    let generators = crate::systems::mesh::town::generate_generators(params, &candidate.boundary_polygon, &candidate.road_path, seed);
+    candidate.set_generators(generators);
+    let voronoi_data = crate::systems::mesh::poly::voronoi::vpoly(candidate.generator_points.clone(), None, &candidate.boundary_polygon, params.circumcenter_merge_threshold);
+    candidate.apply_voronoi(voronoi_data);
+    candidate
+}
+
+// select the element behind a validity report entry and move the camera onto it
+// duplicate points select the first point in circumcenter mode,
+// cell issues select the owning generator in generator mode and outline the cell
+#[allow(clippy::too_many_arguments)]
+pub fn handle_jump_to_issue(
+    mut events: EventReader<JumpToIssueEvent>,
+    skeleton_data: Res<SkeletonData>,
+    mut edit_mode: ResMut<EditMode>,
+    mut selected_point: ResMut<SelectedPoint>,
+    mut drag_state: ResMut<DragState>,
+    mut highlighted: ResMut<HighlightedIssue>,
+    mut mode_events: EventWriter<ModeChangeEvent>,
+    mut cameras: Query<&mut RtsCamera>,
+) {
+    for JumpToIssueEvent(issue) in events.read() {
+        let (mode, selection) = match *issue {
+            ValidityIssue::Empty => continue,
+            ValidityIssue::DuplicatePoints(a, _) => (EditMode::Circumcenters, Some(a)),
+            ValidityIssue::TooFewVertices { generator, .. }
+            | ValidityIssue::IndexOutOfBounds { generator, .. }
+            | ValidityIssue::DegenerateCell { generator, .. } => (EditMode::Generators, generator),
+        };
+
+        if *edit_mode != mode {
+            *edit_mode = mode;
+            mode_events.write(ModeChangeEvent(mode));
+        }
+        selected_point.0 = selection;
+        drag_state.dragging_point_index = None;
+        highlighted.0 = Some(*issue);
+
+        if let Some(target) = issue.location(&skeleton_data) {
+            for mut camera in cameras.iter_mut() {
+                camera.target_focus.translation = Vec3::new(target.x, 0.0, target.y);
+            }
+        }
+    }
+}
+
+// keep camera panning scoped to the settlement
+// bounds follow the boundary's bounding box plus a margin, the camera clamps its focus to them
+pub fn fit_camera_bounds(
+    skeleton_data: Res<SkeletonData>,
+    mut cameras: Query<&mut RtsCamera>,
+) {
+    if !skeleton_data.is_changed() || skeleton_data.boundary_polygon.is_empty() {
+        return;
+    }
+
+    let boundary = &skeleton_data.boundary_polygon;
+    let min = boundary.iter().copied().fold(Vec2::splat(f32::INFINITY), Vec2::min);
+    let max = boundary.iter().copied().fold(Vec2::splat(f32::NEG_INFINITY), Vec2::max);
+    let bounds = Aabb2d {
+        min: min - Vec2::splat(crate::config::CAMERA_BOUNDS_MARGIN),
+        max: max + Vec2::splat(crate::config::CAMERA_BOUNDS_MARGIN),
+    };
+
+    for mut camera in cameras.iter_mut() {
+        if camera.bounds != bounds {
+            camera.bounds = bounds;
+        }
+    }
+}
+
+// the RtsCamera the user looks through, other cameras (plan capture etc) are ignored
+// a missing or duplicated camera is logged once per failure streak instead of failing silently
+#[derive(SystemParam)]
+pub struct MainCamera<'w, 's> {
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<RtsCamera>>,
+    warned: Local<'s, bool>,
+}
+
+impl MainCamera<'_, '_> {
+    pub fn get(&mut self, system: &str) -> Option<(&Camera, &GlobalTransform)> {
+        match self.cameras.single() {
+            Ok(camera) => {
+                *self.warned = false;
+                Some(camera)
+            }
+            Err(error) => {
+                if !*self.warned {
+                    match error {
+                        QuerySingleError::NoEntities(_) => warn!("{}: no RtsCamera found, skipping", system),
+                        QuerySingleError::MultipleEntities(_) => warn!("{}: more than one RtsCamera found, skipping", system),
+                    }
+                    *self.warned = true;
+                }
+                None
+            }
+        }
+    }
+}
 
 // screen to world conversion, on 0-plane
 // util function
+#[allow(clippy::too_many_arguments)]
 fn screen_to_world_on_plane(
     screen_pos: Vec2,
     camera: &Camera,
@@ -32,38 +196,117 @@ fn screen_to_world_on_plane(
     Some(ray.origin + ray.direction * t)
 }
 
+// hover/select buildings under the cursor
+// active whenever the skeleton editor isn't using the mouse
+#[allow(clippy::too_many_arguments)]
+pub fn handle_building_picking(
+    mut contexts: EguiContexts,
+    mut hovered_building: ResMut<HoveredBuilding>,
+    mut selected_building: ResMut<SelectedBuilding>,
+    generation_mode: Res<GenerationMode>,
+    gizmos_visible: Res<crate::systems::ui::GizmosVisible>,
+    is_3d: Res<crate::systems::ui::Is3D>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut main_camera: MainCamera,
+    buildings: Query<(Entity, &crate::systems::mesh::town::Building)>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+) {
+    // drop selections whose building was despawned by a regeneration
+    if selected_building.0.is_some_and(|entity| !buildings.contains(entity)) {
+        selected_building.0 = None;
+    }
+
+    if *generation_mode == GenerationMode::Manual && gizmos_visible.0 {
+        hovered_building.0 = None;
+        return;
+    }
+
+    // ignore the cursor while it's over the UI
+    if let Ok(ctx) = contexts.ctx_mut() && ctx.is_pointer_over_area() {
+        hovered_building.0 = None;
+        return;
+    }
+
+    let Ok(window) = windows.single() else { return };
+    let Some((camera, camera_transform)) = main_camera.get("handle_building_picking") else { return };
+    let Some(cursor_pos) = window.cursor_position() else {
+        hovered_building.0 = None;
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_pos) else { return };
+
+    // intersect the ray with each building's roof plane (ground in 2D),
+    // nearest footprint hit wins
+    let mut closest: Option<(Entity, f32)> = None;
+    if ray.direction.y.abs() > f32::EPSILON {
+        for (entity, building) in buildings.iter() {
+            let height = if is_3d.0 { building.wall_height } else { 0.0 };
+            let t = (height - ray.origin.y) / ray.direction.y;
+            if t < 0.0 || closest.is_some_and(|(_, best)| best <= t) {
+                continue;
+            }
+
+            let hit = ray.origin + ray.direction * t;
+            if crate::systems::mesh::poly::utils::point_in_polygon(&Vec2::new(hit.x, hit.z), &building.footprint) {
+                closest = Some((entity, t));
+            }
+        }
+    }
+
+    hovered_building.0 = closest.map(|(entity, _)| entity);
+
+    if mouse_button.just_pressed(MouseButton::Left) {
+        selected_building.0 = hovered_building.0;
+    }
+}
+
 // handle mouse interactions with circumcenter points
 // for manual mode
+#[allow(clippy::too_many_arguments, clippy::collapsible_if)]
 pub fn handle_mouse_interaction(
+    mut contexts: EguiContexts,
     mut skeleton_data: ResMut<SkeletonData>,
     mut edit_mode: ResMut<EditMode>,
-    mut drag_state: ResMut<DragState>,
-    mut hovered_point: ResMut<HoveredPoint>,
-    mut selected_point: ResMut<SelectedPoint>,
-    mut regen_events: EventWriter<RegenerateEvent>,
-    mut mode_events: EventWriter<ModeChangeEvent>,
+    edit_state: EditState,
+    events: InteractionEvents,
     seed: Res<Seed>,
-    params: Res<crate::systems::mesh::Params>,
+    mut params: ResMut<crate::systems::mesh::Params>,
     generation_mode: Res<GenerationMode>,
     gizmos_visible: Res<crate::systems::ui::GizmosVisible>,
     windows: Query<&Window, With<PrimaryWindow>>,
-    camera_query: Query<(&Camera, &GlobalTransform), With<RtsCamera>>,
+    mut main_camera: MainCamera,
     mouse_button: Res<ButtonInput<MouseButton>>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    mut block_overrides: ResMut<BlockOverrides>,
+    grid: Res<crate::systems::grid::GridConfig>,
 ) {
+    let InteractionEvents { regen: mut regen_events, mode: mut mode_events, mut notifications, mut reroll_block } = events;
+    let EditState { drag: mut drag_state, hovered: mut hovered_point, selected: mut selected_point, mut selected_group, mut history } = edit_state;
+
+    // the group follows the single selection, anything that replaced or cleared it drops the group
+    if !selected_point.0.is_some_and(|idx| selected_group.0.contains(&idx)) {
+        selected_group.0 = selected_point.0.into_iter().collect();
+    }
+
     // check if user in edit mode
     if *generation_mode != GenerationMode::Manual || !gizmos_visible.0 {
         // user cannot modift points outside of edit mode
         return;
     }
 
+    // typing into a text field, the letters and backspace belong to it
+    if let Ok(ctx) = contexts.ctx_mut() && ctx.wants_keyboard_input() {
+        return;
+    }
+
     // handle edit mode switching; E = forward; Q = backward
     if keyboard.just_pressed(KeyCode::KeyE) {
         *edit_mode = match *edit_mode {
             EditMode::Boundary => EditMode::Roads,
             EditMode::Roads => EditMode::Generators,
             EditMode::Generators => EditMode::Circumcenters,
-            EditMode::Circumcenters => EditMode::Boundary,
+            EditMode::Circumcenters => EditMode::Blocks,
+            EditMode::Blocks => EditMode::Boundary,
         };
         // reset selection when changing modes
         selected_point.0 = None;
@@ -75,7 +318,8 @@ pub fn handle_mouse_interaction(
     
     if keyboard.just_pressed(KeyCode::KeyQ) {
         *edit_mode = match *edit_mode {
-            EditMode::Boundary => EditMode::Circumcenters,
+            EditMode::Boundary => EditMode::Blocks,
+            EditMode::Blocks => EditMode::Circumcenters,
             EditMode::Circumcenters => EditMode::Generators,
             EditMode::Generators => EditMode::Roads,
             EditMode::Roads => EditMode::Boundary,
@@ -89,7 +333,7 @@ pub fn handle_mouse_interaction(
     }
 
     let Ok(window) = windows.single() else { return };
-    let Ok((camera, camera_transform)) = camera_query.single() else { return };
+    let Some((camera, camera_transform)) = main_camera.get("handle_mouse_interaction") else { return };
     let Some(cursor_pos) = window.cursor_position() else { return };
 
     let Some(world_pos) = screen_to_world_on_plane(cursor_pos, camera, camera_transform) else { return };
@@ -161,6 +405,27 @@ pub fn handle_mouse_interaction(
                 .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
                 .map(|(i, _)| i)
         }
+        EditMode::Blocks => {
+            // block (voronoi cell) under the cursor
+            // merged cells resolve to the block's lowest cell, that is where its override lives
+            let cursor = Vec2::new(world_pos.x, world_pos.z);
+            let cell = skeleton_data.cells.iter()
+                .position(|cell| {
+                    let polygon: Vec<Vec2> = cell.iter()
+                        .filter_map(|&idx| skeleton_data.points.get(idx))
+                        .map(|p| Vec2::new(p.x, p.z))
+                        .collect();
+                    crate::systems::mesh::poly::utils::point_in_polygon(&cursor, &polygon)
+                });
+            cell.map(|cell| {
+                if params.block_merge_prob <= 0.0 {
+                    return cell;
+                }
+                skeleton_data.merged_blocks(&params, seed.0).into_iter()
+                    .find(|(cells, _)| cells.contains(&cell))
+                    .map_or(cell, |(cells, _)| cells[0])
+            })
+        }
     };
 
     hovered_point.0 = closest_point;
@@ -176,7 +441,8 @@ pub fn handle_mouse_interaction(
             match *edit_mode {
                 EditMode::Generators => {
                     // remove generator point
-                    skeleton_data.generator_points.remove(point_idx);
+                    history.record(&skeleton_data);
+                    skeleton_data.remove_generator(point_idx);
                     selected_point.0 = None;
                     drag_state.dragging_point_index = None;
                     hovered_point.0 = None;
@@ -184,24 +450,72 @@ pub fn handle_mouse_interaction(
                 }
                 EditMode::Roads => {
                     // remove road point
+                    history.record(&skeleton_data);
                     skeleton_data.road_path.remove(point_idx);
+                    if point_idx < skeleton_data.road_widths.len() {
+                        skeleton_data.road_widths.remove(point_idx);
+                    }
                     selected_point.0 = None;
                     drag_state.dragging_point_index = None;
                     hovered_point.0 = None;
                     regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
                 }
                 EditMode::Circumcenters => {
-                    // circumcenters don't support deletion for now
-                    // as of yet :)
+                    // try on a copy first, refuse if the diagram would break
+                    let mut candidate = skeleton_data.clone();
+                    candidate.remove_circumcenter(point_idx);
+                    match candidate.validate().first() {
+                        None => {
+                            history.record(&skeleton_data);
+                            *skeleton_data = candidate;
+                            selected_point.0 = None;
+                            drag_state.dragging_point_index = None;
+                            hovered_point.0 = None;
+                            regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+                        }
+                        Some(issue) => {
+                            notifications.write(NotificationEvent { message: format!("Can't delete circumcenter: {}", issue.describe()), is_error: true });
+                        }
+                    }
                 }
                 EditMode::Boundary => {
-                    // boundary vertices don't support deletion for now
-                    // need minimum vertices for valid polygon
+                    // the vertex count has to stay within the slider range
+                    if skeleton_data.boundary_polygon.len() <= *crate::config::BOUNDARY_VERTEX_RANGE.start() {
+                        notifications.write(NotificationEvent { message: format!("Can't delete boundary vertex: a boundary needs at least {} vertices", crate::config::BOUNDARY_VERTEX_RANGE.start()), is_error: true });
+                    } else if point_idx < skeleton_data.boundary_polygon.len() {
+                        let mut boundary = skeleton_data.boundary_polygon.clone();
+                        boundary.remove(point_idx);
+                        let vertex_count = boundary.len();
+                        let candidate = with_boundary(&skeleton_data, boundary, &params, seed.0);
+
+                        match candidate.validate().first() {
+                            None => {
+                                history.record(&skeleton_data);
+                                *skeleton_data = candidate;
+                                params.boundary_vertex_count = vertex_count;
+                                selected_point.0 = None;
+                                drag_state.dragging_point_index = None;
+                                hovered_point.0 = None;
+                                regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+                            }
+                            Some(issue) => {
+                                notifications.write(NotificationEvent { message: format!("Can't delete boundary vertex: {}", issue.describe()), is_error: true });
+                            }
+                        }
+                    }
+                }
+                EditMode::Blocks => {
+                    // reset the block back to the global params
+                    if block_overrides.0.remove(&point_idx).is_some() {
+                        regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+                    }
                 }
             }
         } else if *edit_mode == EditMode::Roads {
             // no selection in roads mode, clear entire path
+            history.record(&skeleton_data);
             skeleton_data.road_path.clear();
+            skeleton_data.road_widths.clear();
             selected_point.0 = None;
             drag_state.dragging_point_index = None;
             regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
@@ -211,7 +525,10 @@ pub fn handle_mouse_interaction(
     // backspace for roads mode; remove last point
     if *edit_mode == EditMode::Roads && keyboard.just_pressed(KeyCode::Backspace) {
         if !skeleton_data.road_path.is_empty() {
+            history.record(&skeleton_data);
             skeleton_data.road_path.pop();
+            let road_len = skeleton_data.road_path.len();
+            skeleton_data.road_widths.truncate(road_len);
             // reset states if we removed the selected/dragged point
             if let Some(selected_idx) = selected_point.0 {
                 if selected_idx >= skeleton_data.road_path.len() {
@@ -227,16 +544,59 @@ pub fn handle_mouse_interaction(
         }
     }
 
+    // blocks mode; adjust the selected block's subdivision
+    // space toggles no subdivision, +/- steps finer/coarser, R re-rolls just this block
+    if *edit_mode == EditMode::Blocks && let Some(block_idx) = selected_point.0 {
+        if keyboard.just_pressed(KeyCode::KeyR) {
+            reroll_block.write(RerollBlockEvent(block_idx));
+        }
+
+        let current = block_overrides.0.get(&block_idx).copied();
+        let new = if keyboard.just_pressed(KeyCode::Space) {
+            Some(if current == Some(BlockOverride::Whole) { None } else { Some(BlockOverride::Whole) })
+        } else if keyboard.just_pressed(KeyCode::Equal) || keyboard.just_pressed(KeyCode::NumpadAdd) {
+            Some(BlockOverride::step(current, 1))
+        } else if keyboard.just_pressed(KeyCode::Minus) || keyboard.just_pressed(KeyCode::NumpadSubtract) {
+            Some(BlockOverride::step(current, -1))
+        } else {
+            None
+        };
+
+        if let Some(new) = new && new != current {
+            match new {
+                Some(block_override) => block_overrides.0.insert(block_idx, block_override),
+                None => block_overrides.0.remove(&block_idx),
+            };
+            regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+        }
+    }
+
+    // generators mode; C places the settlement core under the cursor
+    // regenerates the generators so the core bias applies
+    if *edit_mode == EditMode::Generators && keyboard.just_pressed(KeyCode::KeyC) {
+        params.core_point = Some(Vec2::new(world_pos.x, world_pos.z));
+        selected_point.0 = None;
+        drag_state.dragging_point_index = None;
+        regen_events.write(RegenerateEvent { seed: seed.0, user_edit: false });
+    }
+
+    // grid snapping, held alt places/drags freely for the moment
+    let snap = grid.snap && !keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+
     // handle point creation
     if mouse_button.just_pressed(MouseButton::Right) {
-        let new_point = Vec3::new(world_pos.x, 0.0, world_pos.z);
+        let placed = Vec2::new(world_pos.x, world_pos.z);
+        let placed = if snap { grid.snap_point(placed) } else { placed };
+        let new_point = Vec3::new(placed.x, 0.0, placed.y);
         match *edit_mode {
             EditMode::Generators => {
+                history.record(&skeleton_data);
                 skeleton_data.generator_points.push(new_point);
                 selected_point.0 = Some(skeleton_data.generator_points.len() - 1);
                 regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
             }
             EditMode::Roads => {
+                history.record(&skeleton_data);
                 skeleton_data.road_path.push(new_point);
                 selected_point.0 = Some(skeleton_data.road_path.len() - 1);
                 regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
@@ -245,78 +605,193 @@ pub fn handle_mouse_interaction(
                 // circumcenters mode doesn't support point creation
             }
             EditMode::Boundary => {
-                // boundary mode doesn't support point creation for now
-                // would need to insert vertex into polygon properly
+                // split the boundary edge nearest the click at the projected point
+                let boundary = &skeleton_data.boundary_polygon;
+                let nearest_edge = (0..boundary.len())
+                    .map(|edge| {
+                        let (start, end) = (boundary[edge], boundary[(edge + 1) % boundary.len()]);
+                        let t = ((placed - start).dot(end - start) / (end - start).length_squared().max(1e-6)).clamp(0.0, 1.0);
+                        let projected = start + (end - start) * t;
+                        (edge, projected, projected.distance(placed))
+                    })
+                    .min_by(|a, b| a.2.total_cmp(&b.2))
+                    .filter(|&(_, _, distance)| distance <= SELECTION_RADIUS);
+
+                if let Some((edge, projected, _)) = nearest_edge {
+                    if boundary.len() >= *crate::config::BOUNDARY_VERTEX_RANGE.end() {
+                        notifications.write(NotificationEvent { message: format!("Can't insert boundary vertex: a boundary has at most {} vertices", crate::config::BOUNDARY_VERTEX_RANGE.end()), is_error: true });
+                    } else {
+                        let mut boundary = boundary.clone();
+                        boundary.insert(edge + 1, projected);
+                        let vertex_count = boundary.len();
+                        let candidate = with_boundary(&skeleton_data, boundary, &params, seed.0);
+
+                        match candidate.validate().first() {
+                            None => {
+                                history.record(&skeleton_data);
+                                *skeleton_data = candidate;
+                                params.boundary_vertex_count = vertex_count;
+                                selected_point.0 = Some(edge + 1);
+                                regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+                            }
+                            Some(issue) => {
+                                notifications.write(NotificationEvent { message: format!("Can't insert boundary vertex: {}", issue.describe()), is_error: true });
+                            }
+                        }
+                    }
+                }
+            }
+            EditMode::Blocks => {
+                // blocks are selected, not created
             }
         }
     }
     
     // handle left click
-    // point selection and dragging
+    // point selection and dragging, a drag on empty space draws a selection box
+    let cursor = Vec2::new(world_pos.x, world_pos.z);
     if mouse_button.just_pressed(MouseButton::Left) {
-        if let Some(point_idx) = closest_point {
-            // select point, and drag
+        if *edit_mode == EditMode::Blocks {
+            // blocks are only selected, never dragged
+            selected_point.0 = closest_point;
+        } else if let Some(point_idx) = closest_point {
+            // select point, and drag it along with the rest of the group it belongs to
+            if !selected_group.0.contains(&point_idx) {
+                selected_group.0 = vec![point_idx];
+            }
             selected_point.0 = Some(point_idx);
-            let point_pos = match *edit_mode {
-                EditMode::Generators => skeleton_data.generator_points[point_idx],
-                EditMode::Circumcenters => skeleton_data.points[point_idx],
-                EditMode::Roads => skeleton_data.road_path[point_idx],
-                EditMode::Boundary => {
-                    if let Some(vertex) = skeleton_data.get_boundary_vertex(point_idx) {
-                        Vec3::new(vertex.x, 0.0, vertex.y)
-                    } else {
-                        Vec3::ZERO // fallback
-                    }
-                }
-            };
+            let point_pos = point_position(&skeleton_data, *edit_mode, point_idx).unwrap_or(Vec2::ZERO);
+            history.begin_drag(&skeleton_data);
             drag_state.dragging_point_index = Some(point_idx);
-            drag_state.drag_offset = Vec2::new(world_pos.x - point_pos.x, world_pos.z - point_pos.z);
+            drag_state.drag_offset = cursor - point_pos;
+            drag_state.group = selected_group.0.iter()
+                .filter_map(|&idx| point_position(&skeleton_data, *edit_mode, idx).map(|position| (idx, position)))
+                .collect();
         } else {
             // clicked on empty space, deselect
             selected_point.0 = None;
+            selected_group.0.clear();
+            drag_state.box_start = Some(cursor);
+            drag_state.box_end = cursor;
         }
     } else if mouse_button.just_released(MouseButton::Left) {
+        if let Some(start) = drag_state.box_start.take() {
+            // select every point of the mode inside the box
+            let selection = Rect::from_corners(start, cursor);
+            selected_group.0 = (0..point_count(&skeleton_data, *edit_mode))
+                .filter(|&idx| point_position(&skeleton_data, *edit_mode, idx).is_some_and(|position| selection.contains(position)))
+                .collect();
+            selected_point.0 = selected_group.0.first().copied();
+        }
         if drag_state.dragging_point_index.is_some() {
             // stop dragging, then trigger regeneration for modes that need it
             drag_state.dragging_point_index = None;
+            drag_state.group.clear();
+            history.end_drag(&skeleton_data);
             if matches!(*edit_mode, EditMode::Generators | EditMode::Circumcenters | EditMode::Roads | EditMode::Boundary) {
                 regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
             }
         }
     } else if mouse_button.pressed(MouseButton::Left) {
+        if drag_state.box_start.is_some() {
+            drag_state.box_end = cursor;
+        }
         if let Some(point_idx) = drag_state.dragging_point_index {
-            // update point position during drag
+            // the grabbed point follows the cursor (snapped), the rest of the group keeps its offset to it
+            let dragged = cursor - drag_state.drag_offset;
+            let dragged = if snap { grid.snap_point(dragged) } else { dragged };
+            let Some(&(_, grabbed_origin)) = drag_state.group.iter().find(|(idx, _)| *idx == point_idx) else { return };
+            let delta = dragged - grabbed_origin;
+
+            // boundary offsets are stored against the base polygon
+            let base_polygon = if *edit_mode == EditMode::Boundary {
+                crate::systems::mesh::poly::point_gen::generate_boundary_polygon(
+                    params.boundary_vertex_count, 
+                    params.boundary_scale,
+                    params.boundary_aspect,
+                    params.boundary_noise_freq,
+                    params.boundary_noise_amp,
+                    seed.0
+                )
+            } else {
+                Vec::new()
+            };
+
             // different arrays based on edit mode
-            let new_pos = Vec3::new(
-                world_pos.x - drag_state.drag_offset.x,
-                0.0,
-                world_pos.z - drag_state.drag_offset.y,
-            );
-            match *edit_mode {
-                EditMode::Generators => {
-                    skeleton_data.generator_points[point_idx] = new_pos;
-                }
-                EditMode::Circumcenters => {
-                    skeleton_data.points[point_idx] = new_pos;
-                }
-                EditMode::Roads => {
-                    skeleton_data.road_path[point_idx] = new_pos;
-                }
-                EditMode::Boundary => {
-                    // calculate offset from base position and store it
-                    let base_polygon = crate::systems::mesh::poly::point_gen::generate_boundary_polygon(
-                        params.boundary_vertex_count, 
-                        params.boundary_scale,
-                        seed.0
-                    );
-                    if point_idx < base_polygon.len() && point_idx < skeleton_data.boundary_vertex_offsets.len() {
-                        let base_pos = base_polygon[point_idx];
-                        skeleton_data.boundary_vertex_offsets[point_idx] = Vec2::new(new_pos.x, new_pos.z) - base_pos;
+            for &(idx, origin) in &drag_state.group {
+                let new_pos = origin + delta;
+                match *edit_mode {
+                    EditMode::Generators => {
+                        skeleton_data.generator_points[idx] = Vec3::new(new_pos.x, 0.0, new_pos.y);
+                    }
+                    EditMode::Circumcenters => {
+                        skeleton_data.points[idx] = Vec3::new(new_pos.x, 0.0, new_pos.y);
+                    }
+                    EditMode::Roads => {
+                        skeleton_data.road_path[idx] = Vec3::new(new_pos.x, 0.0, new_pos.y);
+                    }
+                    EditMode::Boundary => {
+                        // calculate offset from base position and store it
+                        if idx < base_polygon.len() && idx < skeleton_data.boundary_vertex_offsets.len() {
+                            skeleton_data.boundary_vertex_offsets[idx] = new_pos - base_polygon[idx];
+                        }
+                        skeleton_data.set_boundary_vertex(idx, new_pos);
+                    }
+                    EditMode::Blocks => {
+                        // blocks are not dragged
                     }
-                    skeleton_data.set_boundary_vertex(point_idx, Vec2::new(new_pos.x, new_pos.z));
                 }
             }
         }
     }
 
-}
\ No newline at end of file
+}
+
+// ctrl+z steps back through the manual edits, ctrl+y (or ctrl+shift+z) forward again
+// the restored skeleton is rebuilt as it was, without generating anything new
+#[allow(clippy::too_many_arguments)]
+pub fn handle_undo_redo(
+    mut contexts: EguiContexts,
+    mut skeleton_data: ResMut<SkeletonData>,
+    mut history: ResMut<EditHistory>,
+    mut drag_state: ResMut<DragState>,
+    mut selected_point: ResMut<SelectedPoint>,
+    mut rebuild_events: EventWriter<RebuildEvent>,
+    mut params: ResMut<Params>,
+    generation_mode: Res<GenerationMode>,
+    seed: Res<Seed>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    if *generation_mode != GenerationMode::Manual || drag_state.dragging_point_index.is_some() {
+        return;
+    }
+    if !keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    // text fields have their own undo
+    if let Ok(ctx) = contexts.ctx_mut() && ctx.wants_keyboard_input() {
+        return;
+    }
+
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let restored = if keyboard.just_pressed(KeyCode::KeyZ) && !shift {
+        history.undo(&skeleton_data)
+    } else if keyboard.just_pressed(KeyCode::KeyY) || (keyboard.just_pressed(KeyCode::KeyZ) && shift) {
+        history.redo()
+    } else {
+        return;
+    };
+
+    if let Some(restored) = restored {
+        *skeleton_data = restored;
+        // boundary inserts and deletes change the vertex count, boundary-mode regeneration must see the restored one
+        let vertex_count = skeleton_data.boundary_polygon.len();
+        if params.boundary_vertex_count != vertex_count {
+            params.boundary_vertex_count = vertex_count;
+        }
+        // indices may point past the restored point lists
+        selected_point.0 = None;
+        drag_state.dragging_point_index = None;
+        rebuild_events.write(RebuildEvent { seed: seed.0 });
+    }
+}
@@ -1,5 +1,13 @@
 // this is the entry point for the building generation plugin
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::collections::{HashMap, HashSet};
+use std::ops::{Range, RangeInclusive};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 
 use crate::config::*;
 
@@ -10,12 +18,25 @@ pub mod town;
 #[derive(Resource)]
 pub struct Seed(pub u64);
 
-#[derive(Resource)]
+#[derive(Resource, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct SkeletonData {
     pub generator_points: Vec<Vec3>,        // user-placeable seed points  
+    #[serde(default)]
+    pub generator_weights: Vec<f32>,        // power diagram weights, parallel to generator_points, missing = GENERATOR_WEIGHT
     pub points: Vec<Vec3>,                  // circumcenters (computed from generators or manually edited)
     pub cells: Vec<Vec<usize>>,             // each cell contains circumcenter indices forming one Voronoi polygon
+    pub cell_generators: Vec<usize>,        // generator index owning each cell, parallel to cells
+    #[serde(skip)]
+    pub cell_adjacency: Vec<Vec<usize>>,    // neighbouring cells of each cell, parallel to cells, rebuilt on load
     pub road_path: Vec<Vec3>,               // road path, sequence of points
+    #[serde(default)]
+    pub road_widths: Vec<f32>,              // corridor width at each road point, parallel to road_path, missing = ROAD_WIDTH
+    #[serde(default)]
+    pub road_generators: Range<usize>,      // generator indices placed along the road, empty for imported or hand-placed sets
+    #[serde(default)]
+    pub boundary_generators: Range<usize>,  // generator indices lining the boundary, empty for imported or hand-placed sets
+    #[serde(skip)]
+    pub relaxation_steps: usize,            // lloyd's passes the generators went through, fewer than asked once relaxation converged
 
     pub boundary_polygon: Polygon,          // boundary constraint polygon
     pub boundary_vertex_offsets: Vec<Vec2>, // absolute boundary offsets
@@ -25,6 +46,66 @@ pub struct SkeletonData {
 pub struct DragState {
     pub dragging_point_index: Option<usize>,
     pub drag_offset: Vec2,
+    pub group: Vec<(usize, Vec2)>, // selected points moved along, with their positions when the drag started
+    pub box_start: Option<Vec2>,   // rubber-band corner on the ground (x, z), while dragging out a selection box
+    pub box_end: Vec2,
+}
+
+// skeleton snapshots for undo/redo of manual edits
+// snapshots[..cursor] are the states before each edit, snapshots[cursor..] the ones redo steps back into
+// the oldest snapshot is dropped past EDIT_HISTORY_LIMIT
+#[derive(Resource, Default)]
+pub struct EditHistory {
+    snapshots: Vec<SkeletonData>,
+    cursor: usize,
+    drag_start: Option<SkeletonData>, // state before the drag in progress, recorded on release if it moved
+}
+
+impl EditHistory {
+    // call before a mutating edit, drops the redo branch
+    pub fn record(&mut self, before: &SkeletonData) {
+        self.snapshots.truncate(self.cursor);
+        self.snapshots.push(before.clone());
+        if self.snapshots.len() > EDIT_HISTORY_LIMIT {
+            self.snapshots.remove(0);
+        }
+        self.cursor = self.snapshots.len();
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag_start.is_some()
+    }
+
+    pub fn begin_drag(&mut self, before: &SkeletonData) {
+        self.drag_start = Some(before.clone());
+    }
+
+    // a click without movement leaves no entry
+    pub fn end_drag(&mut self, after: &SkeletonData) {
+        if let Some(before) = self.drag_start.take() && before != *after {
+            self.record(&before);
+        }
+    }
+
+    // state to restore, current is kept so redo can come back to it
+    pub fn undo(&mut self, current: &SkeletonData) -> Option<SkeletonData> {
+        if self.cursor == 0 {
+            return None;
+        }
+        if self.cursor == self.snapshots.len() {
+            self.snapshots.push(current.clone());
+        }
+        self.cursor -= 1;
+        Some(self.snapshots[self.cursor].clone())
+    }
+
+    pub fn redo(&mut self) -> Option<SkeletonData> {
+        if self.cursor + 1 >= self.snapshots.len() {
+            return None;
+        }
+        self.cursor += 1;
+        Some(self.snapshots[self.cursor].clone())
+    }
 }
 
 #[derive(Resource, Default)]
@@ -33,6 +114,18 @@ pub struct HoveredPoint(pub Option<usize>);
 #[derive(Resource, Default)]
 pub struct SelectedPoint(pub Option<usize>);
 
+// box-selected points of the current edit mode, always includes SelectedPoint
+// empty whenever nothing is selected
+#[derive(Resource, Default)]
+pub struct SelectedPoints(pub Vec<usize>);
+
+// building under the cursor / last clicked building, for inspection
+#[derive(Resource, Default)]
+pub struct HoveredBuilding(pub Option<Entity>);
+
+#[derive(Resource, Default)]
+pub struct SelectedBuilding(pub Option<Entity>);
+
 
 // Event for regeneration
 #[derive(Event)]
@@ -41,6 +134,12 @@ pub struct RegenerateEvent {
     pub user_edit: bool,
 }
 
+// Event for rebuilding the town from the skeleton as it stands, generators and diagram are kept
+#[derive(Event)]
+pub struct RebuildEvent {
+    pub seed: u64,
+}
+
 // Event for clearing all data
 #[derive(Event)]
 pub struct ClearEvent;
@@ -49,6 +148,58 @@ pub struct ClearEvent;
 #[derive(Event)]
 pub struct RelaxEvent;
 
+// Event for carving the road corridor out of existing buildings
+#[derive(Event)]
+pub struct CarveRoadEvent;
+
+// Event for re-rolling one block's subdivision, keyed by block index
+#[derive(Event)]
+pub struct RerollBlockEvent(pub usize);
+
+// Event for changing one generator's power diagram weight
+// release ends the slider drag, so the whole drag is one undo step
+#[derive(Event)]
+pub struct GeneratorWeightEvent {
+    pub generator: usize,
+    pub weight: f32,
+    pub release: bool,
+}
+
+// Event for changing the corridor width at one road point
+// release ends the slider drag, so the whole drag is one undo step
+#[derive(Event)]
+pub struct RoadWidthEvent {
+    pub point: usize,
+    pub width: f32,
+    pub release: bool,
+}
+
+// Event for resetting the boundary to a regular polygon
+#[derive(Event)]
+pub struct RegularizeBoundaryEvent;
+
+// Event for replacing the boundary with the convex hull of the generators
+#[derive(Event)]
+pub struct FitBoundaryEvent;
+
+// skeleton edit actions the UI fires: boundary fitting, road carving, imports, scene files and per-point edits
+#[derive(SystemParam)]
+pub struct EditEvents<'w> {
+    pub regularize: EventWriter<'w, RegularizeBoundaryEvent>,
+    pub carve: EventWriter<'w, CarveRoadEvent>,
+    pub fit_boundary: EventWriter<'w, FitBoundaryEvent>,
+    pub import_generators: EventWriter<'w, crate::systems::import::ImportGeneratorsEvent>,
+    pub import_settings: ResMut<'w, crate::systems::import::ImportSettings>,
+    pub import_boundary: EventWriter<'w, crate::systems::import::ImportBoundaryEvent>,
+    pub save_scene: EventWriter<'w, crate::systems::persistence::SaveSceneEvent>,
+    pub load_scene: EventWriter<'w, crate::systems::persistence::LoadSceneEvent>,
+    pub scene_file: ResMut<'w, crate::systems::persistence::SceneFile>,
+    pub jump_to_issue: EventWriter<'w, JumpToIssueEvent>,
+    pub reroll_block: EventWriter<'w, RerollBlockEvent>,
+    pub generator_weight: EventWriter<'w, GeneratorWeightEvent>,
+    pub road_width: EventWriter<'w, RoadWidthEvent>,
+}
+
 // generation mode
 #[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
 pub enum GenerationMode {
@@ -65,6 +216,130 @@ pub enum EditMode {
     Generators,    // user manipulates generator points
     Circumcenters, // user manipulates circumcenters directly
     Roads,         // user places road point paths
+    Blocks,        // user overrides subdivision per block
+}
+
+// per-block subdivision override, set in block edit mode
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlockOverride {
+    Whole,      // no subdivision, block stays one building / plaza
+    Finer(u32), // subdivide this many levels finer than the global params
+}
+
+impl BlockOverride {
+    // step one level finer (+1) or coarser (-1)
+    // Whole <-> no override <-> Finer(1) <-> Finer(2) ...
+    pub fn step(current: Option<BlockOverride>, delta: i32) -> Option<BlockOverride> {
+        let level = match current {
+            Some(BlockOverride::Whole) => -1,
+            None => 0,
+            Some(BlockOverride::Finer(n)) => n as i32,
+        };
+        match (level + delta).max(-1) {
+            -1 => Some(BlockOverride::Whole),
+            0 => None,
+            n => Some(BlockOverride::Finer(n as u32)),
+        }
+    }
+}
+
+// overrides keyed by block (voronoi cell) index
+#[derive(Resource, Default)]
+pub struct BlockOverrides(pub HashMap<usize, BlockOverride>);
+
+// how often each block was re-rolled, keyed like the overrides
+// the count is mixed into the block's rng seed, so a full regeneration keeps the re-rolled layout
+#[derive(Resource, Default)]
+pub struct BlockSeeds(pub HashMap<usize, u64>);
+
+impl BlockSeeds {
+    // seeded by where the block is rather than its index, so reordered cells keep their layouts
+    // and an edit only re-rolls the blocks whose shape it changed
+    pub fn block_seed(&self, seed: u64, block_idx: usize, block_polygon: &Polygon) -> u64 {
+        let rerolls = self.0.get(&block_idx).copied().unwrap_or(0);
+        seed.wrapping_add(block_position_hash(block_polygon)) ^ rerolls.wrapping_mul(crate::config::BLOCK_REROLL_SALT)
+    }
+}
+
+// stable hash of a block's centroid rounded to BLOCK_SEED_PRECISION, splitmix64 finalizer per axis
+pub fn block_position_hash(block_polygon: &Polygon) -> u64 {
+    let mix = |mut z: u64| {
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    let centroid = poly::utils::polygon_centroid(block_polygon, poly::utils::polygon_area(block_polygon));
+    let [x, z] = [centroid.x, centroid.y].map(|c| (c / crate::config::BLOCK_SEED_PRECISION).round() as i64 as u64);
+    mix(x ^ mix(z))
+}
+
+// how horizontal faces (footprints, caps) get their UVs
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum UvMode {
+    #[default]
+    BoundsFit,   // 0..1 stretched over each polygon's bounds
+    WorldPlanar, // world-space x/z divided by tile size, tiles across polygons
+}
+
+// what sits on top of the walls
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum RoofStyle {
+    Flat, // walls end in the top cap
+    #[default]
+    Gable, // ridge along the long axis, hip roof on near-square footprints
+    Hip,   // straight skeleton, one face per wall
+}
+
+impl RoofStyle {
+    pub const ALL: [RoofStyle; 3] = [RoofStyle::Flat, RoofStyle::Gable, RoofStyle::Hip];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RoofStyle::Flat => "Flat",
+            RoofStyle::Gable => "Gable",
+            RoofStyle::Hip => "Hip",
+        }
+    }
+}
+
+// how subdivide_to_plots picks the direction of each cut
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum SubdivisionStrategy {
+    #[default]
+    LongestEdge, // perpendicular to the longest edge
+    ObbAxis,     // perpendicular to the long axis of the oriented bounding box, squarer plots
+}
+
+impl SubdivisionStrategy {
+    pub const ALL: [SubdivisionStrategy; 2] = [SubdivisionStrategy::LongestEdge, SubdivisionStrategy::ObbAxis];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubdivisionStrategy::LongestEdge => "Longest Edge",
+            SubdivisionStrategy::ObbAxis => "Bounding Box Axis",
+        }
+    }
+}
+
+// how the regular generators are laid out before relaxation
+#[derive(Clone, Copy, PartialEq, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum PointDistribution {
+    #[default]
+    Spiral,  // pgen, dense in the middle and thinning outward
+    Poisson, // evenly spaced random points
+    Grid,    // square lattice
+}
+
+impl PointDistribution {
+    pub const ALL: [PointDistribution; 3] = [PointDistribution::Spiral, PointDistribution::Poisson, PointDistribution::Grid];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PointDistribution::Spiral => "Spiral",
+            PointDistribution::Poisson => "Poisson Disk",
+            PointDistribution::Grid => "Grid",
+        }
+    }
 }
 
 // my 2d polygon datatype
@@ -72,51 +347,471 @@ pub enum EditMode {
 pub type Polygon = Vec<Vec2>;
 
 // town generation parameters
-#[derive(Resource)]
+// serde(default) so presets saved before a parameter existed still load
+#[derive(Resource, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
 pub struct Params {
     pub max_recursion_depth: usize,
+    pub subdivision_strategy: SubdivisionStrategy,
     // pub max_distance: f32,
     pub min_sq: f32,
+    pub max_sq: f32,
+    pub min_block_area: f32,
+    pub drop_small_blocks: bool, // small blocks are removed instead of kept as one building
+    pub block_merge_prob: f32,    // neighbouring cells merged into larger irregular blocks
+    pub block_setback: f32,       // blocks inset from their cell edges, leaves streets between them
+    pub courtyard_prob: f32,      // large blocks built as a ring around an empty courtyard
+    pub min_edge_length: f32,     // shorter footprint edges are collapsed, avoids micro-walls
+    pub max_aspect_ratio: f32,    // slivers past this length to width ratio are re-cut or dropped, 0 = no limit
+    pub irregular_footprints: bool, // notch some plots into L/U shaped buildings
+    pub irregular_footprint_prob: f32,
+    pub setback_margin: f32,      // inset buildings into their plots, leaves yards between them
+    pub setback_jitter: f32,      // 0 = uniform setback, 1 = varied setback and free placement within it
+    pub show_interiors: bool,     // split large footprints into rooms, drawn as low interior walls
+    pub interior_min_area: f32,
     pub grid_chaos: f32,
+    pub split_bias: f32, // where cuts land along an edge before grid_chaos spreads them
     pub size_chaos: f32,
     pub empty_prob: f32,
     pub alley_width: f32,
     pub alley_chance: f32,
+    pub alley_paths: bool, // pave the alley gaps with thin path meshes
     pub min_wall_height: f32,
     pub max_wall_height: f32,
+    pub center_height_falloff: f32, // taller toward the settlement core, 0 = uniform
+    pub roof_style: RoofStyle,
     pub min_roof_height: f32,
     pub max_roof_height: f32,
+    pub smooth_normals: bool, // soft shading across roof ridges, walls stay flat
+    pub base_embed: f32,
+    pub eave_overhang: f32, // roofs reach this far past the walls (m)
+    pub window_density: f32, // chance per window slot, 0 keeps plain walls without a door
+    pub floor_height: f32,   // floor-to-floor height, walls get a uv seam per floor
+    // road parameters
+    pub road_curbs: bool, // spawn the road corridor as a mesh with raised curbs
+    pub curb_height: f32,
+    pub road_smoothing: usize, // catmull-rom points per road segment, 0 = straight segments
+    // keep the previous generation as a transparent ghost for comparison
+    pub keep_ghost: bool,
     // boundary parameters
     pub boundary_spacing: f32,
     pub boundary_vertex_count: usize,
     pub boundary_inner_offset: f32,
     pub boundary_scale: f32,
+    pub boundary_aspect: f32,
+    pub boundary_noise_freq: usize,
+    pub boundary_noise_amp: f32,
     pub generator_count: usize,
+    pub point_distribution: PointDistribution,
+    pub density_gradient: f32, // sampling density, > 0 clusters generators in the middle, < 0 at the edge
+    pub relaxation_steps: usize, // lloyd's passes, 0 = raw distribution
+    pub relaxation_epsilon: f32, // stop relaxing once a pass moves the generators less than this in total
+    pub min_generator_spacing: f32,
+    pub core_point: Option<Vec2>, // settlement core, boundary centroid when unset
+    pub core_bias: f32,
     // voronoi parameters
     pub circumcenter_merge_threshold: f32,
+    // texturing parameters
+    pub uv_mode: UvMode,
+    pub uv_tile_size: f32,
+    pub texture_meters_per_tile: f32, // wall texture repeat
+    pub wall_textures: bool,          // pick a texture per building from the WallTextures set
 }
 
 impl Default for Params {
     fn default() -> Self {
         Self {
             max_recursion_depth: MAX_RECURSION_DEPTH,
+            subdivision_strategy: SubdivisionStrategy::LongestEdge,
             // min_sq now represents minimum building area in m²
             min_sq: crate::config::BUILDING_AREA_MIN,
+            // hard ceiling, plots above this keep getting cut
+            max_sq: crate::config::BUILDING_AREA_LIMIT,
+            // slivers along the boundary become a single building
+            min_block_area: crate::config::MIN_BLOCK_AREA,
+            drop_small_blocks: false,
+            block_merge_prob: crate::config::BLOCK_MERGE_PROB,
+            block_setback: BLOCK_SETBACK,
+            courtyard_prob: COURTYARD_PROB,
+            min_edge_length: crate::config::MIN_EDGE_LENGTH,
+            max_aspect_ratio: MAX_ASPECT_RATIO,
+            irregular_footprints: false,
+            irregular_footprint_prob: crate::config::IRREGULAR_FOOTPRINT_PROB,
+            setback_margin: crate::config::SETBACK_MARGIN,
+            setback_jitter: crate::config::SETBACK_JITTER,
+            show_interiors: false,
+            interior_min_area: crate::config::INTERIOR_MIN_AREA,
             grid_chaos: GRID_CHAOS,
+            split_bias: SPLIT_BIAS,
             size_chaos: SIZE_CHAOS,
             empty_prob: EMPTY_PROB,
             alley_width: ALLEY_WIDTH,
             alley_chance: ALLEY_CHANCE,
+            alley_paths: false,
             min_wall_height: MIN_WALL_HEIGHT,
             max_wall_height: MAX_WALL_HEIGHT,
+            center_height_falloff: CENTER_HEIGHT_FALLOFF,
+            roof_style: RoofStyle::Gable,
             min_roof_height: MIN_ROOF_HEIGHT,
             max_roof_height: MAX_ROOF_HEIGHT,
+            smooth_normals: false,
+            base_embed: BASE_EMBED,
+            eave_overhang: EAVE_OVERHANG,
+            window_density: WINDOW_DENSITY,
+            floor_height: FLOOR_HEIGHT,
+            road_curbs: false,
+            curb_height: crate::config::CURB_HEIGHT,
+            road_smoothing: crate::config::ROAD_SMOOTHING,
+            keep_ghost: false,
             boundary_spacing: crate::config::BOUNDARY_GENERATOR_SPACING,
             boundary_vertex_count: 4, // default to 4-sided polygon
             boundary_inner_offset: crate::config::BOUNDARY_GENERATOR_INNER_OFFSET,
             boundary_scale: 75.0, // default settlement radius in meters
+            boundary_aspect: crate::config::BOUNDARY_ASPECT,
+            boundary_noise_freq: crate::config::BOUNDARY_NOISE_FREQ,
+            boundary_noise_amp: crate::config::BOUNDARY_NOISE_AMP,
             generator_count: crate::config::POINT_COUNT,
+            point_distribution: PointDistribution::default(),
+            density_gradient: DENSITY_GRADIENT,
+            relaxation_steps: RELAXATION_STEPS,
+            relaxation_epsilon: RELAXATION_EPSILON,
+            min_generator_spacing: crate::config::MIN_GENERATOR_SPACING,
+            core_point: None,
+            core_bias: crate::config::CORE_BIAS,
             circumcenter_merge_threshold: crate::config::CIRCUMCENTER_MERGE_THRESHOLD,
+            uv_mode: UvMode::default(),
+            uv_tile_size: crate::config::UV_TILE_SIZE,
+            texture_meters_per_tile: crate::config::TEXTURE_METERS_PER_TILE,
+            wall_textures: false,
+        }
+    }
+}
+
+// parameters that the "surprise me" randomization can touch
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum RandomParam {
+    MinSq,
+    GridChaos,
+    SizeChaos,
+    EmptyProb,
+    AlleyWidth,
+    AlleyChance,
+    WallHeights,
+}
+
+impl RandomParam {
+    pub const ALL: [RandomParam; 7] = [
+        RandomParam::MinSq,
+        RandomParam::GridChaos,
+        RandomParam::SizeChaos,
+        RandomParam::EmptyProb,
+        RandomParam::AlleyWidth,
+        RandomParam::AlleyChance,
+        RandomParam::WallHeights,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RandomParam::MinSq => "Min Building Area",
+            RandomParam::GridChaos => "Grid Irregularity",
+            RandomParam::SizeChaos => "Size Variation",
+            RandomParam::EmptyProb => "Empty Plot Probability",
+            RandomParam::AlleyWidth => "Alley Width",
+            RandomParam::AlleyChance => "Alley Frequency",
+            RandomParam::WallHeights => "Wall Heights",
+        }
+    }
+}
+
+// wall texture set, drawn once at startup, one per WALL_TEXTURE_NAMES entry
+#[derive(Resource)]
+pub struct WallTextures(pub Vec<Handle<Image>>);
+
+impl FromWorld for WallTextures {
+    fn from_world(world: &mut World) -> Self {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        Self(crate::config::WALL_TEXTURE_NAMES.iter()
+            .map(|name| images.add(WallTextures::draw(name, crate::config::WALL_TEXTURE_SIZE)))
+            .collect())
+    }
+}
+
+impl WallTextures {
+    // light greys, the building colour tints them through the material
+    // every pattern wraps at the edges, wall uvs are in tiles so the textures repeat
+    fn draw(name: &str, size: u32) -> Image {
+        // per-pixel grain, fixed so every run draws the same textures
+        let grain = |x: u32, y: u32| {
+            let hash = ((x as u64) << 32 | y as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 40;
+            hash as f32 / (1u64 << 24) as f32 - 0.5
+        };
+        let tau = std::f32::consts::TAU;
+        let mut data = Vec::with_capacity((size * size * 4) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                let (u, v) = (x as f32 / size as f32, y as f32 / size as f32);
+                let value = match name {
+                    // 8 courses of 2 bricks, every other course shifted by half a brick
+                    "brick" => {
+                        let course = y * 8 / size;
+                        let shift = if course % 2 == 1 { size / 4 } else { 0 };
+                        let mortar = y % (size / 8) == 0 || (x + shift) % (size / 2) == 0;
+                        if mortar { 245.0 } else { 175.0 + 30.0 * grain(x, y) }
+                    }
+                    // 4 vertical planks with dark seams and a wavy grain
+                    "wood" => {
+                        let plank = x * 4 / size;
+                        if x % (size / 4) == 0 {
+                            130.0
+                        } else {
+                            195.0 + 18.0 * (v * tau * 3.0 + plank as f32 * 1.7 + (u * tau * 4.0).sin()).sin() + 12.0 * grain(x, y)
+                        }
+                    }
+                    // 8 ridges across the sheet
+                    "corrugated" => 215.0 + 35.0 * (u * tau * 8.0).sin(),
+                    // plaster and anything unknown, a faint mottle
+                    _ => 225.0 + 24.0 * grain(x, y),
+                };
+                let value = value.clamp(0.0, 255.0) as u8;
+                data.extend([value, value, value, 255]);
+            }
+        }
+
+        let mut image = Image::new(
+            Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            address_mode_u: ImageAddressMode::Repeat,
+            address_mode_v: ImageAddressMode::Repeat,
+            ..default()
+        });
+        image
+    }
+
+    // deterministic per building, hashed so it doesn't touch the block rngs
+    pub fn pick(&self, seed: u64, building_id: u32) -> Option<Handle<Image>> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let hash = (seed ^ building_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32;
+        self.0.get(hash as usize % self.0.len()).cloned()
+    }
+}
+
+// parameters excluded from randomization
+#[derive(Resource, Default)]
+pub struct ParamLocks(pub HashSet<RandomParam>);
+
+// parameters that can be nudged from the keyboard, each with a fixed step
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SteppedParam {
+    MinSq,
+    MaxSq,
+    GridChaos,
+    SizeChaos,
+    EmptyProb,
+    AlleyWidth,
+    AlleyChance,
+    MinWallHeight,
+    MaxWallHeight,
+    GeneratorCount,
+}
+
+impl SteppedParam {
+    pub const ALL: [SteppedParam; 10] = [
+        SteppedParam::MinSq,
+        SteppedParam::MaxSq,
+        SteppedParam::GridChaos,
+        SteppedParam::SizeChaos,
+        SteppedParam::EmptyProb,
+        SteppedParam::AlleyWidth,
+        SteppedParam::AlleyChance,
+        SteppedParam::MinWallHeight,
+        SteppedParam::MaxWallHeight,
+        SteppedParam::GeneratorCount,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SteppedParam::MinSq => "Min Building Area",
+            SteppedParam::MaxSq => "Max Building Area",
+            SteppedParam::GridChaos => "Grid Irregularity",
+            SteppedParam::SizeChaos => "Size Variation",
+            SteppedParam::EmptyProb => "Empty Plot Probability",
+            SteppedParam::AlleyWidth => "Alley Width",
+            SteppedParam::AlleyChance => "Alley Frequency",
+            SteppedParam::MinWallHeight => "Min Wall Height",
+            SteppedParam::MaxWallHeight => "Max Wall Height",
+            SteppedParam::GeneratorCount => "Generator Count",
+        }
+    }
+
+    // one key press worth of change
+    pub fn step(&self) -> f32 {
+        match self {
+            SteppedParam::MinSq => 1.0,
+            SteppedParam::MaxSq => 10.0,
+            SteppedParam::GridChaos | SteppedParam::SizeChaos | SteppedParam::AlleyChance => 0.05,
+            SteppedParam::EmptyProb => 0.02,
+            SteppedParam::AlleyWidth => 0.1,
+            SteppedParam::MinWallHeight | SteppedParam::MaxWallHeight => 0.25,
+            SteppedParam::GeneratorCount => 1.0,
+        }
+    }
+
+    pub fn value(&self, params: &Params) -> f32 {
+        match self {
+            SteppedParam::MinSq => params.min_sq,
+            SteppedParam::MaxSq => params.max_sq,
+            SteppedParam::GridChaos => params.grid_chaos,
+            SteppedParam::SizeChaos => params.size_chaos,
+            SteppedParam::EmptyProb => params.empty_prob,
+            SteppedParam::AlleyWidth => params.alley_width,
+            SteppedParam::AlleyChance => params.alley_chance,
+            SteppedParam::MinWallHeight => params.min_wall_height,
+            SteppedParam::MaxWallHeight => params.max_wall_height,
+            SteppedParam::GeneratorCount => params.generator_count as f32,
+        }
+    }
+}
+
+// pairs of building ids that neighbour each other within a block
+// rebuilt by generate_town, for pathfinding/analysis downstream
+#[derive(Resource, Default)]
+pub struct BuildingAdjacency {
+    pub edges: Vec<(u32, u32)>,
+}
+
+impl Params {
+    // pull every parameter back into its slider range, for values that didn't come from the sliders
+    // (hand-edited presets). wall heights keep the margin the sliders enforce
+    pub fn clamp_to_ranges(&mut self) {
+        let clamp = |value: &mut f32, range: RangeInclusive<f32>| {
+            *value = if value.is_finite() { value.clamp(*range.start(), *range.end()) } else { *range.start() };
+        };
+        let clamp_usize = |value: &mut usize, range: RangeInclusive<usize>| *value = (*value).clamp(*range.start(), *range.end());
+
+        clamp_usize(&mut self.max_recursion_depth, MAX_RECURSION_RANGE);
+        clamp(&mut self.min_sq, MIN_SQ_RANGE);
+        clamp(&mut self.max_sq, MAX_SQ_RANGE);
+        clamp(&mut self.min_block_area, MIN_BLOCK_AREA_RANGE);
+        clamp(&mut self.block_merge_prob, BLOCK_MERGE_PROB_RANGE);
+        clamp(&mut self.min_edge_length, MIN_EDGE_LENGTH_RANGE);
+        clamp(&mut self.irregular_footprint_prob, IRREGULAR_FOOTPRINT_PROB_RANGE);
+        clamp(&mut self.block_setback, BLOCK_SETBACK_RANGE);
+        clamp(&mut self.courtyard_prob, COURTYARD_PROB_RANGE);
+        clamp(&mut self.max_aspect_ratio, MAX_ASPECT_RATIO_RANGE);
+        clamp(&mut self.setback_margin, SETBACK_MARGIN_RANGE);
+        clamp(&mut self.setback_jitter, SETBACK_JITTER_RANGE);
+        clamp(&mut self.interior_min_area, INTERIOR_MIN_AREA_RANGE);
+        clamp(&mut self.grid_chaos, GRID_CHAOS_RANGE);
+        clamp(&mut self.split_bias, SPLIT_BIAS_RANGE);
+        clamp(&mut self.size_chaos, SIZE_CHAOS_RANGE);
+        clamp(&mut self.empty_prob, EMPTY_PROB_RANGE);
+        clamp(&mut self.alley_width, ALLEY_WIDTH_RANGE);
+        clamp(&mut self.alley_chance, ALLEY_CHANCE_RANGE);
+        let (wall_min, wall_max) = (*WALL_HEIGHT_RANGE.start(), *WALL_HEIGHT_RANGE.end());
+        clamp(&mut self.min_wall_height, wall_min..=wall_max - WALL_HEIGHT_MARGIN);
+        clamp(&mut self.max_wall_height, self.min_wall_height + WALL_HEIGHT_MARGIN..=wall_max);
+        let (roof_min, roof_max) = (*ROOF_HEIGHT_RANGE.start(), *ROOF_HEIGHT_RANGE.end());
+        clamp(&mut self.min_roof_height, roof_min..=roof_max - ROOF_HEIGHT_MARGIN);
+        clamp(&mut self.max_roof_height, self.min_roof_height + ROOF_HEIGHT_MARGIN..=roof_max);
+        clamp(&mut self.center_height_falloff, CENTER_HEIGHT_FALLOFF_RANGE);
+        clamp(&mut self.base_embed, BASE_EMBED_RANGE);
+        clamp(&mut self.eave_overhang, EAVE_OVERHANG_RANGE);
+        clamp(&mut self.window_density, WINDOW_DENSITY_RANGE);
+        clamp(&mut self.floor_height, FLOOR_HEIGHT_RANGE);
+        clamp(&mut self.curb_height, CURB_HEIGHT_RANGE);
+        clamp_usize(&mut self.road_smoothing, ROAD_SMOOTHING_RANGE);
+        clamp(&mut self.boundary_spacing, BOUNDARY_SPACING_RANGE);
+        clamp_usize(&mut self.boundary_vertex_count, BOUNDARY_VERTEX_RANGE);
+        clamp(&mut self.boundary_inner_offset, BOUNDARY_INNER_OFFSET_RANGE);
+        clamp(&mut self.boundary_scale, BOUNDARY_SCALE_RANGE);
+        clamp(&mut self.boundary_aspect, BOUNDARY_ASPECT_RANGE);
+        clamp_usize(&mut self.boundary_noise_freq, BOUNDARY_NOISE_FREQ_RANGE);
+        clamp(&mut self.boundary_noise_amp, BOUNDARY_NOISE_AMP_RANGE);
+        clamp_usize(&mut self.generator_count, GENERATOR_COUNT_RANGE);
+        clamp_usize(&mut self.relaxation_steps, RELAXATION_STEPS_RANGE);
+        clamp(&mut self.density_gradient, DENSITY_GRADIENT_RANGE);
+        clamp(&mut self.relaxation_epsilon, RELAXATION_EPSILON_RANGE);
+        clamp(&mut self.min_generator_spacing, GENERATOR_SPACING_RANGE);
+        clamp(&mut self.core_bias, CORE_BIAS_RANGE);
+        clamp(&mut self.circumcenter_merge_threshold, MERGE_THRESHOLD_RANGE);
+        clamp(&mut self.uv_tile_size, UV_TILE_SIZE_RANGE);
+        clamp(&mut self.texture_meters_per_tile, TEXTURE_METERS_PER_TILE_RANGE);
+        if self.core_point.is_some_and(|core| !core.is_finite()) {
+            self.core_point = None;
+        }
+    }
+
+    // sample every randomizable parameter within its slider range
+    // values are always drawn, so locking one parameter doesn't shift the others for a given seed
+    pub fn randomize(&mut self, seed: u64, locks: &ParamLocks) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut sample = |range: RangeInclusive<f32>| rng.random_range(range);
+
+        let min_sq = sample(MIN_SQ_RANGE);
+        let grid_chaos = sample(GRID_CHAOS_RANGE);
+        let size_chaos = sample(SIZE_CHAOS_RANGE);
+        let empty_prob = sample(EMPTY_PROB_RANGE);
+        let alley_width = sample(ALLEY_WIDTH_RANGE);
+        let alley_chance = sample(ALLEY_CHANCE_RANGE);
+        let min_wall_height = sample(*WALL_HEIGHT_RANGE.start()..=(*WALL_HEIGHT_RANGE.end() - WALL_HEIGHT_MARGIN));
+        let max_wall_height = sample((min_wall_height + WALL_HEIGHT_MARGIN)..=*WALL_HEIGHT_RANGE.end());
+
+        let unlocked = |param: RandomParam| !locks.0.contains(&param);
+        if unlocked(RandomParam::MinSq) { self.min_sq = min_sq; }
+        if unlocked(RandomParam::GridChaos) { self.grid_chaos = grid_chaos; }
+        if unlocked(RandomParam::SizeChaos) { self.size_chaos = size_chaos; }
+        if unlocked(RandomParam::EmptyProb) { self.empty_prob = empty_prob; }
+        if unlocked(RandomParam::AlleyWidth) { self.alley_width = alley_width; }
+        if unlocked(RandomParam::AlleyChance) { self.alley_chance = alley_chance; }
+        if unlocked(RandomParam::WallHeights) {
+            self.min_wall_height = min_wall_height;
+            self.max_wall_height = max_wall_height;
+        }
+    }
+
+    // move a parameter by `steps` of its step size, clamped to the slider range
+    // returns false when it was already at the limit
+    pub fn nudge(&mut self, param: SteppedParam, steps: i32) -> bool {
+        let delta = param.step() * steps as f32;
+        let step_f32 = |value: &mut f32, range: RangeInclusive<f32>| {
+            let new = (*value + delta).clamp(*range.start(), *range.end());
+            let changed = new != *value;
+            *value = new;
+            changed
+        };
+
+        // wall heights keep the same margin the sliders enforce
+        let (wall_min, wall_max) = (*WALL_HEIGHT_RANGE.start(), *WALL_HEIGHT_RANGE.end());
+        match param {
+            SteppedParam::MinSq => step_f32(&mut self.min_sq, MIN_SQ_RANGE),
+            SteppedParam::MaxSq => step_f32(&mut self.max_sq, MAX_SQ_RANGE),
+            SteppedParam::GridChaos => step_f32(&mut self.grid_chaos, GRID_CHAOS_RANGE),
+            SteppedParam::SizeChaos => step_f32(&mut self.size_chaos, SIZE_CHAOS_RANGE),
+            SteppedParam::EmptyProb => step_f32(&mut self.empty_prob, EMPTY_PROB_RANGE),
+            SteppedParam::AlleyWidth => step_f32(&mut self.alley_width, ALLEY_WIDTH_RANGE),
+            SteppedParam::AlleyChance => step_f32(&mut self.alley_chance, ALLEY_CHANCE_RANGE),
+            SteppedParam::MinWallHeight => {
+                let limit = (self.max_wall_height - WALL_HEIGHT_MARGIN).max(wall_min);
+                step_f32(&mut self.min_wall_height, wall_min..=limit)
+            }
+            SteppedParam::MaxWallHeight => {
+                let limit = (self.min_wall_height + WALL_HEIGHT_MARGIN).min(wall_max);
+                step_f32(&mut self.max_wall_height, limit..=wall_max)
+            }
+            SteppedParam::GeneratorCount => {
+                let new = self.generator_count.saturating_add_signed(steps as isize)
+                    .clamp(*GENERATOR_COUNT_RANGE.start(), *GENERATOR_COUNT_RANGE.end());
+                let changed = new != self.generator_count;
+                self.generator_count = new;
+                changed
+            }
         }
     }
 }
@@ -133,25 +828,174 @@ impl SkeletonData {
         }
     }
     
+    pub fn generator_weight(&self, idx: usize) -> f32 {
+        self.generator_weights.get(idx).copied().unwrap_or(GENERATOR_WEIGHT)
+    }
+
+    pub fn road_width(&self, idx: usize) -> f32 {
+        self.road_widths.get(idx).copied().unwrap_or(ROAD_WIDTH)
+    }
+
+    // road widths smoothed like the road path, one per point of smooth_road_path
+    pub fn smoothed_road_widths(&self, subdivisions: usize) -> Vec<f32> {
+        let widths: Vec<f32> = (0..self.road_path.len()).map(|idx| self.road_width(idx)).collect();
+        poly::point_gen::smooth_road_widths(&self.road_path, &widths, subdivisions)
+    }
+
     pub fn boundary_vertex_count(&self) -> usize {
         self.boundary_polygon.len()
     }
 
+    // take freshly generated points along with the ranges of their fixed generators, weights start over
+    pub fn set_generators(&mut self, generators: town::Generators) {
+        self.generator_points = generators.points;
+        self.road_generators = generators.road;
+        self.boundary_generators = generators.boundary;
+        self.relaxation_steps = generators.relaxation_steps;
+        self.generator_weights.clear();
+    }
+
+    // drop a generator, the fixed ranges shrink or shift down with it
+    pub fn remove_generator(&mut self, idx: usize) {
+        if idx >= self.generator_points.len() {
+            return;
+        }
+        self.generator_points.remove(idx);
+        if idx < self.generator_weights.len() {
+            self.generator_weights.remove(idx);
+        }
+        for range in [&mut self.road_generators, &mut self.boundary_generators] {
+            if idx < range.start {
+                *range = range.start - 1..range.end - 1;
+            } else if idx < range.end {
+                range.end -= 1;
+            }
+        }
+    }
+
+    // whether a cell belongs to one of the generators placed along the road
+    pub fn is_road_cell(&self, cell_idx: usize) -> bool {
+        self.cell_generators.get(cell_idx).is_some_and(|generator| self.road_generators.contains(generator))
+    }
+
+    // take the computed diagram from vpoly output, keeps cells and their generators in sync
+    pub fn apply_voronoi(&mut self, voronoi: SkeletonData) {
+        self.points = voronoi.points;
+        self.cells = voronoi.cells;
+        self.cell_generators = voronoi.cell_generators;
+        self.cell_adjacency = voronoi.cell_adjacency;
+    }
+
+    // drop a circumcenter, cells using it connect its neighbours directly
+    // indices above the removed one shift down by one
+    pub fn remove_circumcenter(&mut self, idx: usize) {
+        if idx >= self.points.len() {
+            return;
+        }
+        self.points.remove(idx);
+        for cell in self.cells.iter_mut() {
+            cell.retain(|&point_idx| point_idx != idx);
+            for point_idx in cell.iter_mut() {
+                if *point_idx > idx {
+                    *point_idx -= 1;
+                }
+            }
+        }
+        // dropped corners can leave two cells touching at a point only
+        self.cell_adjacency = poly::voronoi::cell_adjacency(&self.cells);
+    }
+
+    // pairs of cells sharing an edge, lower index first
+    // taken from the current cells rather than cell_adjacency, so it holds while cells are being edited
+    pub fn adjacent_cell_pairs(&self) -> Vec<(usize, usize)> {
+        // sorted, the merge rng below must see the pairs in a stable order
+        poly::voronoi::cell_adjacency(&self.cells).into_iter()
+            .enumerate()
+            .flat_map(|(a, neighbours)| neighbours.into_iter().filter(move |&b| b > a).map(move |b| (a, b)))
+            .collect()
+    }
+
+    // blocks as (cells, outline index loop), neighbouring cells merged with block_merge_prob
+    // a block is keyed by its lowest cell index, road cells are never merged
+    // groups whose union isn't a single loop fall back to separate cells
+    pub fn merged_blocks(&self, params: &Params, seed: u64) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let singles = |cells: &mut dyn Iterator<Item = usize>| -> Vec<(Vec<usize>, Vec<usize>)> {
+            cells.map(|cell_idx| (vec![cell_idx], self.cells[cell_idx].clone())).collect()
+        };
+        if params.block_merge_prob <= 0.0 {
+            return singles(&mut (0..self.cells.len()));
+        }
+
+        // union-find over cells
+        let mut parent: Vec<usize> = (0..self.cells.len()).collect();
+        fn root(parent: &mut [usize], mut idx: usize) -> usize {
+            while parent[idx] != idx {
+                parent[idx] = parent[parent[idx]];
+                idx = parent[idx];
+            }
+            idx
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed ^ BLOCK_MERGE_SALT);
+        for (a, b) in self.adjacent_cell_pairs() {
+            if self.is_road_cell(a) || self.is_road_cell(b) {
+                continue;
+            }
+            if rng.random::<f32>() < params.block_merge_prob {
+                let (root_a, root_b) = (root(&mut parent, a), root(&mut parent, b));
+                parent[root_a.max(root_b)] = root_a.min(root_b);
+            }
+        }
+
+        let mut groups: Vec<Vec<usize>> = vec![Vec::new(); self.cells.len()];
+        for cell_idx in 0..self.cells.len() {
+            let group = root(&mut parent, cell_idx);
+            groups[group].push(cell_idx);
+        }
+
+        let positions: Vec<Vec2> = self.points.iter().map(|p| Vec2::new(p.x, p.z)).collect();
+        let mut blocks = Vec::new();
+        for group in groups.into_iter().filter(|group| !group.is_empty()) {
+            if group.len() == 1 {
+                blocks.extend(singles(&mut group.into_iter()));
+                continue;
+            }
+            let cells: Vec<&[usize]> = group.iter().map(|&cell_idx| self.cells[cell_idx].as_slice()).collect();
+            match poly::utils::union_index_polygons(&cells, &positions) {
+                Some(outline) => blocks.push((group, outline)),
+                None => blocks.extend(singles(&mut group.into_iter())),
+            }
+        }
+        blocks.sort_by_key(|(cells, _)| cells[0]);
+        blocks
+    }
+
     pub fn is_valid(&self) -> bool {
+        self.validate().is_empty()
+    }
+
+    // every problem in the diagram, cell issues name the generator that owns the cell
+    pub fn validate(&self) -> Vec<ValidityIssue> {
         if self.points.is_empty() || self.cells.is_empty() {
-            return false;
+            return vec![ValidityIssue::Empty];
         }
 
+        let mut issues = Vec::new();
+
         // check all indices are within bounds
-        for cell in &self.cells {
+        for (cell_idx, cell) in self.cells.iter().enumerate() {
+            let generator = self.cell_generators.get(cell_idx).copied();
+
             if cell.len() < 3 {
-                return false; // cells must have at least 3 points
+                // cells must have at least 3 points
+                issues.push(ValidityIssue::TooFewVertices { cell: cell_idx, generator });
+                continue;
             }
 
-            for &point_idx in cell {
-                if point_idx >= self.points.len() {
-                    return false; // idx out of bounds
-                }
+            if cell.iter().any(|&point_idx| point_idx >= self.points.len()) {
+                // idx out of bounds
+                issues.push(ValidityIssue::IndexOutOfBounds { cell: cell_idx, generator });
+                continue;
             }
 
             let cell_points: Vec<_> = cell.iter()
@@ -160,7 +1004,7 @@ impl SkeletonData {
 
             let area = poly::utils::polygon_area(&cell_points);
             if area.abs() < f32::EPSILON {
-                return false; // degenerate cell
+                issues.push(ValidityIssue::DegenerateCell { cell: cell_idx, generator });
             }
         }
 
@@ -170,15 +1014,82 @@ impl SkeletonData {
             for j in (i + 1)..self.points.len() {
                 let dist = self.points[i].distance(self.points[j]);
                 if dist < EPSILON {
-                    return false;
+                    issues.push(ValidityIssue::DuplicatePoints(i, j));
                 }
             }
         }
 
-        true
+        issues
     }
 }
 
+// a problem found by SkeletonData::validate
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ValidityIssue {
+    Empty,
+    TooFewVertices { cell: usize, generator: Option<usize> },
+    IndexOutOfBounds { cell: usize, generator: Option<usize> },
+    DegenerateCell { cell: usize, generator: Option<usize> },
+    DuplicatePoints(usize, usize),
+}
+
+impl ValidityIssue {
+    pub fn describe(&self) -> String {
+        let owner = |generator: &Option<usize>| match generator {
+            Some(idx) => format!(" (generator {})", idx),
+            None => String::new(),
+        };
+        match self {
+            ValidityIssue::Empty => "no points or cells".to_string(),
+            ValidityIssue::TooFewVertices { cell, generator } => format!("cell {} has < 3 vertices{}", cell, owner(generator)),
+            ValidityIssue::IndexOutOfBounds { cell, generator } => format!("cell {} has a bad vertex index{}", cell, owner(generator)),
+            ValidityIssue::DegenerateCell { cell, generator } => format!("degenerate cell {}{}", cell, owner(generator)),
+            ValidityIssue::DuplicatePoints(a, b) => format!("duplicate points {}/{}", a, b),
+        }
+    }
+
+    // the cell an issue is about, if any
+    pub fn cell(&self) -> Option<usize> {
+        match self {
+            ValidityIssue::TooFewVertices { cell, .. }
+            | ValidityIssue::IndexOutOfBounds { cell, .. }
+            | ValidityIssue::DegenerateCell { cell, .. } => Some(*cell),
+            ValidityIssue::Empty | ValidityIssue::DuplicatePoints(..) => None,
+        }
+    }
+
+    // where the camera should look to see the issue
+    // cells use the mean of their valid vertices, falling back to the owning generator
+    pub fn location(&self, data: &SkeletonData) -> Option<Vec2> {
+        let to_2d = |p: &Vec3| Vec2::new(p.x, p.z);
+        match self {
+            ValidityIssue::Empty => None,
+            ValidityIssue::DuplicatePoints(a, _) => data.points.get(*a).map(to_2d),
+            ValidityIssue::TooFewVertices { cell, generator }
+            | ValidityIssue::IndexOutOfBounds { cell, generator }
+            | ValidityIssue::DegenerateCell { cell, generator } => {
+                let vertices: Vec<Vec2> = data.cells.get(*cell)?.iter()
+                    .filter_map(|&idx| data.points.get(idx))
+                    .map(to_2d)
+                    .collect();
+                if vertices.is_empty() {
+                    generator.and_then(|idx| data.generator_points.get(idx)).map(to_2d)
+                } else {
+                    Some(vertices.iter().sum::<Vec2>() / vertices.len() as f32)
+                }
+            }
+        }
+    }
+}
+
+// jump to an entry of the validity report: select it, focus the camera and highlight it
+#[derive(Event)]
+pub struct JumpToIssueEvent(pub ValidityIssue);
+
+// issue picked from the validity report, outlined until it is fixed
+#[derive(Resource, Default)]
+pub struct HighlightedIssue(pub Option<ValidityIssue>);
+
 
 // main plugin for generation
 pub struct BuildingGenerationPlugin;
@@ -192,28 +1103,21 @@ impl Plugin for BuildingGenerationPlugin {
             // generate initial points on start
             .insert_resource({
                 let params = Params::default();
-                let boundary_polygon = poly::point_gen::generate_boundary_polygon(params.boundary_vertex_count, params.boundary_scale, INITIAL_SEED);
-                let boundary_generators = poly::point_gen::generate_boundary_generators(&boundary_polygon, crate::config::BOUNDARY_GENERATOR_SPACING, crate::config::BOUNDARY_GENERATOR_INNER_OFFSET);
-                let regular_generators = poly::point_gen::pgen(
-                    params.generator_count, 
-                    CANVAS_WIDTH, 
-                    CANVAS_HEIGHT, 
-                    SPIRAL_SPREAD, 
-                    INITIAL_SEED
-                );
-                let all_generators = poly::point_gen::prelax(
-                    regular_generators,
-                    boundary_generators,
-                    4, 
-                    CANVAS_WIDTH, 
-                    CANVAS_HEIGHT
-                );
-                let voronoi_data = poly::voronoi::vpoly(all_generators.clone(), &boundary_polygon, crate::config::CIRCUMCENTER_MERGE_THRESHOLD);
+                let boundary_polygon = poly::point_gen::generate_boundary_polygon(params.boundary_vertex_count, params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, INITIAL_SEED);
+                let generators = town::generate_generators(&params, &boundary_polygon, &[], INITIAL_SEED);
+                let voronoi_data = poly::voronoi::vpoly(generators.points.clone(), None, &boundary_polygon, crate::config::CIRCUMCENTER_MERGE_THRESHOLD);
                 SkeletonData {
-                    generator_points: all_generators,
+                    generator_points: generators.points,
+                    generator_weights: Vec::new(),
                     points: voronoi_data.points,
                     cells: voronoi_data.cells,
+                    cell_generators: voronoi_data.cell_generators,
+                    cell_adjacency: voronoi_data.cell_adjacency,
                     road_path: Vec::new(),
+                    road_widths: Vec::new(),
+                    road_generators: generators.road,
+                    boundary_generators: generators.boundary,
+                    relaxation_steps: generators.relaxation_steps,
                     boundary_polygon: boundary_polygon.clone(),
                     boundary_vertex_offsets: vec![Vec2::ZERO; boundary_polygon.len()],
                 }
@@ -221,13 +1125,46 @@ impl Plugin for BuildingGenerationPlugin {
 
             .insert_resource(EditMode::default())
             .insert_resource(DragState::default())
+            .insert_resource(EditHistory::default())
             .insert_resource(HoveredPoint::default())
             .insert_resource(SelectedPoint::default())
+            .insert_resource(SelectedPoints::default())
+            .insert_resource(HoveredBuilding::default())
+            .insert_resource(SelectedBuilding::default())
+            .insert_resource(ParamLocks::default())
+            .insert_resource(BuildingAdjacency::default())
+            .insert_resource(BlockOverrides::default())
+            .insert_resource(BlockSeeds::default())
+            .init_resource::<WallTextures>()
+            .insert_resource(HighlightedIssue::default())
+            .insert_resource(crate::systems::export::ExportSettings::default())
+            .insert_resource(crate::systems::import::ImportSettings::default())
+            .insert_resource(crate::systems::persistence::SceneFile::default())
+            .insert_resource(crate::systems::persistence::PresetStore::load(crate::config::PRESETS_FILE))
 
             .add_event::<RegenerateEvent>()
+            .add_event::<RebuildEvent>()
             .add_event::<ClearEvent>()
             .add_event::<RelaxEvent>()
+            .add_event::<RegularizeBoundaryEvent>()
+            .add_event::<CarveRoadEvent>()
+            .add_event::<FitBoundaryEvent>()
+            .add_event::<JumpToIssueEvent>()
+            .add_event::<RerollBlockEvent>()
+            .add_event::<GeneratorWeightEvent>()
+            .add_event::<RoadWidthEvent>()
+            .add_event::<crate::systems::import::ImportGeneratorsEvent>()
+            .add_event::<crate::systems::import::ImportBoundaryEvent>()
+            .add_event::<crate::systems::persistence::SaveSceneEvent>()
+            .add_event::<crate::systems::persistence::LoadSceneEvent>()
+            .add_event::<crate::systems::export::ExportPlanEvent>()
+            .add_event::<crate::systems::export::ExportMorphEvent>()
+            .add_event::<crate::systems::export::ExportTilesEvent>()
+            .insert_resource(crate::systems::export::PlanCapture::default())
             .add_event::<crate::systems::export::ExportEvent>()
+            .add_event::<crate::systems::export::ExportCentroidsEvent>()
+            .add_event::<crate::systems::export::ExportJsonEvent>()
+            .add_event::<crate::systems::export::ExportGeoJsonEvent>()
 
             // add startup town generation pipeline
             .add_systems(Startup, |mut commands: Commands, 
@@ -236,13 +1173,17 @@ impl Plugin for BuildingGenerationPlugin {
                                    seed: Res<Seed>, 
                                    params: Res<Params>, 
                                    mut skeleton_data: ResMut<SkeletonData>,
+                                   overrides: Res<BlockOverrides>,
+                                   block_seeds: Res<BlockSeeds>,
+                                   wall_textures: Res<WallTextures>,
                                    is_3d: Res<crate::systems::ui::Is3D>| {
-                town::generate_town(&mut commands, &mut meshes, &mut materials, seed.0, &params, &mut skeleton_data, is_3d.0);
+                town::generate_town(&mut commands, &mut meshes, &mut materials, seed.0, &params, &mut skeleton_data, &overrides, &block_seeds, &wall_textures, is_3d.0);
             })
-            .add_systems(Update, (debug_gizmos, town::handle_regeneration, crate::systems::export::handle_export));
+            .add_systems(Update, (debug_gizmos, town::handle_regeneration, town::handle_block_reroll, town::handle_regularize_boundary, town::handle_fit_boundary, town::handle_generator_weight, town::handle_road_width, crate::systems::import::handle_import_generators, crate::systems::import::handle_import_boundary, crate::systems::persistence::handle_save_scene, crate::systems::persistence::handle_load_scene, town::despawn_disabled_ghosts, town::handle_road_carve, town::update_building_lod, (crate::systems::export::handle_export, crate::systems::export::handle_export_centroids, crate::systems::export::handle_export_json, crate::systems::export::handle_export_geojson, crate::systems::export::handle_export_plan, crate::systems::export::handle_export_morph, crate::systems::export::handle_export_tiles)));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn debug_gizmos(
     mut gizmos: Gizmos,
     skeleton: Res<SkeletonData>,
@@ -251,19 +1192,42 @@ fn debug_gizmos(
     drag_state: Res<DragState>,
     hovered_point: Res<HoveredPoint>,
     selected_point: Res<SelectedPoint>,
+    selected_points: Res<SelectedPoints>,
+    layers: Res<crate::systems::ui::GizmoLayers>,
+    overrides: Res<BlockOverrides>,
+    params: Res<Params>,
+    mut highlighted: ResMut<HighlightedIssue>,
 ) {
     if !gizmos_visible.0 {
         return;
     }
 
+    // drop the highlight once an edit fixed the issue
+    if let Some(issue) = highlighted.0 && skeleton.is_changed() && !skeleton.validate().contains(&issue) {
+        highlighted.0 = None;
+    }
+
+    // hidden layers are drawn from empty slices
+    let generator_points: &[Vec3] = if layers.generators { &skeleton.generator_points } else { &[] };
+    let circumcenters: &[Vec3] = if layers.circumcenters { &skeleton.points } else { &[] };
+    let cells: &[Vec<usize>] = if layers.cell_edges { &skeleton.cells } else { &[] };
+    let is_selected = |i: usize| selected_point.0 == Some(i) || selected_points.0.contains(&i);
+
+    // rubber-band selection box
+    if let Some(start) = drag_state.box_start {
+        let end = drag_state.box_end;
+        let corners = [start, Vec2::new(end.x, start.y), end, Vec2::new(start.x, end.y), start];
+        gizmos.linestrip(corners.map(|corner| Vec3::new(corner.x, 0.03, corner.y)), Color::srgba(1.0, 1.0, 0.0, 0.8));
+    }
+
     // draw points based on current edit mode
     match *edit_mode {
         EditMode::Generators => {
             // draw generator points (active editing)
-            for (i, point) in skeleton.generator_points.iter().enumerate() {
+            for (i, point) in generator_points.iter().enumerate() {
                 let (color, radius) = if Some(i) == drag_state.dragging_point_index {
                     (Color::srgba(0.0, 1.0, 0.0, 0.8), 1.2) // green for dragging
-                } else if Some(i) == selected_point.0 {
+                } else if is_selected(i) {
                     (Color::srgba(1.0, 1.0, 0.0, 0.8), 1.0) // yellow for selected
                 } else if Some(i) == hovered_point.0 {
                     (Color::srgba(1.0, 0.5, 0.0, 0.7), 0.8) // orange for hovered
@@ -280,16 +1244,25 @@ fn debug_gizmos(
             }
             
             // draw circumcenters (reference only)
-            for point in skeleton.points.iter() {
+            for point in circumcenters.iter() {
                 gizmos.sphere(Vec3::new(point.x, 0.005, point.z), 0.15, Color::srgba(0.53, 0.18, 0.85, 0.3));
             }
+
+            // user placed settlement core
+            if let Some(core) = params.core_point {
+                let core = Vec3::new(core.x, 0.02, core.y);
+                let color = Color::srgba(0.9, 0.2, 0.7, 0.9); // magenta
+                gizmos.sphere(core, 1.5, color);
+                gizmos.line(core - Vec3::X * 2.5, core + Vec3::X * 2.5, color);
+                gizmos.line(core - Vec3::Z * 2.5, core + Vec3::Z * 2.5, color);
+            }
         }
         EditMode::Circumcenters => {
             // draw circumcenter points (active editing)
-            for (i, point) in skeleton.points.iter().enumerate() {
+            for (i, point) in circumcenters.iter().enumerate() {
                 let (color, radius) = if Some(i) == drag_state.dragging_point_index {
                     (Color::srgba(0.0, 1.0, 0.0, 0.8), 1.0) // green for dragging
-                } else if Some(i) == selected_point.0 {
+                } else if is_selected(i) {
                     (Color::srgba(1.0, 1.0, 0.0, 0.8), 0.8) // yellow for selected
                 } else if Some(i) == hovered_point.0 {
                     (Color::srgba(1.0, 0.5, 0.0, 0.7), 0.6) // orange for hovered
@@ -301,7 +1274,7 @@ fn debug_gizmos(
             }
             
             // draw generators (reference only)
-            for point in skeleton.generator_points.iter() {
+            for point in generator_points.iter() {
                 gizmos.cuboid(
                     Transform::from_translation(Vec3::new(point.x, 0.005, point.z))
                         .with_scale(Vec3::splat(0.15)),
@@ -311,7 +1284,7 @@ fn debug_gizmos(
         }
         EditMode::Roads => {
             // draw single road path
-            if !skeleton.road_path.is_empty() {
+            if layers.road && !skeleton.road_path.is_empty() {
                 let line_color = Color::srgba(0.24, 0.55, 0.31, 0.9); // green
                 let point_color = Color::srgba(0.24, 0.55, 0.31, 0.8);
                 
@@ -319,7 +1292,7 @@ fn debug_gizmos(
                 for (point_idx, point) in skeleton.road_path.iter().enumerate() {
                     let (color, radius) = if drag_state.dragging_point_index == Some(point_idx) {
                         (Color::srgba(0.0, 1.0, 0.0, 0.8), 0.8) // green for dragging
-                    } else if selected_point.0 == Some(point_idx) {
+                    } else if is_selected(point_idx) {
                         (Color::srgba(1.0, 1.0, 0.0, 0.8), 0.7) // yellow for selected
                     } else if hovered_point.0 == Some(point_idx) {
                         (Color::srgba(1.0, 0.5, 0.0, 0.7), 0.65) // orange for hovered
@@ -330,37 +1303,35 @@ fn debug_gizmos(
                     gizmos.sphere(Vec3::new(point.x, 0.02, point.z), radius, color);
                 }
                 
-                // draw road lines connecting points with thick line
-                for i in 0..(skeleton.road_path.len().saturating_sub(1)) {
-                    let start = skeleton.road_path[i];
-                    let end = skeleton.road_path[i + 1];
-                    
-                    // thick line effect with multiple parallel lines
-                    for offset in [-0.05, 0.0, 0.05] {
-                        gizmos.line(
-                            Vec3::new(start.x + offset, start.y + 0.01, start.z),
-                            Vec3::new(end.x + offset, end.y + 0.01, end.z),
-                            line_color
-                        );
+                // road drawn at the width it gets carved with, so the preview matches the corridor
+                let road_path = poly::point_gen::smooth_road_path(&skeleton.road_path, params.road_smoothing);
+                let path: Vec<Vec2> = road_path.iter().map(|point| Vec2::new(point.x, point.z)).collect();
+                if layers.road_corridor {
+                    // long edges of each segment's corridor quad, widths vary per segment
+                    for quad in town::road_corridor_quads(&road_path, &skeleton.smoothed_road_widths(params.road_smoothing)) {
+                        gizmos.line(Vec3::new(quad[0].x, 0.01, quad[0].y), Vec3::new(quad[1].x, 0.01, quad[1].y), line_color);
+                        gizmos.line(Vec3::new(quad[3].x, 0.01, quad[3].y), Vec3::new(quad[2].x, 0.01, quad[2].y), line_color);
                     }
                 }
+                // center line
+                gizmos.linestrip(path.iter().map(|p| Vec3::new(p.x, 0.01, p.y)), line_color);
             }
             
             // draw generators (reference only)
-            for point in skeleton.generator_points.iter() {
+            for point in generator_points.iter() {
                 gizmos.cuboid(
                     Transform::from_translation(Vec3::new(point.x, 0.005, point.z))
                         .with_scale(Vec3::splat(0.1)),
                     Color::srgba(0.18, 0.28, 0.45, 0.2)
                 );
             }
-            for point in skeleton.points.iter() {
+            for point in circumcenters.iter() {
                 gizmos.sphere(Vec3::new(point.x, 0.005, point.z), 0.1, Color::srgba(0.53, 0.18, 0.85, 0.2));
             }
         }
         EditMode::Boundary => {
             // draw boundary polygon vertices (active editing)
-            let boundary = &skeleton.boundary_polygon;
+            let boundary: &[Vec2] = if layers.boundary { &skeleton.boundary_polygon } else { &[] };
             let boundary_color = Color::srgba(0.71, 0.24, 0.24, 0.9); // red
             let line_color = Color::srgba(0.71, 0.24, 0.24, 0.7);
             
@@ -368,7 +1339,7 @@ fn debug_gizmos(
             for (vertex_idx, vertex) in boundary.iter().enumerate() {
                 let (color, radius) = if drag_state.dragging_point_index == Some(vertex_idx) {
                     (Color::srgba(0.0, 1.0, 0.0, 0.8), 0.8) // green for dragging
-                } else if selected_point.0 == Some(vertex_idx) {
+                } else if is_selected(vertex_idx) {
                     (Color::srgba(1.0, 1.0, 0.0, 0.8), 0.7) // yellow for selected
                 } else if hovered_point.0 == Some(vertex_idx) {
                     (Color::srgba(1.0, 0.5, 0.0, 0.7), 0.65) // orange for hovered
@@ -392,17 +1363,42 @@ fn debug_gizmos(
             }
             
             // draw generators and circumcenters (reference only)
-            for point in skeleton.generator_points.iter() {
+            for point in generator_points.iter() {
                 gizmos.cuboid(
                     Transform::from_translation(Vec3::new(point.x, 0.005, point.z))
                         .with_scale(Vec3::splat(0.1)),
                     Color::srgba(0.0, 0.0, 1.0, 0.2)
                 );
             }
-            for point in skeleton.points.iter() {
+            for point in circumcenters.iter() {
                 gizmos.sphere(Vec3::new(point.x, 0.005, point.z), 0.1, Color::srgba(1.0, 0.0, 0.0, 0.2));
             }
         }
+        EditMode::Blocks => {
+            // outline overridden, hovered and selected blocks
+            for (cell_idx, cell) in skeleton.cells.iter().enumerate() {
+                let color = if Some(cell_idx) == selected_point.0 {
+                    Color::srgba(1.0, 1.0, 0.0, 0.9) // yellow for selected
+                } else if Some(cell_idx) == hovered_point.0 {
+                    Color::srgba(1.0, 0.5, 0.0, 0.8) // orange for hovered
+                } else {
+                    match overrides.0.get(&cell_idx) {
+                        Some(BlockOverride::Whole) => Color::srgba(0.85, 0.25, 0.25, 0.8), // red, not subdivided
+                        Some(BlockOverride::Finer(_)) => Color::srgba(0.25, 0.75, 0.85, 0.8), // cyan, finer
+                        None => continue,
+                    }
+                };
+
+                for i in 0..cell.len() {
+                    let (Some(start), Some(end)) = (skeleton.points.get(cell[i]), skeleton.points.get(cell[(i + 1) % cell.len()])) else { continue };
+                    gizmos.line(
+                        Vec3::new(start.x, 0.02, start.z),
+                        Vec3::new(end.x, 0.02, end.z),
+                        color
+                    );
+                }
+            }
+        }
     }
 
     // // draw circumcenter points
@@ -410,8 +1406,45 @@ fn debug_gizmos(
     //     gizmos.sphere(*point, 0.5, bevy::color::palettes::basic::RED);
     // }
     
+    // generator -> cell ownership, a faint star per generator
+    if layers.generator_stars {
+        for (cell, &generator_idx) in skeleton.cells.iter().zip(skeleton.cell_generators.iter()) {
+            let Some(generator) = skeleton.generator_points.get(generator_idx) else { continue };
+            for &point_idx in cell {
+                if let Some(vertex) = skeleton.points.get(point_idx) {
+                    gizmos.line(*generator, *vertex, Color::srgba(0.4, 0.7, 1.0, 0.25));
+                }
+            }
+        }
+    }
+
+    // issue picked from the validity report, red outline for cells, rings for duplicate points
+    if let Some(issue) = highlighted.0 {
+        let highlight_color = Color::srgba(1.0, 0.1, 0.1, 1.0);
+        if let ValidityIssue::DuplicatePoints(a, b) = issue {
+            for point in [a, b].iter().filter_map(|&idx| skeleton.points.get(idx)) {
+                gizmos.circle(
+                    Isometry3d::new(Vec3::new(point.x, 0.03, point.z), Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+                    0.6,
+                    highlight_color,
+                );
+            }
+        } else if let Some(cell) = issue.cell().and_then(|cell| skeleton.cells.get(cell)) {
+            let vertices: Vec<Vec3> = cell.iter()
+                .filter_map(|&idx| skeleton.points.get(idx))
+                .map(|p| Vec3::new(p.x, 0.03, p.z))
+                .collect();
+            if vertices.len() >= 2 {
+                gizmos.linestrip(vertices.iter().chain(vertices.first()).copied(), highlight_color);
+            }
+            for vertex in &vertices {
+                gizmos.sphere(*vertex, 0.3, highlight_color);
+            }
+        }
+    }
+
     // draw Voronoi cell boundaries
-    for cell in &skeleton.cells {
+    for cell in cells {
         if cell.len() >= 3 {
             // basically iterate through the circumcenters and draw edges between them
             for i in 0..cell.len() {
@@ -460,6 +1493,10 @@ fn debug_gizmos(
                             // Very faded Voronoi lines for reference in boundary mode
                             gizmos.line(start, end, Color::srgba(1.0, 1.0, 1.0, 0.05));
                         }
+                        EditMode::Blocks => {
+                            // Faded Voronoi lines, highlighted blocks are drawn above
+                            gizmos.line(start, end, Color::srgba(1.0, 1.0, 1.0, 0.2));
+                        }
                     }
                 }
             }
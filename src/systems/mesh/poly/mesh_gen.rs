@@ -2,15 +2,31 @@ use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology};
 use bevy::render::render_asset::RenderAssetUsages;
 
-use crate::systems::mesh::Polygon;
-use super::utils::{polygon_area, polygon_centroid};
+use crate::systems::mesh::{Polygon, UvMode};
+use super::utils::{polygon_area, triangulate_polygon};
 
-// TODO: may need to replace the center-point based approach, as it may not work for all types of footprints
-//  for example, in extreme cases of shapes where the centroid falls outside of the polygon, face filling is impossible
-//  but this kind of shape shouldn't happen in the first place...
+// caps are ear clipped, so concave footprints (L/U shaped buildings) fill correctly
+// triangles come back CCW in the xz plane, which faces down once mapped to 3D (x, y) -> (x, z)
+
+// planar UV for a horizontal (footprint/cap) vertex
+// BoundsFit stretches 0..1 over the polygon bounds, WorldPlanar tiles continuously across polygons
+fn planar_uv(vertex: Vec2, min: Vec2, max: Vec2, uv_mode: UvMode, tile_size: f32) -> [f32; 2] {
+    match uv_mode {
+        UvMode::BoundsFit => [(vertex.x - min.x) / (max.x - min.x), (vertex.y - min.y) / (max.y - min.y)],
+        UvMode::WorldPlanar => [vertex.x / tile_size, vertex.y / tile_size],
+    }
+}
+
+// polygon bounds as (min, max)
+fn polygon_bounds(polygon: &Polygon) -> (Vec2, Vec2) {
+    polygon.iter().fold(
+        (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+        |(min, max), v| (min.min(*v), max.max(*v)),
+    )
+}
 
 // create the footprint mesh
-pub fn polygon_to_layer_zero(polygon: &Polygon) -> Mesh {
+pub fn polygon_to_layer_zero(polygon: &Polygon, uv_mode: UvMode, tile_size: f32) -> Mesh {
     if polygon.len() < 3 {
         return Mesh::new(
             PrimitiveTopology::TriangleList,
@@ -23,34 +39,20 @@ pub fn polygon_to_layer_zero(polygon: &Polygon) -> Mesh {
     let mut uvs = Vec::new();
     let mut indices = Vec::new();
 
-    let centroid = polygon_centroid(polygon, polygon_area(polygon));
-    
-    // add center vertex
-    positions.push([centroid.x, 0.0, centroid.y]);
-    normals.push([0.0, 1.0, 0.0]);
-    uvs.push([0.5, 0.5]);
-    
-    let center_idx = 0u32;
+    let (min, max) = polygon_bounds(polygon);
     
     // add polygon vertices
-    for (i, vertex) in polygon.iter().enumerate() {
+    for vertex in polygon.iter() {
         positions.push([vertex.x, 0.0, vertex.y]);
         normals.push([0.0, 1.0, 0.0]);
         
-        // UV coordinates based on position relative to bounds
-        let min_x = polygon.iter().map(|v| v.x).fold(f32::INFINITY, f32::min);
-        let max_x = polygon.iter().map(|v| v.x).fold(f32::NEG_INFINITY, f32::max);
-        let min_y = polygon.iter().map(|v| v.y).fold(f32::INFINITY, f32::min);
-        let max_y = polygon.iter().map(|v| v.y).fold(f32::NEG_INFINITY, f32::max);
-        
-        let u = (vertex.x - min_x) / (max_x - min_x);
-        let v = (vertex.y - min_y) / (max_y - min_y);
-        uvs.push([u, v]);
-        
-        // create triangle from center to edge
-        // counter-clockwise
-        let next_idx = if i + 1 < polygon.len() { i + 1 } else { 0 };
-        indices.extend([center_idx, (next_idx + 1) as u32, (i + 1) as u32]);
+        // UV coordinates based on position relative to bounds (or world position)
+        uvs.push(planar_uv(*vertex, min, max, uv_mode, tile_size));
+    }
+
+    // flipped so the footprint faces up
+    for [a, b, c] in triangulate_polygon(polygon) {
+        indices.extend([a as u32, c as u32, b as u32]);
     }
     
     let mut mesh = Mesh::new(
@@ -67,7 +69,12 @@ pub fn polygon_to_layer_zero(polygon: &Polygon) -> Mesh {
 }
 
 // build 3D mesh from polygon footprint
-pub fn polygon_to_building(polygon: &Polygon, wall_height: f32) -> Mesh {
+// base_embed sinks the bottom below y=0 so buildings read as planted in the ground
+// wall UVs are in tiles of meters_per_tile, u runs on around the perimeter so textures wrap corners
+// walls are cut at every floor_height with a uv seam, v restarts at each floor (0 = one band)
+// openings cut recessed windows and a door into the walls, one row per floor, None keeps plain wall quads
+#[allow(clippy::too_many_arguments)]
+pub fn polygon_to_building(polygon: &Polygon, wall_height: f32, base_embed: f32, uv_mode: UvMode, tile_size: f32, meters_per_tile: f32, floor_height: f32, openings: Option<WallOpenings>) -> Mesh {
     if polygon.len() < 3 {
         return Mesh::new(
             PrimitiveTopology::TriangleList,
@@ -79,6 +86,14 @@ pub fn polygon_to_building(polygon: &Polygon, wall_height: f32) -> Mesh {
     let mut normals = Vec::new();
     let mut uvs = Vec::new();
     let mut indices = Vec::new();
+    let base = -base_embed;
+    let tile = meters_per_tile.max(f32::EPSILON);
+    let mut perimeter = 0.0;
+    let floors = floor_levels(base, wall_height, floor_height);
+    let plan = openings.and_then(|openings| plan_openings(polygon, &floors, wall_height, floor_height, openings));
+    let mut walls = MeshBuffers::default();
+    // recesses go into the building whichever way the footprint winds
+    let winding = if polygon_area(polygon) < 0.0 { -1.0 } else { 1.0 };
 
     // walls
     for i in 0..polygon.len() {
@@ -87,61 +102,69 @@ pub fn polygon_to_building(polygon: &Polygon, wall_height: f32) -> Mesh {
         let v2 = polygon[next];
         let edge = v2 - v1;
         let normal = Vec2::new(edge.y, -edge.x).normalize();
-        let base_idx = positions.len() as u32;
 
-        // wall quad
-        positions.extend([
-            [v1.x, 0.0, v1.y],
-            [v2.x, 0.0, v2.y],
-            [v1.x, wall_height, v1.y],
-            [v2.x, wall_height, v2.y]
-        ]);
+        if let Some((levels, per_edge)) = &plan {
+            push_opened_wall(&mut walls, (v1, v2), winding, levels, &per_edge[i], perimeter, tile, floor_height);
+            perimeter += edge.length();
+            continue;
+        }
+        let (u0, u1) = (perimeter / tile, (perimeter + edge.length()) / tile);
+        perimeter += edge.length();
+
+        // one wall quad per floor
+        for band in floors.windows(2) {
+            let (bottom, top) = (band[0], band[1]);
+            let base_idx = positions.len() as u32;
+            positions.extend([
+                [v1.x, bottom, v1.y],
+                [v2.x, bottom, v2.y],
+                [v1.x, top, v1.y],
+                [v2.x, top, v2.y]
+            ]);
 
-        normals.extend([[normal.x, 0.0, normal.y]; 4]);
+            normals.extend([[normal.x, 0.0, normal.y]; 4]);
 
-        let edge_length = edge.length();
-        uvs.extend([
-            [0.0, 0.0], [edge_length, 0.0],
-            [0.0, wall_height], [edge_length, wall_height]
-        ]);
+            let floor = floor_start(bottom, floor_height);
+            let (v0, v1) = ((bottom - floor) / tile, (top - floor) / tile);
+            uvs.extend([
+                [u0, v0], [u1, v0],
+                [u0, v1], [u1, v1]
+            ]);
 
-        indices.extend([base_idx, base_idx + 2, base_idx + 1]);
-        indices.extend([base_idx + 1, base_idx + 2, base_idx + 3]);
+            indices.extend([base_idx, base_idx + 2, base_idx + 1]);
+            indices.extend([base_idx + 1, base_idx + 2, base_idx + 3]);
+        }
     }
 
-    // caps
-    let centroid = polygon_centroid(polygon, polygon_area(polygon));
+    // opened walls are only ever built together, nothing is in the buffers yet
+    if plan.is_some() {
+        (positions, normals, uvs, indices) = (walls.positions, walls.normals, walls.uvs, walls.indices);
+    }
+
+    // caps, both share one triangulation
+    let (min, max) = polygon_bounds(polygon);
+    let triangles = triangulate_polygon(polygon);
     
     // bottom cap (facing down)
-    let bottom_center = positions.len() as u32;
-    positions.push([centroid.x, 0.0, centroid.y]);
-    normals.push([0.0, -1.0, 0.0]);
-    uvs.push([0.5, 0.5]);
-    
-    for i in 0..polygon.len() {
-        let vertex = polygon[i];
-        positions.push([vertex.x, 0.0, vertex.y]);
+    let bottom_start = positions.len() as u32;
+    for vertex in polygon.iter() {
+        positions.push([vertex.x, base, vertex.y]);
         normals.push([0.0, -1.0, 0.0]);
-        uvs.push([0.0, 0.0]);
-        
-        let next_i = (i + 1) % polygon.len();
-        indices.extend([bottom_center, bottom_center + 1 + i as u32, bottom_center + 1 + next_i as u32]);
+        uvs.push(planar_uv(*vertex, min, max, uv_mode, tile_size));
+    }
+    for [a, b, c] in &triangles {
+        indices.extend([bottom_start + *a as u32, bottom_start + *b as u32, bottom_start + *c as u32]);
     }
 
     // top cap (facing up)
-    let top_center = positions.len() as u32;
-    positions.push([centroid.x, wall_height, centroid.y]);
-    normals.push([0.0, 1.0, 0.0]);
-    uvs.push([0.5, 0.5]);
-    
-    for i in 0..polygon.len() {
-        let vertex = polygon[i];
+    let top_start = positions.len() as u32;
+    for vertex in polygon.iter() {
         positions.push([vertex.x, wall_height, vertex.y]);
         normals.push([0.0, 1.0, 0.0]);
-        uvs.push([0.0, 0.0]);
-        
-        let next_i = (i + 1) % polygon.len();
-        indices.extend([top_center, top_center + 1 + next_i as u32, top_center + 1 + i as u32]);
+        uvs.push(planar_uv(*vertex, min, max, uv_mode, tile_size));
+    }
+    for [a, b, c] in &triangles {
+        indices.extend([top_start + *a as u32, top_start + *c as u32, top_start + *b as u32]);
     }
 
     let mut mesh = Mesh::new(
@@ -155,4 +178,631 @@ pub fn polygon_to_building(polygon: &Polygon, wall_height: f32) -> Mesh {
     mesh.insert_indices(Indices::U32(indices));
 
     mesh
-}
\ No newline at end of file
+}
+
+// stand-in building for distant views, walls and a flat top without openings or floor seams
+// lod 0 keeps the footprint, lod 1 and up collapse it to its oriented bounding box (a plain box)
+pub fn polygon_to_building_lod(polygon: &Polygon, height: f32, lod: u32) -> Mesh {
+    use crate::config::{TEXTURE_METERS_PER_TILE, UV_TILE_SIZE};
+    let outline = match lod {
+        0 => polygon.clone(),
+        _ => super::ops::oriented_bounding_box(polygon),
+    };
+    polygon_to_building(&outline, height, 0.0, UvMode::BoundsFit, UV_TILE_SIZE, TEXTURE_METERS_PER_TILE, 0.0, None)
+}
+
+// heights the walls are cut at: base, each floor line, top
+fn floor_levels(base: f32, wall_height: f32, floor_height: f32) -> Vec<f32> {
+    let mut levels = vec![base];
+    if floor_height > 0.0 {
+        levels.extend((1..)
+            .map(|floor| floor as f32 * floor_height)
+            .take_while(|&level| level < wall_height - 1e-4)
+            .filter(|&level| level > base + 1e-4));
+    }
+    levels.push(wall_height);
+    levels
+}
+
+// floor line at or below `height`, wall uv v is measured from it
+// the ground floor also covers base_embed below 0
+fn floor_start(height: f32, floor_height: f32) -> f32 {
+    if floor_height > 0.0 {
+        ((height.max(0.0) + 1e-4) / floor_height).floor() * floor_height
+    } else {
+        0.0
+    }
+}
+
+// recessed windows and a door cut into a building's walls
+#[derive(Clone, Copy)]
+pub struct WallOpenings {
+    pub window_density: f32, // chance each window slot gets a window
+    pub seed: u64,           // per building, the same building always gets the same openings
+}
+
+// one opening on a wall, u along the edge from its start, v as heights
+struct Opening {
+    u0: f32,
+    u1: f32,
+    v0: f32,
+    v1: f32,
+}
+
+// vertex buffers of a mesh under construction
+#[derive(Default)]
+struct MeshBuffers {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBuffers {
+    // one triangle, wound so it faces along `normal`
+    fn triangle(&mut self, corners: [(Vec3, [f32; 2]); 3], normal: Vec3) {
+        let [a, mut b, mut c] = corners;
+        if (b.0 - a.0).cross(c.0 - a.0).dot(normal) < 0.0 {
+            std::mem::swap(&mut b, &mut c);
+        }
+        let base_idx = self.positions.len() as u32;
+        for (position, uv) in [a, b, c] {
+            self.positions.push(position.to_array());
+            self.normals.push(normal.to_array());
+            self.uvs.push(uv);
+        }
+        self.indices.extend([base_idx, base_idx + 1, base_idx + 2]);
+    }
+
+    fn quad(&mut self, corners: [(Vec3, [f32; 2]); 4], normal: Vec3) {
+        self.triangle([corners[0], corners[1], corners[2]], normal);
+        self.triangle([corners[0], corners[2], corners[3]], normal);
+    }
+}
+
+// window rows per floor and a door on the longest edge
+// every wall is later cut at the same heights (`levels`, the floor lines included), so neighbouring walls
+// share their corner vertices and the mesh stays manifold
+// None when no floor is tall enough for a window or the density is 0
+fn plan_openings(polygon: &Polygon, floors: &[f32], wall_height: f32, floor_height: f32, openings: WallOpenings) -> Option<(Vec<f32>, Vec<Vec<Opening>>)> {
+    use crate::config::{DOOR_STEP, DOOR_WIDTH, FLOOR_HEIGHT, OPENING_MARGIN, WINDOW_HEIGHT, WINDOW_SILL, WINDOW_SPACING, WINDOW_WIDTH};
+    use rand::{Rng, SeedableRng};
+
+    if openings.window_density <= 0.0 {
+        return None;
+    }
+    let base = floors[0];
+    let row_spacing = if floor_height > 0.0 { floor_height } else { FLOOR_HEIGHT };
+    let rows: Vec<(f32, f32)> = (0..)
+        .map(|floor| floor as f32 * row_spacing + WINDOW_SILL)
+        .map(|sill| (sill, sill + WINDOW_HEIGHT))
+        .take_while(|&(_, head)| head + OPENING_MARGIN <= wall_height)
+        .collect();
+    if rows.is_empty() {
+        return None;
+    }
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(openings.seed);
+    let mut per_edge: Vec<Vec<Opening>> = (0..polygon.len()).map(|_| Vec::new()).collect();
+
+    // door on the longest edge, reaching up to the ground floor window heads
+    let (door_edge, _, door_length) = super::subdivision::vlongest_edge(polygon)?;
+    let mut door = None;
+    if door_length >= DOOR_WIDTH + 2.0 * OPENING_MARGIN {
+        let half = DOOR_WIDTH * 0.5;
+        let centre = rng.random_range(OPENING_MARGIN + half..=door_length - OPENING_MARGIN - half);
+        door = Some((centre - half, centre + half));
+        per_edge[door_edge].push(Opening { u0: centre - half, u1: centre + half, v0: DOOR_STEP.max(base + DOOR_STEP), v1: rows[0].1 });
+    }
+
+    // window slots centred along each wall, jittered within their slot
+    for (i, edge_openings) in per_edge.iter_mut().enumerate() {
+        let length = polygon[i].distance(polygon[(i + 1) % polygon.len()]);
+        let slots = ((length - 2.0 * OPENING_MARGIN) / WINDOW_SPACING).floor().max(0.0) as usize;
+        let start = (length - slots as f32 * WINDOW_SPACING) * 0.5;
+        for (row, &(sill, head)) in rows.iter().enumerate() {
+            for slot in 0..slots {
+                // both draws always happen, the layout of one wall doesn't shift the next
+                let placed = rng.random::<f32>() < openings.window_density;
+                let jitter = rng.random_range(-1.0_f32..=1.0) * (WINDOW_SPACING - WINDOW_WIDTH) * 0.25;
+                let centre = start + (slot as f32 + 0.5) * WINDOW_SPACING + jitter;
+                let (u0, u1) = (centre - WINDOW_WIDTH * 0.5, centre + WINDOW_WIDTH * 0.5);
+                let blocked = row == 0 && i == door_edge
+                    && door.is_some_and(|(d0, d1)| u1 + OPENING_MARGIN > d0 && u0 - OPENING_MARGIN < d1);
+                if placed && !blocked {
+                    edge_openings.push(Opening { u0, u1, v0: sill, v1: head });
+                }
+            }
+        }
+    }
+
+    let mut levels = floors.to_vec();
+    levels.extend(per_edge.iter().flatten().flat_map(|opening| [opening.v0, opening.v1]));
+    levels.sort_by(|a, b| a.total_cmp(b));
+    levels.dedup();
+    Some((levels, per_edge))
+}
+
+// one wall cut into horizontal bands at `levels`, openings are recessed by WINDOW_DEPTH
+// each band between the openings is triangulated as a strip between its bottom and top vertices,
+// which include the opening corners of the bands below and above so there are no T-junctions
+#[allow(clippy::too_many_arguments)]
+fn push_opened_wall(wall: &mut MeshBuffers, (start, end): (Vec2, Vec2), winding: f32, levels: &[f32], openings: &[Opening], perimeter: f32, tile: f32, floor_height: f32) {
+    let edge = end - start;
+    let length = edge.length();
+    let dir = edge / length;
+    let outward = Vec2::new(edge.y, -edge.x).normalize() * winding;
+    let depth = crate::config::WINDOW_DEPTH;
+
+    // wall coordinates to 3D, uv continues the plain wall mapping
+    // v is measured from `floor`, the floor line of the band being built
+    let at_floor = |u: f32, v: f32, inset: f32, floor: f32| {
+        let p = start + dir * u - outward * inset;
+        (Vec3::new(p.x, v, p.y), [(perimeter + u) / tile, (v - floor) / tile])
+    };
+    let normal = Vec3::new(outward.x, 0.0, outward.y);
+    let along = Vec3::new(dir.x, 0.0, dir.y);
+    let same = |a: f32, b: f32| (a - b).abs() <= 1e-5;
+
+    // vertices on a level line: wall ends plus corners of openings starting, ending or crossing there
+    let line_points = |v: f32| {
+        let mut points = vec![0.0, length];
+        for opening in openings.iter().filter(|opening| opening.v0 <= v + 1e-5 && opening.v1 >= v - 1e-5) {
+            points.extend([opening.u0, opening.u1]);
+        }
+        points.sort_by(|a, b| a.total_cmp(b));
+        points.dedup_by(|a, b| same(*a, *b));
+        points
+    };
+
+    for band in levels.windows(2) {
+        let (bottom, top) = (band[0], band[1]);
+        let floor = floor_start(bottom, floor_height);
+        let at = |u: f32, v: f32, inset: f32| at_floor(u, v, inset, floor);
+        let mut holes: Vec<&Opening> = openings.iter().filter(|opening| opening.v0 <= bottom + 1e-5 && opening.v1 >= top - 1e-5).collect();
+        holes.sort_by(|a, b| a.u0.total_cmp(&b.u0));
+
+        // solid stretches between the holes
+        let mut solid = Vec::new();
+        let mut from = 0.0;
+        for hole in &holes {
+            solid.push((from, hole.u0));
+            from = hole.u1;
+        }
+        solid.push((from, length));
+
+        let (bottom_points, top_points) = (line_points(bottom), line_points(top));
+        for (s, e) in solid {
+            let chain = |points: &[f32]| -> Vec<f32> {
+                points.iter().copied().filter(|&u| u >= s - 1e-5 && u <= e + 1e-5).collect()
+            };
+            let (lower, upper) = (chain(&bottom_points), chain(&top_points));
+            let (mut i, mut j) = (0, 0);
+            while i + 1 < lower.len() || j + 1 < upper.len() {
+                if j + 1 >= upper.len() || (i + 1 < lower.len() && lower[i + 1] <= upper[j + 1]) {
+                    wall.triangle([at(lower[i], bottom, 0.0), at(lower[i + 1], bottom, 0.0), at(upper[j], top, 0.0)], normal);
+                    i += 1;
+                } else {
+                    wall.triangle([at(lower[i], bottom, 0.0), at(upper[j + 1], top, 0.0), at(upper[j], top, 0.0)], normal);
+                    j += 1;
+                }
+            }
+        }
+
+        // recess sides and back panel, one band at a time so they meet the wall's band vertices
+        for hole in holes {
+            wall.quad([at(hole.u0, bottom, 0.0), at(hole.u0, top, 0.0), at(hole.u0, top, depth), at(hole.u0, bottom, depth)], along);
+            wall.quad([at(hole.u1, bottom, 0.0), at(hole.u1, top, 0.0), at(hole.u1, top, depth), at(hole.u1, bottom, depth)], -along);
+            wall.quad([at(hole.u0, bottom, depth), at(hole.u1, bottom, depth), at(hole.u1, top, depth), at(hole.u0, top, depth)], normal);
+        }
+    }
+
+    // sill and lintel
+    for opening in openings {
+        let floor = floor_start(opening.v0, floor_height);
+        let at = |u: f32, v: f32, inset: f32| at_floor(u, v, inset, floor);
+        wall.quad([at(opening.u0, opening.v0, 0.0), at(opening.u1, opening.v0, 0.0), at(opening.u1, opening.v0, depth), at(opening.u0, opening.v0, depth)], Vec3::Y);
+        wall.quad([at(opening.u0, opening.v1, 0.0), at(opening.u1, opening.v1, 0.0), at(opening.u1, opening.v1, depth), at(opening.u0, opening.v1, depth)], -Vec3::Y);
+    }
+}
+
+// outline of a roof reaching `overhang` past the walls
+// the overhang is halved until the outline stays simple, narrow notches fold over otherwise
+pub fn eave_outline(polygon: &Polygon, overhang: f32) -> Polygon {
+    let mut overhang = overhang;
+    for _ in 0..crate::config::EAVE_CLAMP_STEPS {
+        if overhang <= 0.0 {
+            break;
+        }
+        let outline = super::ops::offset(polygon, overhang);
+        if super::ops::is_simple(&outline) {
+            return outline;
+        }
+        overhang *= 0.5;
+    }
+    polygon.clone()
+}
+
+// roof plane as a height above the eaves, h(p) = slope . p + offset
+#[derive(Clone, Copy)]
+struct RoofPlane {
+    slope: Vec2,
+    offset: f32,
+}
+
+impl RoofPlane {
+    fn height(&self, p: Vec2) -> f32 {
+        self.slope.dot(p) + self.offset
+    }
+}
+
+// pitched roof over a footprint, sitting on top of the walls at wall_height
+// the ridge runs along the footprint's longest axis at roof_height above the eaves,
+// the roof is the lowest of its planes everywhere: two sloped sides for a gable roof,
+// plus two sloped ends (hip roof) when the footprint is close to square
+// walls are raised up to the roof line along every edge, which gives the triangular gable ends
+// smooth shades the roof planes softly across ridges and hips, the gable walls stay flat
+pub fn polygon_to_gable_roof(polygon: &Polygon, wall_height: f32, roof_height: f32, smooth: bool) -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    if let Some(planes) = roof_planes(polygon, roof_height) {
+        let (min, max) = polygon_bounds(polygon);
+        let roof_at = |p: Vec2| planes.iter().map(|plane| plane.height(p)).fold(f32::INFINITY, f32::min).max(0.0);
+
+        // roof planes, each footprint triangle is cut to the part where that plane is the lowest
+        for [a, b, c] in triangulate_polygon(polygon) {
+            let triangle = vec![polygon[a], polygon[b], polygon[c]];
+            for (i, plane) in planes.iter().enumerate() {
+                let mut piece = triangle.clone();
+                for (j, other) in planes.iter().enumerate() {
+                    if i != j && piece.len() >= 3 {
+                        piece = clip_below(&piece, plane, other);
+                    }
+                }
+                if piece.len() < 3 {
+                    continue;
+                }
+
+                let normal = Vec3::new(-plane.slope.x, 1.0, -plane.slope.y).normalize();
+                let start = positions.len() as u32;
+                for vertex in &piece {
+                    positions.push([vertex.x, wall_height + plane.height(*vertex).max(0.0), vertex.y]);
+                    normals.push(normal.to_array());
+                    uvs.push(planar_uv(*vertex, min, max, UvMode::BoundsFit, 1.0));
+                }
+                // fan over the convex piece, flipped so it faces up like the top cap
+                for k in 1..piece.len() as u32 - 1 {
+                    indices.extend([start, start + k + 1, start + k]);
+                }
+            }
+        }
+        // only the roof planes are in the buffers yet
+        if smooth {
+            smooth_shading(&mut positions, &mut normals, &mut uvs, &mut indices);
+        }
+
+        // gable walls, each edge split where the roof line above it changes plane
+        for i in 0..polygon.len() {
+            let v1 = polygon[i];
+            let v2 = polygon[(i + 1) % polygon.len()];
+            let edge = v2 - v1;
+            if edge.length_squared() <= f32::EPSILON {
+                continue;
+            }
+            let normal = Vec2::new(edge.y, -edge.x).normalize();
+
+            let mut breaks = vec![0.0, 1.0];
+            for (j, a) in planes.iter().enumerate() {
+                for b in &planes[j + 1..] {
+                    let slope = (a.slope - b.slope).dot(edge);
+                    if slope.abs() > f32::EPSILON {
+                        let t = -((a.slope - b.slope).dot(v1) + a.offset - b.offset) / slope;
+                        if t > 0.0 && t < 1.0 {
+                            breaks.push(t);
+                        }
+                    }
+                }
+            }
+            breaks.sort_by(|a, b| a.total_cmp(b));
+
+            let mut along = 0.0;
+            for span in breaks.windows(2) {
+                let (p1, p2) = (v1 + edge * span[0], v1 + edge * span[1]);
+                let (h1, h2) = (roof_at(p1), roof_at(p2));
+                let length = p1.distance(p2);
+                if h1.max(h2) > f32::EPSILON {
+                    let base_idx = positions.len() as u32;
+                    positions.extend([
+                        [p1.x, wall_height, p1.y],
+                        [p2.x, wall_height, p2.y],
+                        [p1.x, wall_height + h1, p1.y],
+                        [p2.x, wall_height + h2, p2.y],
+                    ]);
+                    normals.extend([[normal.x, 0.0, normal.y]; 4]);
+                    uvs.extend([
+                        [along, wall_height], [along + length, wall_height],
+                        [along, wall_height + h1], [along + length, wall_height + h2],
+                    ]);
+                    indices.extend([base_idx, base_idx + 2, base_idx + 1]);
+                    indices.extend([base_idx + 1, base_idx + 2, base_idx + 3]);
+                }
+                along += length;
+            }
+        }
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+
+    mesh
+}
+
+// hip roof over the footprint's straight skeleton, every wall gets one face sloping inward
+// handles the irregular voronoi footprints a two-plane gable can't follow
+// all faces share one pitch, set so the highest skeleton point is roof_height above the walls
+// smooth shades softly across the hips and valleys instead of one normal per face
+pub fn polygon_to_hip_roof(polygon: &Polygon, wall_height: f32, roof_height: f32, smooth: bool) -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    let faces = super::ops::straight_skeleton(polygon);
+    let peak = faces.iter().flatten().fold(0.0_f32, |peak, corner| peak.max(corner.y));
+    if peak > 0.0 && roof_height > 0.0 {
+        let (min, max) = polygon_bounds(polygon);
+        let pitch = roof_height / peak;
+        for face in faces {
+            let corners = face.map(|corner| Vec3::new(corner.x, wall_height + corner.y * pitch, corner.z));
+            // both triangles' normals summed, one of them is degenerate where the face ends in a point
+            let normal = ((corners[2] - corners[0]).cross(corners[1] - corners[0])
+                + (corners[3] - corners[0]).cross(corners[2] - corners[0])).normalize_or_zero();
+            if normal == Vec3::ZERO {
+                continue;
+            }
+
+            // flipped like the top cap so it faces up
+            let base_idx = positions.len() as u32;
+            for corner in corners {
+                positions.push(corner.to_array());
+                normals.push(normal.to_array());
+                uvs.push(planar_uv(corner.xz(), min, max, UvMode::BoundsFit, 1.0));
+            }
+            indices.extend([base_idx, base_idx + 2, base_idx + 1]);
+            indices.extend([base_idx, base_idx + 3, base_idx + 2]);
+        }
+        if smooth {
+            smooth_shading(&mut positions, &mut normals, &mut uvs, &mut indices);
+        }
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+
+    mesh
+}
+
+// merge vertices sharing a position, each gets the area weighted average normal of its triangles
+// roof uvs are planar, so merged vertices already agree on them
+fn smooth_shading(positions: &mut Vec<[f32; 3]>, normals: &mut Vec<[f32; 3]>, uvs: &mut Vec<[f32; 2]>, indices: &mut [u32]) {
+    let key = |p: &[f32; 3]| p.map(|c| (c * 1e4).round() as i64);
+    let mut welded = std::collections::HashMap::new();
+    let (mut merged_positions, mut merged_uvs) = (Vec::new(), Vec::new());
+    let remap: Vec<u32> = positions.iter().zip(uvs.iter())
+        .map(|(position, uv)| *welded.entry(key(position)).or_insert_with(|| {
+            merged_positions.push(*position);
+            merged_uvs.push(*uv);
+            merged_positions.len() as u32 - 1
+        }))
+        .collect();
+    for index in indices.iter_mut() {
+        *index = remap[*index as usize];
+    }
+
+    // unnormalized cross products, larger triangles weigh more
+    let mut summed = vec![Vec3::ZERO; merged_positions.len()];
+    for triangle in indices.chunks(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| Vec3::from(merged_positions[i as usize]));
+        let face = (b - a).cross(c - a);
+        for &i in triangle {
+            summed[i as usize] += face;
+        }
+    }
+    *normals = summed.into_iter().map(|normal| normal.normalize_or(Vec3::Y).to_array()).collect();
+    *positions = merged_positions;
+    *uvs = merged_uvs;
+}
+
+// the planes of a roof over `polygon`, laid out in the frame of its longest edge
+// the long side of the footprint's extent becomes the ridge direction, both sides slope down to the eaves
+// near-square footprints get two more planes sloping down to the ends at the same pitch (hip roof)
+fn roof_planes(polygon: &Polygon, roof_height: f32) -> Option<Vec<RoofPlane>> {
+    if polygon.len() < 3 || roof_height <= 0.0 {
+        return None;
+    }
+    let (longest, start, _) = super::subdivision::vlongest_edge(polygon)?;
+    let mut axis = (polygon[(longest + 1) % polygon.len()] - start).normalize_or_zero();
+    if axis == Vec2::ZERO {
+        return None;
+    }
+
+    // extent along the axis and across it
+    let extent = |dir: Vec2| polygon.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+        let d = v.dot(dir);
+        (lo.min(d), hi.max(d))
+    });
+    let (mut along, mut across) = (extent(axis), extent(axis.perp()));
+    if along.1 - along.0 < across.1 - across.0 {
+        axis = axis.perp();
+        (along, across) = (across, along);
+    }
+    let side = axis.perp();
+
+    let half_width = (across.1 - across.0) * 0.5;
+    let half_length = (along.1 - along.0) * 0.5;
+    if half_width <= f32::EPSILON {
+        return None;
+    }
+    let (centre_across, centre_along) = ((across.0 + across.1) * 0.5, (along.0 + along.1) * 0.5);
+
+    // height drops from roof_height at the ridge to 0 at the eaves
+    let pitch = roof_height / half_width;
+    let mut planes = vec![
+        RoofPlane { slope: -side * pitch, offset: roof_height + centre_across * pitch },
+        RoofPlane { slope: side * pitch, offset: roof_height - centre_across * pitch },
+    ];
+    if half_length < half_width * crate::config::HIP_ROOF_ASPECT {
+        planes.push(RoofPlane { slope: -axis * pitch, offset: (half_length + centre_along) * pitch });
+        planes.push(RoofPlane { slope: axis * pitch, offset: (half_length - centre_along) * pitch });
+    }
+    Some(planes)
+}
+
+// keep the part of a convex polygon where `plane` is not above `other`
+fn clip_below(polygon: &[Vec2], plane: &RoofPlane, other: &RoofPlane) -> Polygon {
+    // plane - other <= 0, as the half-plane left of a directed line
+    let normal = plane.slope - other.slope;
+    let length_squared = normal.length_squared();
+    if length_squared <= f32::EPSILON {
+        return polygon.to_vec();
+    }
+    let a = normal * -(plane.offset - other.offset) / length_squared;
+    super::utils::clip_polygon_half_plane(polygon, a, a + Vec2::new(-normal.y, normal.x))
+}
+
+// append one quad (v0, v1, v2, v3 counter-clockwise seen from the normal side)
+fn push_quad(
+    positions: &mut Vec<[f32; 3]>,
+    normals: &mut Vec<[f32; 3]>,
+    uvs: &mut Vec<[f32; 2]>,
+    indices: &mut Vec<u32>,
+    corners: [Vec3; 4],
+    normal: Vec3,
+) {
+    let base_idx = positions.len() as u32;
+    positions.extend(corners.map(|c| c.to_array()));
+    normals.extend([normal.to_array(); 4]);
+    uvs.extend([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+    indices.extend([base_idx, base_idx + 1, base_idx + 2]);
+    indices.extend([base_idx, base_idx + 2, base_idx + 3]);
+}
+
+// road corridor mesh, flat surface plus a raised curb along both long edges
+// quads come from road_corridor_quads: [start - perp, end - perp, end + perp, start + perp]
+// curbs are closed on top and on both vertical sides, the ends stay open under the overlap
+pub fn road_corridor_to_mesh(quads: &[Polygon], curb_height: f32, curb_width: f32) -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    for quad in quads.iter().filter(|quad| quad.len() == 4) {
+        let at = |v: Vec2, y: f32| Vec3::new(v.x, y, v.y);
+
+        // surface
+        push_quad(&mut positions, &mut normals, &mut uvs, &mut indices,
+            [at(quad[0], 0.0), at(quad[3], 0.0), at(quad[2], 0.0), at(quad[1], 0.0)], Vec3::Y);
+
+        // one curb per long edge, (outer start, outer end) with the inward direction
+        // the second edge runs backwards so both curbs wind the same way
+        let inward = (quad[3] - quad[0]).normalize_or_zero();
+        for (start, end, inward) in [(quad[0], quad[1], inward), (quad[2], quad[3], -inward)] {
+            let (inner_start, inner_end) = (start + inward * curb_width, end + inward * curb_width);
+            let outward = -inward;
+
+            // top
+            push_quad(&mut positions, &mut normals, &mut uvs, &mut indices,
+                [at(start, curb_height), at(inner_start, curb_height), at(inner_end, curb_height), at(end, curb_height)], Vec3::Y);
+            // inner face, towards the road
+            push_quad(&mut positions, &mut normals, &mut uvs, &mut indices,
+                [at(inner_start, 0.0), at(inner_end, 0.0), at(inner_end, curb_height), at(inner_start, curb_height)],
+                Vec3::new(inward.x, 0.0, inward.y));
+            // outer face, towards the buildings
+            push_quad(&mut positions, &mut normals, &mut uvs, &mut indices,
+                [at(end, 0.0), at(start, 0.0), at(start, curb_height), at(end, curb_height)],
+                Vec3::new(outward.x, 0.0, outward.y));
+        }
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+
+    mesh
+}
+
+// flat road surface, a quad strip along the polyline, widths are per path point
+// the strip follows ops::tapered_ribbon, so corners are mitered like every other ribbon
+// near zero length segments are skipped, u runs across the road, v along it in local widths
+pub fn road_path_to_mesh(path: &[Vec3], widths: &[f32]) -> Mesh {
+    let mut points: Vec<Vec2> = Vec::with_capacity(path.len());
+    let mut kept_widths: Vec<f32> = Vec::with_capacity(path.len());
+    for (point, &width) in path.iter().map(|p| Vec2::new(p.x, p.z)).zip(widths) {
+        if points.last().is_none_or(|last| last.distance(point) >= 0.001) {
+            points.push(point);
+            kept_widths.push(width);
+        }
+    }
+    // points are already further apart than the ribbon's own dedupe, vertex i pairs with point i
+    let outline = super::ops::tapered_ribbon(&points, &kept_widths);
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    let mut indices = Vec::new();
+
+    let n = outline.len() / 2;
+    let mut along = 0.0;
+    for i in 0..n {
+        if i > 0 {
+            along += points[i].distance(points[i - 1]) / ((kept_widths[i - 1] + kept_widths[i]) * 0.5).max(1e-4);
+        }
+
+        // right then left edge vertex
+        for (v, u) in [(outline[i], 0.0), (outline[2 * n - 1 - i], 1.0)] {
+            positions.push([v.x, 0.0, v.y]);
+            normals.push([0.0, 1.0, 0.0]);
+            uvs.push([u, along]);
+        }
+        if i > 0 {
+            let base = (2 * i - 2) as u32;
+            indices.extend([base, base + 1, base + 3]);
+            indices.extend([base, base + 3, base + 2]);
+        }
+    }
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+
+    mesh
+}
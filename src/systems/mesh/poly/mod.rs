@@ -4,4 +4,5 @@ pub mod point_gen;
 pub mod subdivision;
 pub mod mesh_gen;
 pub mod voronoi;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod ops;
\ No newline at end of file
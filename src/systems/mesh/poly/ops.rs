@@ -0,0 +1,632 @@
+// POLYGON OPERATIONS
+//
+// conventions for everything in here:
+//  - input polygons may have either winding, outer boundaries come back counter-clockwise
+//  - holes in boolean results come back clockwise, so signed areas add up
+//  - coordinates closer than EPSILON (m) are treated as the same point
+
+use bevy::prelude::*;
+use crate::systems::mesh::Polygon;
+use super::utils::{point_in_polygon, point_to_line_distance, polygon_area};
+
+/// Distance below which two points or a point and an edge are considered touching.
+pub const EPSILON: f32 = 1e-4;
+
+/// Corners of a shrinking skeleton front closer than this (m) are merged.
+/// Coarser than `EPSILON`, a split landing next to a corner would otherwise leave an edge with no usable direction.
+const SKELETON_MERGE: f32 = 1e-2;
+
+/// Longest miter allowed at a corner, as a multiple of the offset distance.
+const MITER_LIMIT: f32 = 4.0;
+
+/// Copy of `polygon` wound counter-clockwise.
+fn ccw(polygon: &[Vec2]) -> Polygon {
+    let mut polygon = polygon.to_vec();
+    if polygon_area(&polygon) < 0.0 {
+        polygon.reverse();
+    }
+    polygon
+}
+
+/// Offsets a polygon outward by `distance`, or inward for negative distances, with mitered corners.
+/// Miters are capped at `MITER_LIMIT` times the distance so needle corners don't shoot off.
+///
+/// # Returns
+/// The offset polygon, counter-clockwise. Large inward offsets can self-intersect, use `inset` when that matters.
+pub fn offset(polygon: &[Vec2], distance: f32) -> Polygon {
+    let polygon = ccw(polygon);
+    let n = polygon.len();
+    if n < 3 || distance == 0.0 {
+        return polygon;
+    }
+
+    // outward normal of edge i -> i + 1, right hand side for a ccw polygon
+    let normal = |i: usize| {
+        let edge = polygon[(i + 1) % n] - polygon[i];
+        Vec2::new(edge.y, -edge.x).normalize_or_zero()
+    };
+
+    (0..n)
+        .map(|i| {
+            let (before, after) = (normal((i + n - 1) % n), normal(i));
+            let miter = before + after;
+            let denom = 1.0 + before.dot(after);
+            if denom <= EPSILON {
+                // edges fold back onto each other
+                return polygon[i] + after * distance;
+            }
+            let shift = miter * (distance / denom);
+            polygon[i] + shift.clamp_length_max(distance.abs() * MITER_LIMIT)
+        })
+        .collect()
+}
+
+/// Shrinks a polygon by `distance` on every side.
+///
+/// # Returns
+/// The inset polygon, counter-clockwise, or `None` when it collapses, flips or self-intersects.
+pub fn inset(polygon: &[Vec2], distance: f32) -> Option<Polygon> {
+    let original = ccw(polygon);
+    let result = offset(&original, -distance);
+    if result.len() < 3 || polygon_area(&result) <= EPSILON || !is_simple(&result) {
+        return None;
+    }
+
+    // past the medial axis edges turn around, the outline can still look valid
+    let n = result.len();
+    let flipped = (0..n).any(|i| {
+        let before = original[(i + 1) % n] - original[i];
+        let after = result[(i + 1) % n] - result[i];
+        before.dot(after) <= 0.0
+    });
+    if flipped {
+        return None;
+    }
+    Some(result)
+}
+
+/// Checks that a polygon is usable as a footprint: at least 3 vertices, non-zero area,
+/// no repeated vertices and no edges crossing or touching except neighbours at their shared vertex.
+///
+/// # Returns `true` if the polygon is simple.
+pub fn is_simple(polygon: &[Vec2]) -> bool {
+    let n = polygon.len();
+    if n < 3 || polygon_area(&polygon.to_vec()).abs() <= EPSILON {
+        return false;
+    }
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if polygon[i].distance(polygon[j]) < EPSILON {
+                return false;
+            }
+        }
+    }
+
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        for j in (i + 1)..n {
+            // neighbours share a vertex, that touch is fine
+            if j == i + 1 || (i == 0 && j == n - 1) {
+                continue;
+            }
+            let (c, d) = (polygon[j], polygon[(j + 1) % n]);
+            if segments_touch(a, b, c, d) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// Convex hull of a point set (Andrew's monotone chain).
+///
+/// # Returns
+/// The hull vertices counter-clockwise, collinear points dropped.
+/// Fewer than 3 distinct points return them as-is.
+pub fn convex_hull(points: &[Vec2]) -> Polygon {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // > 0 when o -> a -> b turns left
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+
+    let mut lower: Vec<Vec2> = Vec::new();
+    for &p in &sorted {
+        while lower.len() >= 2 && cross(lower[lower.len() - 2], lower[lower.len() - 1], p) <= 0.0 {
+            lower.pop();
+        }
+        lower.push(p);
+    }
+
+    let mut upper: Vec<Vec2> = Vec::new();
+    for &p in sorted.iter().rev() {
+        while upper.len() >= 2 && cross(upper[upper.len() - 2], upper[upper.len() - 1], p) <= 0.0 {
+            upper.pop();
+        }
+        upper.push(p);
+    }
+
+    // last point of each chain is the first of the other
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+/// Smallest-area rectangle around a polygon, one side lies along an edge of its convex hull.
+///
+/// # Returns
+/// The four corners counter-clockwise, the first edge runs along the rectangle's first axis.
+/// Empty when the polygon has no area.
+pub fn oriented_bounding_box(polygon: &[Vec2]) -> Polygon {
+    let hull = convex_hull(polygon);
+    if hull.len() < 3 {
+        return Vec::new();
+    }
+
+    // (area, axis, min, max) with min/max in the axis frame
+    let mut best: Option<(f32, Vec2, Vec2, Vec2)> = None;
+    for i in 0..hull.len() {
+        let Some(axis) = (hull[(i + 1) % hull.len()] - hull[i]).try_normalize() else {
+            continue;
+        };
+        let (min, max) = hull.iter()
+            .map(|p| Vec2::new(p.dot(axis), p.dot(axis.perp())))
+            .fold((Vec2::INFINITY, Vec2::NEG_INFINITY), |(min, max), p| (min.min(p), max.max(p)));
+        let area = (max - min).x * (max - min).y;
+        if best.is_none_or(|(best_area, ..)| area < best_area) {
+            best = Some((area, axis, min, max));
+        }
+    }
+
+    let Some((_, axis, min, max)) = best else {
+        return Vec::new();
+    };
+    [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)].into_iter()
+        .map(|corner| axis * corner.x + axis.perp() * corner.y)
+        .collect()
+}
+
+/// Collapses edges shorter than `min_edge_length`, merging both endpoints into their midpoint.
+/// The shortest offending edge goes first, repeated until none are left. Winding is kept.
+///
+/// # Returns
+/// The simplified polygon. It can drop below 3 vertices for tiny input, callers should check.
+pub fn simplify(polygon: &[Vec2], min_edge_length: f32) -> Polygon {
+    let mut result = polygon.to_vec();
+    if min_edge_length <= 0.0 {
+        return result;
+    }
+
+    while result.len() >= 3 {
+        let n = result.len();
+        let shortest = (0..n)
+            .map(|i| (i, result[i].distance(result[(i + 1) % n])))
+            .filter(|&(_, length)| length < min_edge_length)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((i, _)) = shortest else { break };
+
+        let j = (i + 1) % n;
+        result[i] = (result[i] + result[j]) * 0.5;
+        result.remove(j);
+    }
+
+    result
+}
+
+/// Brings a polygon's vertex count into `vertex_range`. Above the range the vertex spanning the
+/// smallest triangle with its neighbours is dropped first (Visvalingam-Whyatt), below it the
+/// longest edge is split at its midpoint. Winding is kept.
+///
+/// # Returns
+/// The adjusted polygon. Input with fewer than 3 vertices is returned as-is. Dropping vertices
+/// of a concave polygon can make it self-intersect, callers should check with `is_simple`.
+pub fn limit_vertices(polygon: &[Vec2], vertex_range: std::ops::RangeInclusive<usize>) -> Polygon {
+    let mut result = polygon.to_vec();
+    if result.len() < 3 {
+        return result;
+    }
+
+    while result.len() > (*vertex_range.end()).max(3) {
+        let n = result.len();
+        let smallest = (0..n)
+            .map(|i| {
+                let (before, after) = (result[(i + n - 1) % n], result[(i + 1) % n]);
+                (i, (result[i] - before).perp_dot(after - before).abs())
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((i, _)) = smallest else { break };
+        result.remove(i);
+    }
+
+    while result.len() < *vertex_range.start() {
+        let n = result.len();
+        let longest = (0..n)
+            .max_by(|&a, &b| result[a].distance(result[(a + 1) % n]).total_cmp(&result[b].distance(result[(b + 1) % n])));
+        let Some(i) = longest else { break };
+        let midpoint = (result[i] + result[(i + 1) % n]) * 0.5;
+        result.insert(i + 1, midpoint);
+    }
+
+    result
+}
+
+/// Outline of an open polyline widened to `width`, mitered at the joints and cut square at both ends.
+/// Repeated points are skipped, joints use the same miter cap as `offset`.
+///
+/// # Returns
+/// The ribbon polygon, counter-clockwise, or an empty polygon when the path has no length.
+pub fn ribbon(path: &[Vec2], width: f32) -> Polygon {
+    if width <= 0.0 {
+        return Vec::new();
+    }
+    ccw(&tapered_ribbon(path, &vec![width; path.len()]))
+}
+
+/// Like `ribbon`, with the width given per path point, each side runs straight between its corners.
+/// Miters at a joint use that point's width.
+///
+/// # Returns
+/// The right side from the first kept point to the last, then the left side back, so vertex `i` and
+/// `len - 1 - i` lie across the path from each other. Empty when the path has no length.
+pub fn tapered_ribbon(path: &[Vec2], widths: &[f32]) -> Polygon {
+    let mut points: Vec<Vec2> = Vec::with_capacity(path.len());
+    let mut half_widths: Vec<f32> = Vec::with_capacity(path.len());
+    for (&point, &width) in path.iter().zip(widths) {
+        if points.last().is_none_or(|last| last.distance(point) > EPSILON) {
+            points.push(point);
+            half_widths.push(width.max(0.0) * 0.5);
+        }
+    }
+    let n = points.len();
+    if n < 2 {
+        return Vec::new();
+    }
+
+    // left normal of segment i -> i + 1
+    let normal = |i: usize| (points[i + 1] - points[i]).normalize().perp();
+    let shifts: Vec<Vec2> = (0..n)
+        .map(|i| {
+            let half_width = half_widths[i];
+            if i == 0 {
+                return normal(0) * half_width;
+            }
+            if i == n - 1 {
+                return normal(n - 2) * half_width;
+            }
+            let (before, after) = (normal(i - 1), normal(i));
+            let denom = 1.0 + before.dot(after);
+            if denom <= EPSILON {
+                // path doubles back on itself
+                return after * half_width;
+            }
+            ((before + after) * (half_width / denom)).clamp_length_max(half_width * MITER_LIMIT)
+        })
+        .collect();
+
+    // right side forward, left side back
+    let mut outline: Polygon = points.iter().zip(&shifts).map(|(point, shift)| *point - *shift).collect();
+    outline.extend(points.iter().zip(&shifts).rev().map(|(point, shift)| *point + *shift));
+    outline
+}
+
+/// Straight skeleton of a simple polygon as roof faces, traced by shrinking the outline at unit speed.
+/// Edge events (an edge shrinking to nothing) merge its corners, split events (a reflex corner
+/// running into another edge) pinch the front in two, each part shrinks on until it collapses.
+///
+/// # Returns
+/// One quad per edge per step between events, `(x, t, z)` with `t` the inward distance travelled,
+/// wound like the counter-clockwise outline. Faces ending in a point repeat it. Empty for degenerate input.
+pub fn straight_skeleton(polygon: &[Vec2]) -> Vec<[Vec3; 4]> {
+    let mut faces = Vec::new();
+    let mut fronts = vec![(dedupe_ring(ccw(polygon)), 0.0)];
+    // every event drops a corner or splits a front, the budget only guards against float trouble
+    let mut budget = 4 * polygon.len() + 16;
+    while let Some((front, time)) = fronts.pop() {
+        let n = front.len();
+        if n < 3 || polygon_area(&front).abs() < EPSILON {
+            continue;
+        }
+        if budget == 0 {
+            faces.extend(skeleton_cap(&front, time));
+            continue;
+        }
+        budget -= 1;
+
+        // inward (left) edge normals, corners move so both their edges advance at unit speed
+        let normals: Vec<Vec2> = (0..n).map(|i| (front[(i + 1) % n] - front[i]).normalize_or_zero().perp()).collect();
+        let velocity: Vec<Vec2> = (0..n)
+            .map(|i| {
+                let (before, after) = (normals[(i + n - 1) % n], normals[i]);
+                let denom = 1.0 + before.dot(after);
+                if denom <= EPSILON { after } else { (before + after) / denom }
+            })
+            .collect();
+
+        // edge events
+        let mut step = f32::INFINITY;
+        for i in 0..n {
+            let edge = front[(i + 1) % n] - front[i];
+            let closing = (velocity[i] - velocity[(i + 1) % n]).dot(edge.normalize_or_zero());
+            if closing > EPSILON {
+                step = step.min(edge.length() / closing);
+            }
+        }
+
+        // split events, only a split earlier than every edge event is kept
+        let mut split = None;
+        for r in 0..n {
+            let (prev, next) = (front[(r + n - 1) % n], front[(r + 1) % n]);
+            if (front[r] - prev).perp_dot(next - front[r]) >= -EPSILON {
+                continue;
+            }
+            for e in (0..n).filter(|&e| e != r && (e + 1) % n != r) {
+                let approach = 1.0 - velocity[r].dot(normals[e]);
+                if approach <= EPSILON {
+                    continue;
+                }
+                let t = (front[r] - front[e]).dot(normals[e]) / approach;
+                if t < 0.0 || t >= step {
+                    continue;
+                }
+                let hit = front[r] + velocity[r] * t;
+                let (a, b) = (front[e] + velocity[e] * t, front[(e + 1) % n] + velocity[(e + 1) % n] * t);
+                // slack at the ends catches two reflex corners running into each other
+                let length = (b - a).length().max(EPSILON);
+                let along = (hit - a).dot(b - a) / (length * length);
+                let slack = SKELETON_MERGE / length;
+                if (-slack..=1.0 + slack).contains(&along) {
+                    step = t;
+                    split = Some((r, e));
+                }
+            }
+        }
+        // no front outlives its largest inscribed circle, a later event means float trouble on a sliver
+        if !step.is_finite() || step > (polygon_area(&front) / std::f32::consts::PI).sqrt() + EPSILON {
+            faces.extend(skeleton_cap(&front, time));
+            continue;
+        }
+
+        let moved: Vec<Vec2> = (0..n).map(|i| front[i] + velocity[i] * step).collect();
+        let lift = |p: Vec2, t: f32| Vec3::new(p.x, t, p.y);
+        for i in 0..n {
+            let j = (i + 1) % n;
+            faces.push([lift(front[i], time), lift(front[j], time), lift(moved[j], time + step), lift(moved[i], time + step)]);
+        }
+
+        match split {
+            // corner r sits on edge e -> e + 1, the loops on either side go on separately
+            Some((r, e)) => {
+                let mut first = vec![moved[r]];
+                let mut k = (r + 1) % n;
+                while k != (e + 1) % n {
+                    first.push(moved[k]);
+                    k = (k + 1) % n;
+                }
+                let mut second = vec![moved[r]];
+                while k != r {
+                    second.push(moved[k]);
+                    k = (k + 1) % n;
+                }
+                fronts.push((dedupe_ring(first), time + step));
+                fronts.push((dedupe_ring(second), time + step));
+            }
+            None => fronts.push((dedupe_ring(moved), time + step)),
+        }
+    }
+    faces
+}
+
+// closes a front it can't shrink any further with faces rising to its centroid
+// the rise is the inradius of a tangential polygon, 2 * area / perimeter
+fn skeleton_cap(front: &[Vec2], time: f32) -> Vec<[Vec3; 4]> {
+    let n = front.len();
+    let area = polygon_area(&front.to_vec());
+    let perimeter: f32 = (0..n).map(|i| front[i].distance(front[(i + 1) % n])).sum();
+    let centroid = super::utils::polygon_centroid(&front.to_vec(), area);
+    let apex = Vec3::new(centroid.x, time + 2.0 * area / perimeter.max(EPSILON), centroid.y);
+    (0..n)
+        .map(|i| {
+            let (a, b) = (front[i], front[(i + 1) % n]);
+            [Vec3::new(a.x, time, a.y), Vec3::new(b.x, time, b.y), apex, apex]
+        })
+        .collect()
+}
+
+// drop consecutive corners closer than SKELETON_MERGE, including across the wrap
+fn dedupe_ring(ring: Polygon) -> Polygon {
+    let mut kept: Polygon = Vec::with_capacity(ring.len());
+    for point in ring {
+        if kept.last().is_none_or(|last| last.distance(point) >= SKELETON_MERGE) {
+            kept.push(point);
+        }
+    }
+    while kept.len() > 1 && kept[0].distance(kept[kept.len() - 1]) < SKELETON_MERGE {
+        kept.pop();
+    }
+    kept
+}
+
+/// Union of two simple polygons.
+/// Polygons that only share edges (neighbouring cells) merge into one outline.
+///
+/// # Returns
+/// The outline loops: one ccw polygon when they overlap or touch along an edge,
+/// both inputs when they are disjoint, plus cw holes where the union encloses empty space.
+pub fn union(a: &[Vec2], b: &[Vec2]) -> Vec<Polygon> {
+    boolean(a, b, BooleanOp::Union)
+}
+
+/// Difference `a - b` of two simple polygons.
+///
+/// # Returns
+/// The remaining loops, ccw, with cw holes when `b` sits strictly inside `a`. Empty when `b` covers `a`.
+pub fn difference(a: &[Vec2], b: &[Vec2]) -> Vec<Polygon> {
+    boolean(a, b, BooleanOp::Difference)
+}
+
+/// Clips `subject` to the area of `clip` (intersection), neither has to be convex.
+///
+/// # Returns
+/// The overlapping loops, ccw. Empty when the polygons don't overlap.
+pub fn clip_to(subject: &[Vec2], clip: &[Vec2]) -> Vec<Polygon> {
+    boolean(subject, clip, BooleanOp::Intersection)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BooleanOp {
+    Union,
+    Difference,
+    Intersection,
+}
+
+// where a split edge lies relative to the other polygon
+#[derive(Clone, Copy, PartialEq)]
+enum EdgeSide {
+    Inside,
+    Outside,
+    SharedSame,     // on the other boundary, same direction
+    SharedOpposite, // on the other boundary, opposite direction
+}
+
+// boolean ops by edge classification:
+// split both outlines where they meet, keep the pieces the op wants, then chain them into loops
+fn boolean(a: &[Vec2], b: &[Vec2], op: BooleanOp) -> Vec<Polygon> {
+    if a.len() < 3 || b.len() < 3 {
+        return match op {
+            BooleanOp::Union => [a, b].iter().filter(|p| p.len() >= 3).map(|p| ccw(p)).collect(),
+            BooleanOp::Difference if a.len() >= 3 => vec![ccw(a)],
+            _ => Vec::new(),
+        };
+    }
+    let (a, b) = (ccw(a), ccw(b));
+
+    let mut kept: Vec<(Vec2, Vec2)> = Vec::new();
+    for (start, end) in split_edges(&a, &b) {
+        let keep = matches!(
+            (op, classify(start, end, &b)),
+            (BooleanOp::Union, EdgeSide::Outside | EdgeSide::SharedSame)
+                | (BooleanOp::Intersection, EdgeSide::Inside | EdgeSide::SharedSame)
+                | (BooleanOp::Difference, EdgeSide::Outside | EdgeSide::SharedOpposite)
+        );
+        if keep {
+            kept.push((start, end));
+        }
+    }
+    // shared pieces of b were already decided from a's side
+    for (start, end) in split_edges(&b, &a) {
+        match (op, classify(start, end, &a)) {
+            (BooleanOp::Union, EdgeSide::Outside) | (BooleanOp::Intersection, EdgeSide::Inside) => kept.push((start, end)),
+            (BooleanOp::Difference, EdgeSide::Inside) => kept.push((end, start)), // b's boundary becomes a's, reversed
+            _ => {}
+        }
+    }
+
+    chain_loops(kept)
+}
+
+// every edge of `polygon`, split wherever `other` touches or crosses it
+fn split_edges(polygon: &[Vec2], other: &[Vec2]) -> Vec<(Vec2, Vec2)> {
+    let n = polygon.len();
+    let m = other.len();
+    let mut pieces = Vec::new();
+    for i in 0..n {
+        let (p, q) = (polygon[i], polygon[(i + 1) % n]);
+        let dir = q - p;
+        let length_sq = dir.length_squared();
+        if length_sq <= EPSILON * EPSILON {
+            continue;
+        }
+
+        let mut cuts = vec![0.0, 1.0];
+        for j in 0..m {
+            let (r, s) = (other[j], other[(j + 1) % m]);
+            // vertices of the other polygon lying on this edge (t-junctions, overlaps)
+            if point_to_line_distance(r, p, q) < EPSILON {
+                cuts.push((r - p).dot(dir) / length_sq);
+            }
+            // proper crossings
+            if let Some(t) = crossing(p, q, r, s) {
+                cuts.push(t);
+            }
+        }
+
+        cuts.retain(|t| (0.0..=1.0).contains(t));
+        cuts.sort_by(|x, y| x.total_cmp(y));
+        cuts.dedup_by(|x, y| (*x - *y).abs() * length_sq.sqrt() < EPSILON);
+        for pair in cuts.windows(2) {
+            pieces.push((p + dir * pair[0], p + dir * pair[1]));
+        }
+    }
+    pieces
+}
+
+// parameter along p -> q where it crosses r -> s, strictly inside both segments
+fn crossing(p: Vec2, q: Vec2, r: Vec2, s: Vec2) -> Option<f32> {
+    let (d1, d2) = (q - p, s - r);
+    let denom = d1.perp_dot(d2);
+    if denom.abs() < 1e-9 {
+        return None;
+    }
+    let t = (r - p).perp_dot(d2) / denom;
+    let u = (r - p).perp_dot(d1) / denom;
+    (t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then_some(t)
+}
+
+// true when the segments cross or touch anywhere
+fn segments_touch(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    crossing(a, b, c, d).is_some()
+        || point_to_line_distance(a, c, d) < EPSILON
+        || point_to_line_distance(b, c, d) < EPSILON
+        || point_to_line_distance(c, a, b) < EPSILON
+        || point_to_line_distance(d, a, b) < EPSILON
+}
+
+// a split piece is either fully inside, outside or on the other boundary, its midpoint decides
+fn classify(start: Vec2, end: Vec2, other: &[Vec2]) -> EdgeSide {
+    let mid = (start + end) * 0.5;
+    let m = other.len();
+    for j in 0..m {
+        let (r, s) = (other[j], other[(j + 1) % m]);
+        if point_to_line_distance(mid, r, s) < EPSILON {
+            return if (end - start).dot(s - r) > 0.0 { EdgeSide::SharedSame } else { EdgeSide::SharedOpposite };
+        }
+    }
+    if point_in_polygon(&mid, other) { EdgeSide::Inside } else { EdgeSide::Outside }
+}
+
+// link directed edges end to start into closed loops
+fn chain_loops(mut edges: Vec<(Vec2, Vec2)>) -> Vec<Polygon> {
+    let mut loops = Vec::new();
+    while let Some((start, mut end)) = edges.pop() {
+        let mut outline = vec![start];
+        while end.distance(start) >= EPSILON {
+            let Some(next) = edges.iter().position(|(s, _)| s.distance(end) < EPSILON) else {
+                break; // open chain, only happens with degenerate input
+            };
+            let (s, e) = edges.swap_remove(next);
+            outline.push(s);
+            end = e;
+        }
+        if outline.len() >= 3 && end.distance(start) < EPSILON {
+            // split points on straight runs aren't corners
+            let n = outline.len();
+            let corners: Polygon = (0..n)
+                .filter(|&i| {
+                    let (prev, curr, next) = (outline[(i + n - 1) % n], outline[i], outline[(i + 1) % n]);
+                    point_to_line_distance(curr, prev, next) >= EPSILON
+                })
+                .map(|i| outline[i])
+                .collect();
+            if corners.len() >= 3 {
+                loops.push(corners);
+            }
+        }
+    }
+    loops
+}
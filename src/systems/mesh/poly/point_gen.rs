@@ -6,46 +6,257 @@ use rand::{SeedableRng, rngs::StdRng};
 use spade::{DelaunayTriangulation, Point2, Triangulation as _, LastUsedVertexHintGenerator};
 
 use crate::systems::mesh::Polygon;
-use super::utils::{polygon_area, polygon_centroid, calculate_circumcenter};
+use super::utils::{line_segment_intersection, point_in_polygon, polygon_area, polygon_centroid, calculate_circumcenter};
+
+// clamp rect for generators: the boundary's bounding box plus a margin
+// keeps generators where the settlement actually is, falls back to the canvas
+pub fn generator_bounds(boundary_polygon: &[Vec2], margin: f32) -> Rect {
+    if boundary_polygon.is_empty() {
+        return Rect::new(
+            -crate::config::CANVAS_WIDTH,
+            -crate::config::CANVAS_HEIGHT,
+            crate::config::CANVAS_WIDTH,
+            crate::config::CANVAS_HEIGHT,
+        );
+    }
+
+    let mut bounds = Rect::from_center_size(boundary_polygon[0], Vec2::ZERO);
+    for &vertex in boundary_polygon.iter().skip(1) {
+        bounds = bounds.union_point(vertex);
+    }
+    bounds.inflate(margin)
+}
+
+// chance of keeping a sampled point under a density gradient, for rejection sampling
+// distance from center is normalized by radius: positive gradients thin out the edge, negative the middle
+// never below DENSITY_MIN_WEIGHT so sampling always finishes, exactly 1 at gradient 0
+pub fn density_weight(point: Vec2, center: Vec2, radius: f32, gradient: f32) -> f32 {
+    if gradient == 0.0 || radius <= 0.0 {
+        return 1.0;
+    }
+    let distance = (point.distance(center) / radius).min(1.0);
+    let falloff = if gradient > 0.0 { distance } else { 1.0 - distance };
+    (1.0 - gradient.abs() * falloff).max(crate::config::DENSITY_MIN_WEIGHT)
+}
 
 // generates points in a spiral around (0,0)
 // there could be a better approach than this, (needs experimentation)
+// bounds are in the XZ plane (rect y = world z)
+// a density gradient rejects spiral points by density_weight and carries on along the spiral until num_points are kept
+// the spiral is tightened by the expected acceptance, so it still ends about where the plain spiral does
 pub fn pgen(
     num_points: usize,
-    width: f32,
-    height: f32,
+    bounds: Rect,
     spread: f32,
+    density_gradient: f32,
+    gradient_radius: f32,
     seed: u64,
 ) -> Vec<Vec3> {
     let mut rng = StdRng::seed_from_u64(seed);
     let mut points = Vec::with_capacity(num_points);
     
-    for i in 0..num_points {
+    let acceptance = (0..num_points)
+        .map(|i| density_weight(Vec2::new(i as f32 * spread, 0.0), Vec2::ZERO, gradient_radius, density_gradient))
+        .sum::<f32>() / num_points.max(1) as f32;
+    let spread = spread * acceptance.max(crate::config::DENSITY_MIN_WEIGHT);
+    
+    let mut i = 0;
+    while points.len() < num_points {
         let t = i as f32;
+        i += 1;
         let angle = t * 0.5 + rng.random_range(-0.3..0.3);
         let radius = t * spread + rng.random_range(-spread * 0.2..spread * 0.2);
         
-        let x = (angle.cos() * radius).clamp(-width, width);
-        let z = (angle.sin() * radius).clamp(-height, height);
+        let x = (angle.cos() * radius).clamp(bounds.min.x, bounds.max.x);
+        let z = (angle.sin() * radius).clamp(bounds.min.y, bounds.max.y);
         
+        // the rng is only drawn with a gradient, so existing seeds keep their points
+        if density_gradient != 0.0 && rng.random::<f32>() >= density_weight(Vec2::new(x, z), Vec2::ZERO, gradient_radius, density_gradient) {
+            continue;
+        }
         points.push(Vec3::new(x, 0.0, z));
     }
     
     points
 }
 
+// poisson-disk points in a width x height rect with its corner at the origin, Bridson's algorithm
+// grows outward from the middle of the rect, so stopping at count leaves a roughly round patch
+// no two points closer than min_dist, fewer than count when the rect fills up first
+// a density gradient rejects candidates by density_weight around the middle of the rect
+// x maps to world x, y to world z
+pub fn poisson_points(
+    count: usize,
+    width: f32,
+    height: f32,
+    min_dist: f32,
+    density_gradient: f32,
+    gradient_radius: f32,
+    seed: u64,
+) -> Vec<Vec3> {
+    if count == 0 || width <= 0.0 || height <= 0.0 || min_dist <= 0.0 {
+        return Vec::new();
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // background grid, a cell holds at most one point at this size
+    let cell = min_dist / std::f32::consts::SQRT_2;
+    let (cols, rows) = ((width / cell).ceil() as usize, (height / cell).ceil() as usize);
+    let cell_of = |p: Vec2| (((p.x / cell) as usize).min(cols - 1), ((p.y / cell) as usize).min(rows - 1));
+    let mut grid: Vec<Option<usize>> = vec![None; cols * rows];
+
+    let first = Vec2::new(width, height) * 0.5;
+    let (col, row) = cell_of(first);
+    grid[row * cols + col] = Some(0);
+    let mut samples = vec![first];
+    let mut active = vec![0];
+
+    while !active.is_empty() && samples.len() < count {
+        let slot = rng.random_range(0..active.len());
+        let origin = samples[active[slot]];
+
+        // candidates in the annulus between min_dist and twice that
+        let mut placed = false;
+        for _ in 0..crate::config::POISSON_ATTEMPTS {
+            let angle = rng.random_range(0.0..std::f32::consts::TAU);
+            let radius = rng.random_range(min_dist..2.0 * min_dist);
+            let candidate = origin + Vec2::from_angle(angle) * radius;
+            if candidate.x < 0.0 || candidate.y < 0.0 || candidate.x >= width || candidate.y >= height {
+                continue;
+            }
+            if density_gradient != 0.0 && rng.random::<f32>() >= density_weight(candidate, first, gradient_radius, density_gradient) {
+                continue;
+            }
+
+            // only the surrounding 5x5 cells can hold a point within min_dist
+            let (col, row) = cell_of(candidate);
+            let too_close = (row.saturating_sub(2)..=(row + 2).min(rows - 1))
+                .flat_map(|r| (col.saturating_sub(2)..=(col + 2).min(cols - 1)).map(move |c| r * cols + c))
+                .filter_map(|idx| grid[idx])
+                .any(|other| samples[other].distance(candidate) < min_dist);
+            if too_close {
+                continue;
+            }
+
+            grid[row * cols + col] = Some(samples.len());
+            active.push(samples.len());
+            samples.push(candidate);
+            placed = true;
+            break;
+        }
+
+        // nothing fits around this point anymore
+        if !placed {
+            active.swap_remove(slot);
+        }
+    }
+
+    samples.into_iter().map(|p| Vec3::new(p.x, 0.0, p.y)).collect()
+}
+
+// square lattice with the given spacing, the count points closest to the middle of bounds
+// bounds are in the XZ plane (rect y = world z)
+pub fn grid_points(
+    count: usize,
+    bounds: Rect,
+    spacing: f32,
+) -> Vec<Vec3> {
+    if count == 0 || spacing <= 0.0 {
+        return Vec::new();
+    }
+
+    let center = bounds.center();
+    let (half_cols, half_rows) = ((bounds.half_size() / spacing).floor().as_ivec2()).into();
+    let mut points: Vec<Vec2> = (-half_rows..=half_rows)
+        .flat_map(|row| (-half_cols..=half_cols).map(move |col| center + Vec2::new(col as f32, row as f32) * spacing))
+        .collect();
+    // stable sort, ties keep row order so the pick is deterministic
+    points.sort_by(|a, b| a.distance_squared(center).total_cmp(&b.distance_squared(center)));
+    points.truncate(count);
+
+    points.into_iter().map(|p| Vec3::new(p.x, 0.0, p.y)).collect()
+}
+
+// pull the first regular_count points toward a core, denser in the middle and thinner at the edge
+// radial remap: distance / max distance is raised to (1 + bias), 0 leaves the points alone
+// done after relaxation, as lloyd's would even the density back out
+pub fn bias_toward_core(
+    points: Vec<Vec3>,
+    regular_count: usize,
+    core: Vec2,
+    bias: f32,
+) -> Vec<Vec3> {
+    let mut points = points;
+    let regular_count = regular_count.min(points.len());
+    if bias <= 0.0 || regular_count == 0 {
+        return points;
+    }
+
+    let core = Vec3::new(core.x, 0.0, core.y);
+    let max_distance = points[..regular_count].iter()
+        .map(|p| p.distance(core))
+        .fold(0.0, f32::max);
+    if max_distance <= f32::EPSILON {
+        return points;
+    }
+
+    for point in points.iter_mut().take(regular_count) {
+        let offset = *point - core;
+        let distance = offset.length();
+        if distance <= f32::EPSILON {
+            continue;
+        }
+        let remapped = (distance / max_distance).powf(1.0 + bias) * max_distance;
+        *point = core + offset / distance * remapped;
+    }
+
+    points
+}
+
+// generator set between a (t = 0) and b (t = 1), points matched by index
+// surplus points of the longer set travel from/to their nearest point in the other set
+// and only exist on their own half of the morph, so the counts may differ
+pub fn interpolate_generators(a: &[Vec3], b: &[Vec3], t: f32) -> Vec<Vec3> {
+    let t = t.clamp(0.0, 1.0);
+    let shared = a.len().min(b.len());
+    let nearest = |point: Vec3, others: &[Vec3]| {
+        others.iter()
+            .copied()
+            .min_by(|x, y| x.distance_squared(point).total_cmp(&y.distance_squared(point)))
+            .unwrap_or(point)
+    };
+
+    let mut points: Vec<Vec3> = (0..shared)
+        .map(|i| a[i].lerp(b[i], t))
+        .collect();
+
+    if t < 0.5 {
+        // a's surplus fades out toward b
+        points.extend(a[shared..].iter().map(|&p| p.lerp(nearest(p, b), t)));
+    } else {
+        // b's surplus fades in from a
+        points.extend(b[shared..].iter().map(|&p| nearest(p, a).lerp(p, t)));
+    }
+
+    points
+}
+
 // constrained lloyd's relaxation, accepts fixed points
+// stops early once a step moves the regular points less than epsilon in total, 0 always runs every step
+// returns the points and the number of steps actually run
 pub fn prelax(
     regular_points: Vec<Vec3>,
     fixed_points: Vec<Vec3>,
     steps: usize,
-    width: f32,
-    height: f32,
-) -> Vec<Vec3> {
+    epsilon: f32,
+    bounds: Rect,
+) -> (Vec<Vec3>, usize) {
     let mut regular_points = regular_points;
-    let fixed_points = fixed_points;
+    let mut steps_taken = 0;
 
     for _ in 0..steps {
+        steps_taken += 1;
+        let mut movement = 0.0;
         // convert to spade library points (all points for triangulation)
         let mut all_points = regular_points.clone();
         all_points.extend(fixed_points.clone());
@@ -98,36 +309,103 @@ pub fn prelax(
                 let area = polygon_area(&cell_points);
                 if area.abs() > f32::EPSILON {
                     let centroid = polygon_centroid(&cell_points, area);
-                    let new_x = centroid.x.clamp(-width, width);
-                    let new_z = centroid.y.clamp(-height, height);
+                    let new_x = centroid.x.clamp(bounds.min.x, bounds.max.x);
+                    let new_z = centroid.y.clamp(bounds.min.y, bounds.max.y);
                     
                     // move to calculated centroid (only regular points)
-                    regular_points[i] = Vec3::new(new_x, 0.0, new_z);
+                    let moved = Vec3::new(new_x, 0.0, new_z);
+                    movement += regular_points[i].distance(moved);
+                    regular_points[i] = moved;
                 }
             }
         }
 
+        // converged, further steps would barely move anything
+        if movement < epsilon {
+            break;
+        }
     }
     
     // return combined regular + fixed points
     let mut result = regular_points;
     result.extend(fixed_points);
-    result
+    (result, steps_taken)
+}
+
+// push apart regular generators that ended up closer than min_spacing
+// relaxation can leave near-duplicates which give vpoly tiny degenerate cells
+// only the first regular_count points move, fixed points (road/boundary) stay put
+pub fn enforce_min_spacing(
+    points: Vec<Vec3>,
+    regular_count: usize,
+    min_spacing: f32,
+) -> Vec<Vec3> {
+    let mut points = points;
+    let regular_count = regular_count.min(points.len());
+    if min_spacing <= 0.0 {
+        return points;
+    }
+
+    // overshoot slightly so pairs don't settle right on the limit
+    let target = min_spacing * 1.001;
+
+    for _ in 0..crate::config::SPACING_ITERATIONS {
+        let mut moved = false;
+
+        for i in 0..regular_count {
+            for j in (i + 1)..regular_count {
+                let delta = points[j] - points[i];
+                let distance = delta.length();
+                if distance >= min_spacing {
+                    continue;
+                }
+
+                // coincident points have no direction, pick one from the index
+                let direction = if distance > f32::EPSILON {
+                    delta / distance
+                } else {
+                    let angle = j as f32 * 2.399_963; // golden angle
+                    Vec3::new(angle.cos(), 0.0, angle.sin())
+                };
+
+                // split the correction evenly between both points
+                let push = direction * (target - distance) * 0.5;
+                points[i] -= push;
+                points[j] += push;
+                moved = true;
+            }
+        }
+
+        if !moved {
+            break;
+        }
+    }
+
+    points
 }
 
 // generate a random polygon boundary with vertices arranged in a circle
 // represents settlement boundary size
-pub fn generate_boundary_polygon(num_vertices: usize, base_radius: f32, seed: u64) -> crate::systems::mesh::Polygon {
+// aspect stretches x against y (area preserving), 1.0 = round
+// radius wobble is smooth value noise around the perimeter, noise_freq lumps per turn
+// with noise_freq == num_vertices every vertex lands on a lattice point (plain per-vertex jitter)
+pub fn generate_boundary_polygon(num_vertices: usize, base_radius: f32, aspect: f32, noise_freq: usize, noise_amp: f32, seed: u64) -> crate::systems::mesh::Polygon {
+    let stretch = aspect_stretch(aspect);
     let mut rng = StdRng::seed_from_u64(seed);
     let mut vertices = Vec::with_capacity(num_vertices);
+
+    // one random value per lattice point around the perimeter
+    let lattice: Vec<f32> = (0..noise_freq.max(1))
+        .map(|_| rng.random_range(-1.0..1.0))
+        .collect();
     
     for i in 0..num_vertices {
-        let angle = (i as f32 / num_vertices as f32) * std::f32::consts::TAU;
-        let distance_variation = rng.random_range(-0.2..0.2);
-        let radius = base_radius * (1.0 + distance_variation);
+        let t = i as f32 / num_vertices as f32;
+        let angle = t * std::f32::consts::TAU;
+        let radius = base_radius * (1.0 + noise_amp * perimeter_noise(t, &lattice));
         
-        let x = angle.cos() * radius;
-        let y = angle.sin() * radius;
+        let x = angle.cos() * radius * stretch.x;
+        let y = angle.sin() * radius * stretch.y;
         
         vertices.push(Vec2::new(x, y));
     }
@@ -135,6 +413,37 @@ pub fn generate_boundary_polygon(num_vertices: usize, base_radius: f32, seed: u6
     vertices
 }
 
+// periodic 1D value noise, t in 0..1 around the perimeter
+// smoothstep between neighbouring lattice values, wraps at t = 1
+fn perimeter_noise(t: f32, lattice: &[f32]) -> f32 {
+    let n = lattice.len();
+    let x = t.rem_euclid(1.0) * n as f32;
+    let i0 = (x.floor() as usize) % n;
+    let i1 = (i0 + 1) % n;
+    let f = x.fract();
+    let s = f * f * (3.0 - 2.0 * f);
+    lattice[i0] + (lattice[i1] - lattice[i0]) * s
+}
+
+// generate a perfectly regular polygon with vertices on a circle
+// same vertex ordering/angles as generate_boundary_polygon, just without the jitter
+pub fn generate_regular_polygon(num_vertices: usize, radius: f32, aspect: f32) -> crate::systems::mesh::Polygon {
+    let stretch = aspect_stretch(aspect);
+    (0..num_vertices)
+        .map(|i| {
+            let angle = (i as f32 / num_vertices as f32) * std::f32::consts::TAU;
+            Vec2::new(angle.cos() * radius, angle.sin() * radius) * stretch
+        })
+        .collect()
+}
+
+// per-axis scale for a boundary aspect ratio (x / y)
+// split as sqrt so the enclosed area stays the same as the round boundary
+fn aspect_stretch(aspect: f32) -> Vec2 {
+    let root = aspect.max(f32::EPSILON).sqrt();
+    Vec2::new(root, 1.0 / root)
+}
+
 // generate boundary constraint generators along polygon edges  
 // creates generators on both sides: inside (inner_offset) and outside (outer_offset) the boundary
 // spacing, inner_offset, outer_offset all in meters
@@ -177,8 +486,15 @@ pub fn generate_boundary_generators(boundary_polygon: &[Vec2], spacing: f32, inn
                 let point_on_edge = start + edge_vec * t;
 
                 // inner generators (inside boundary)
+                // near a reflex corner of a concave boundary the offset can cross another edge,
+                // then try the other side of the edge, and drop the generator if that is outside too
                 let inner_pos = point_on_edge + inward_normal * inner_offset;
-                generators.push(Vec3::new(inner_pos.x, 0.0, inner_pos.y));
+                let flipped_pos = point_on_edge - inward_normal * inner_offset;
+                if point_in_polygon(&inner_pos, boundary_polygon) {
+                    generators.push(Vec3::new(inner_pos.x, 0.0, inner_pos.y));
+                } else if point_in_polygon(&flipped_pos, boundary_polygon) {
+                    generators.push(Vec3::new(flipped_pos.x, 0.0, flipped_pos.y));
+                }
                 
                 // outer generators (outside boundary)
                 let outer_pos = point_on_edge + outward_normal * outer_offset;
@@ -190,8 +506,108 @@ pub fn generate_boundary_generators(boundary_polygon: &[Vec2], spacing: f32, inn
     generators
 }
 
+// indices of the road points the spline runs through, repeated points are dropped
+fn road_control_indices(road_path: &[Vec3]) -> Vec<usize> {
+    let mut kept: Vec<usize> = Vec::with_capacity(road_path.len());
+    for (idx, point) in road_path.iter().enumerate() {
+        if kept.last().is_none_or(|&last| road_path[last].distance(*point) >= 0.001) {
+            kept.push(idx);
+        }
+    }
+    kept
+}
+
+// resample a road path through a centripetal Catmull-Rom spline, subdivisions points per segment
+// the control points are kept, the ends are padded by mirroring so the curve reaches them
+// 0 subdivisions returns the path unchanged
+pub fn smooth_road_path(road_path: &[Vec3], subdivisions: usize) -> Vec<Vec3> {
+    let control: Vec<Vec3> = road_control_indices(road_path).into_iter().map(|idx| road_path[idx]).collect();
+    if subdivisions == 0 || control.len() < 3 {
+        return road_path.to_vec();
+    }
+
+    let n = control.len();
+    let at = |i: isize| -> Vec3 {
+        if i < 0 {
+            control[0] * 2.0 - control[1]
+        } else if i as usize >= n {
+            control[n - 1] * 2.0 - control[n - 2]
+        } else {
+            control[i as usize]
+        }
+    };
+
+    let mut smoothed = Vec::with_capacity((n - 1) * (subdivisions + 1) + 1);
+    for i in 0..(n - 1) as isize {
+        let (p0, p1, p2, p3) = (at(i - 1), at(i), at(i + 1), at(i + 2));
+        // centripetal knots, no cusps or self loops within a segment
+        let knot = |a: Vec3, b: Vec3| a.distance(b).sqrt().max(1e-4);
+        let t1 = knot(p0, p1);
+        let t2 = t1 + knot(p1, p2);
+        let t3 = t2 + knot(p2, p3);
+
+        smoothed.push(p1);
+        for step in 1..=subdivisions {
+            let t = t1 + (t2 - t1) * step as f32 / (subdivisions + 1) as f32;
+            let a1 = p0 * ((t1 - t) / t1) + p1 * (t / t1);
+            let a2 = p1 * ((t2 - t) / (t2 - t1)) + p2 * ((t - t1) / (t2 - t1));
+            let a3 = p2 * ((t3 - t) / (t3 - t2)) + p3 * ((t - t2) / (t3 - t2));
+            let b1 = a1 * ((t2 - t) / t2) + a2 * (t / t2);
+            let b2 = a2 * ((t3 - t) / (t3 - t1)) + a3 * ((t - t1) / (t3 - t1));
+            smoothed.push(b1 * ((t2 - t) / (t2 - t1)) + b2 * ((t - t1) / (t2 - t1)));
+        }
+    }
+    smoothed.push(control[n - 1]);
+    smoothed
+}
+
+// per point road widths resampled alongside smooth_road_path, linear between the control points
+// widths are parallel to road_path and come back parallel to the smoothed path
+pub fn smooth_road_widths(road_path: &[Vec3], widths: &[f32], subdivisions: usize) -> Vec<f32> {
+    let control = road_control_indices(road_path);
+    if subdivisions == 0 || control.len() < 3 {
+        return widths.to_vec();
+    }
+
+    let mut smoothed = Vec::with_capacity((control.len() - 1) * (subdivisions + 1) + 1);
+    for pair in control.windows(2) {
+        let (start, end) = (widths[pair[0]], widths[pair[1]]);
+        smoothed.extend((0..=subdivisions).map(|step| start + (end - start) * step as f32 / (subdivisions + 1) as f32));
+    }
+    smoothed.push(widths[control[control.len() - 1]]);
+    smoothed
+}
+
+// points where the road path crosses itself, between segments that don't share a vertex
+pub fn road_crossings(road_path: &[Vec3]) -> Vec<Vec2> {
+    let segments: Vec<(Vec2, Vec2)> = road_path.windows(2)
+        .map(|segment| (segment[0].xz(), segment[1].xz()))
+        .collect();
+    let mut crossings = Vec::new();
+    for (i, &(a_start, a_end)) in segments.iter().enumerate() {
+        for &(b_start, b_end) in segments.iter().skip(i + 2) {
+            if let Some(crossing) = line_segment_intersection(a_start, a_end, b_start, b_end) {
+                crossings.push(crossing);
+            }
+        }
+    }
+    crossings
+}
+
 // generate road constraint generators along road path
-pub fn generate_road_generators(road_path: &[Vec3]) -> Vec<Vec3> {
+// generators near a crossing are dropped so the junction stays open instead of being walled off
+pub fn generate_road_generators(road_path: &[Vec3], smoothing: usize) -> Vec<Vec3> {
+    let mut generators = road_path_generators(road_path, smoothing);
+    let crossings = road_crossings(&smooth_road_path(road_path, smoothing));
+    generators.retain(|generator| {
+        crossings.iter().all(|crossing| generator.xz().distance(*crossing) > crate::config::CORNER_CONSTRAINT_DISTANCE)
+    });
+    generators
+}
+
+// constraint generator pairs on both sides of the road path
+// a smoothed path has no corners, its pairs are spread evenly by arc length instead
+fn road_path_generators(road_path: &[Vec3], smoothing: usize) -> Vec<Vec3> {
     if road_path.len() < 2 {
         return Vec::new();
     }
@@ -199,6 +615,36 @@ pub fn generate_road_generators(road_path: &[Vec3]) -> Vec<Vec3> {
     let mut generators = Vec::new();
     let spacing = crate::config::ROAD_GENERATOR_SPACING;
     let offset = crate::config::ROAD_GENERATOR_OFFSET;
+
+    if smoothing > 0 {
+        let curve = smooth_road_path(road_path, smoothing);
+        let segments: Vec<(Vec3, Vec3, f32)> = curve.windows(2)
+            .map(|segment| (segment[0], segment[1], segment[0].distance(segment[1])))
+            .filter(|&(_, _, length)| length >= 0.001) // skip degenerate edges
+            .collect();
+        let total: f32 = segments.iter().map(|&(_, _, length)| length).sum();
+        if total <= 0.1 {
+            return generators;
+        }
+
+        let num_pairs = (total / spacing).ceil() as usize + 1;
+        let (mut segment_idx, mut segment_start) = (0, 0.0);
+        for j in 0..num_pairs {
+            let distance = total * j as f32 / (num_pairs - 1) as f32;
+            while segment_idx + 1 < segments.len() && segment_start + segments[segment_idx].2 < distance {
+                segment_start += segments[segment_idx].2;
+                segment_idx += 1;
+            }
+            let (start, end, length) = segments[segment_idx];
+            let edge_dir = (end - start) / length;
+            let perpendicular = Vec3::new(-edge_dir.z, 0.0, edge_dir.x);
+            let point_on_edge = start + edge_dir * (distance - segment_start).clamp(0.0, length);
+
+            generators.push(point_on_edge + perpendicular * offset);
+            generators.push(point_on_edge - perpendicular * offset);
+        }
+        return generators;
+    }
     let corner_distance = crate::config::CORNER_CONSTRAINT_DISTANCE;
     
     // process straight segments between corners
@@ -2,17 +2,37 @@ use bevy::prelude::*;
 use rand::rngs::StdRng;
 use rand::prelude::*;
 
-use crate::systems::mesh::Polygon;
-use super::utils::{polygon_area, polygon_centroid, line_segment_intersection};
+use crate::systems::mesh::{Polygon, SubdivisionStrategy};
+use super::utils::{polygon_area, polygon_centroid, line_segment_intersection, point_to_line_distance, point_in_polygon, obb_aspect_ratio};
+use super::ops::EPSILON;
+
+/// Gap left by an alley cut between the plots either side of it,
+/// a quad `width` wide along the stretch of the cut inside the polygon.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlleyPolygon {
+    pub quad: Polygon,
+    pub width: f32,
+}
 
 /// Recursively subdivides a polygon into smaller plots (potential building plots)
+/// Plots larger than `max_sq` keep getting cut past the normal stop conditions.
+/// The gap of every cut made as an alley is appended to `alleys`.
+/// Cuts land near `split_bias` of the way along the cut edge, `grid_chaos` blends that toward a uniform position.
+/// `strategy` picks the cut direction, both draw the same random numbers so a seed only changes its cuts.
+/// Slivers, plots longer than `max_aspect_ratio` times their width (0 = no limit), are cut across their
+/// long axis whatever the strategy, and dropped like empty plots once they are too small to cut.
+/// A block (depth 0) of at least `COURTYARD_MIN_AREA` becomes a courtyard block with `courtyard_prob`:
+/// only a ring along its edges is built on, each side of the ring subdivided on its own.
 /// 
 /// # Returns
 /// A vector of polygons representing subdivision plots
+#[allow(clippy::too_many_arguments)]
 pub fn subdivide_to_plots(
     polygon: &Polygon,
     min_sq: f32,
+    max_sq: f32,
     grid_chaos: f32,
+    split_bias: f32,
     size_chaos: f32,
     empty_prob: f32,
     depth: usize,
@@ -20,17 +40,53 @@ pub fn subdivide_to_plots(
     max_recursion_depth: usize,
     alley_chance: f32,
     alley_width: f32,
+    alleys: &mut Vec<AlleyPolygon>,
+    strategy: SubdivisionStrategy,
+    max_aspect_ratio: f32,
+    courtyard_prob: f32,
 ) -> Vec<Polygon> {
-    // constrain depth
-    if depth > max_recursion_depth {
-        return vec![polygon.clone()];
-    }
-
     let area = polygon_area(polygon);
+    let oversized = area.abs() > max_sq;
+    let is_sliver = |plot: &Polygon| max_aspect_ratio > 0.0 && obb_aspect_ratio(plot) > max_aspect_ratio;
+    let sliver = is_sliver(polygon);
+    // a sliver that can't be cut any further is not a usable plot
+    let keep = || if sliver { Vec::new() } else { vec![polygon.clone()] };
+
+    // constrain depth, oversized plots get a few extra levels
+    if depth > max_recursion_depth && (!oversized || depth > max_recursion_depth + crate::config::FORCED_SPLIT_DEPTH) {
+        return keep();
+    }
 
     // exit if too small
-    if area < min_sq {
-        return vec![polygon.clone()];
+    if area < min_sq && !oversized {
+        return keep();
+    }
+
+    // courtyard, the rng is only drawn when enabled so existing seeds keep their layout
+    if depth == 0 && courtyard_prob > 0.0 && area >= crate::config::COURTYARD_MIN_AREA && rng.random::<f32>() < courtyard_prob
+        && let Some(ring) = courtyard_ring(polygon, crate::config::COURTYARD_DEPTH, min_sq) {
+        let mut buildings = Vec::new();
+        for side in ring {
+            buildings.extend(subdivide_to_plots(
+                &side,
+                min_sq,
+                max_sq,
+                grid_chaos,
+                split_bias,
+                size_chaos,
+                empty_prob,
+                depth + 1,
+                rng,
+                max_recursion_depth,
+                alley_chance,
+                alley_width,
+                alleys,
+                strategy,
+                max_aspect_ratio,
+                0.0,
+            ));
+        }
+        return buildings;
     }
 
     // find longest edge of the polygon,
@@ -39,8 +95,10 @@ pub fn subdivide_to_plots(
         return vec![polygon.clone()];
     };
 
-    let spread = 0.8 * grid_chaos;
-    let ratio = (1.0 - spread) / 2.0 + rng.random::<f32>() * spread;
+    // uniform within the middle 80% of the edge, blended with the biased target
+    // a bias of 0.5 gives the plain centred spread
+    let uniform = 0.1 + rng.random::<f32>() * 0.8;
+    let ratio = split_bias + (uniform - split_bias) * grid_chaos;
     let angle_spread = if area < min_sq * 4.0 {
         0.0
     } else {
@@ -55,17 +113,33 @@ pub fn subdivide_to_plots(
 
     let alley_width = if rng.random::<f32>() < alley_chance { alley_width } else { 0.0 };
 
-    // cut the polygon
-    let halves = bisect_poly(polygon, longest_idx, ratio, angle_offset, alley_width);
+    // cut the polygon, slivers across their long axis so the pieces get squarer
+    let cut_strategy = if sliver { SubdivisionStrategy::ObbAxis } else { strategy };
+    let (mut halves, cuts) = match cut_strategy {
+        SubdivisionStrategy::LongestEdge => bisect_poly_with_cut(polygon, longest_idx, ratio, angle_offset, alley_width),
+        SubdivisionStrategy::ObbAxis => bisect_poly_across_obb(polygon, ratio, angle_offset, alley_width),
+    };
+    if alley_width > 0.0 && halves.len() >= 2 {
+        alleys.extend(cuts.into_iter()
+            .map(|(start, end)| super::ops::ribbon(&[start, end], alley_width))
+            .filter(|quad| quad.len() == 4)
+            .map(|quad| AlleyPolygon { quad, width: alley_width }));
+    }
+    let split_failed = |halves: &Vec<Polygon>| halves.len() == 1 && halves[0].len() == polygon.len();
 
-    if halves.len() == 1 && halves[0].len() == polygon.len() {
+    if split_failed(&halves) && oversized {
+        // too big to keep, retry with plain cuts across the other edges
+        halves = forced_bisect(polygon);
+    }
+
+    if split_failed(&halves) {
         // split failed, treat as final
-        return vec![polygon.clone()];
+        return keep();
     }
 
     let mut buildings = Vec::new();
 
-    // repeat for both halves
+    // repeat for every piece
     for half in halves {
         let half_area = polygon_area(&half);
         
@@ -73,7 +147,7 @@ pub fn subdivide_to_plots(
         let size_factor = 2_f32.powf(4.0 * size_chaos * (rng.random::<f32>() - 0.5));
         let adjusted_min = min_sq * size_factor;
         
-        if half_area < adjusted_min * 2.0 {
+        if half_area < adjusted_min * 2.0 && half_area.abs() <= max_sq && !is_sliver(&half) {
             // final plot, check if should be empty
             if rng.random::<f32>() >= empty_prob {
                 buildings.push(half);
@@ -83,7 +157,9 @@ pub fn subdivide_to_plots(
             buildings.extend(subdivide_to_plots(
                 &half,
                 min_sq,
+                max_sq,
                 grid_chaos,
+                split_bias,
                 size_chaos,
                 empty_prob,
                 depth + 1,
@@ -91,6 +167,10 @@ pub fn subdivide_to_plots(
                 max_recursion_depth,
                 alley_chance,
                 alley_width,
+                alleys,
+                strategy,
+                max_aspect_ratio,
+                0.0,
             ));
         }
     }
@@ -98,6 +178,106 @@ pub fn subdivide_to_plots(
     buildings
 }
 
+/// Ring of building depth `depth` along the edges of a block, split into one quad per edge
+/// between the outline and its inset. The inset is the empty courtyard.
+/// 
+/// # Returns
+/// The ring's sides, or `None` when the block is too small to leave a courtyard of at least `min_yard`.
+fn courtyard_ring(polygon: &Polygon, depth: f32, min_yard: f32) -> Option<Vec<Polygon>> {
+    let outer = clean_ring(polygon);
+    let inner = polygon_inset(&outer, depth)?;
+    if polygon_area(&inner) < min_yard {
+        return None;
+    }
+
+    // the inset keeps one corner per outline corner, side i runs between the two edges i
+    let n = outer.len();
+    let sides = (0..n)
+        .map(|i| vec![outer[i], outer[(i + 1) % n], inner[(i + 1) % n], inner[i]])
+        .filter(|side| polygon_area(side) > EPSILON)
+        .collect();
+    Some(sides)
+}
+
+/// Copy of `polygon` without repeated vertices, wound counter-clockwise.
+fn clean_ring(polygon: &Polygon) -> Polygon {
+    let mut ring = polygon.clone();
+    ring.dedup_by(|a, b| a.distance(*b) <= EPSILON);
+    while ring.len() > 1 && ring[0].distance(ring[ring.len() - 1]) <= EPSILON {
+        ring.pop();
+    }
+    if polygon_area(&ring) < 0.0 {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Shrinks a polygon by moving every edge `distance` inward along its normal and re-intersecting
+/// neighbouring edges. Unlike `ops::inset` corners are not mitre-capped, so the strip given up along
+/// every edge is exactly `distance` wide.
+/// 
+/// # Returns
+/// The inset polygon, counter-clockwise, or `None` when it collapses, turns inside out or self-intersects.
+pub fn polygon_inset(polygon: &Polygon, distance: f32) -> Option<Polygon> {
+    let ring = clean_ring(polygon);
+    if ring.len() < 3 {
+        return None;
+    }
+
+    // each edge as a line, moved to the left of the ccw outline
+    let n = ring.len();
+    let lines: Vec<(Vec2, Vec2)> = (0..n)
+        .map(|i| {
+            let dir = (ring[(i + 1) % n] - ring[i]).normalize();
+            (ring[i] + dir.perp() * distance, dir)
+        })
+        .collect();
+
+    // corner i where the lines of the edges before and after it meet
+    let inset: Polygon = (0..n)
+        .map(|i| {
+            let (p0, d0) = lines[(i + n - 1) % n];
+            let (p1, d1) = lines[i];
+            let denom = d0.perp_dot(d1);
+            if denom.abs() <= EPSILON {
+                // collinear edges, the corner just moves inward
+                p1
+            } else {
+                p0 + d0 * ((p1 - p0).perp_dot(d1) / denom)
+            }
+        })
+        .collect();
+
+    // an edge pointing backwards means the inset went past the medial axis
+    let flipped = (0..n).any(|i| (inset[(i + 1) % n] - inset[i]).dot(ring[(i + 1) % n] - ring[i]) <= 0.0);
+    if flipped || polygon_area(&inset) <= EPSILON || !super::ops::is_simple(&inset) {
+        return None;
+    }
+    Some(inset)
+}
+
+/// Fallback cut for oversized plots: halves the polygon perpendicular to each edge,
+/// longest first, until one succeeds.
+/// 
+/// # Returns
+/// The pieces, or the polygon unchanged if every cut failed
+fn forced_bisect(polygon: &Polygon) -> Vec<Polygon> {
+    let mut edges: Vec<usize> = (0..polygon.len()).collect();
+    edges.sort_by(|&a, &b| {
+        let length = |i: usize| polygon[i].distance(polygon[(i + 1) % polygon.len()]);
+        length(b).total_cmp(&length(a))
+    });
+
+    for idx in edges {
+        let halves = bisect_poly(polygon, idx, 0.5, 0.0, 0.0);
+        if halves.len() >= 2 {
+            return halves;
+        }
+    }
+
+    vec![polygon.clone()]
+}
+
 /// Find vertex that starts the longest edge of the polygon
 /// 
 /// # Returns
@@ -129,7 +309,7 @@ pub fn vlongest_edge(
 /// optionally apply angular offset and separation
 /// 
 /// # Returns
-/// A vector containing one or two polygons resulting from the split.
+/// A vector containing the polygons resulting from the split, more than two when a concave polygon is cut.
 pub fn bisect_poly(
     polygon: &Polygon,
     start_idx: usize,
@@ -137,8 +317,22 @@ pub fn bisect_poly(
     angle_offset: f32,
     separation: f32,
 ) -> Vec<Polygon> {
+    bisect_poly_with_cut(polygon, start_idx, ratio, angle_offset, separation).0
+}
+
+/// Same as `bisect_poly`, also handing back where the cut crosses the polygon.
+/// 
+/// # Returns
+/// The split polygons, and the stretches of the cut inside the polygon, one per pair of boundary crossings.
+fn bisect_poly_with_cut(
+    polygon: &Polygon,
+    start_idx: usize,
+    ratio: f32,
+    angle_offset: f32,
+    separation: f32,
+) -> (Vec<Polygon>, Vec<(Vec2, Vec2)>) {
     if polygon.len() < 3 || start_idx >= polygon.len() {
-        return vec![polygon.clone()];
+        return (vec![polygon.clone()], Vec::new());
     }
 
     let next_idx = (start_idx + 1) % polygon.len();
@@ -149,9 +343,46 @@ pub fn bisect_poly(
     let edge_dir = next_v - start_v;
     let cut_point = start_v + edge_dir * ratio;
     
+    split_across(polygon, cut_point, edge_dir, angle_offset, separation)
+}
+
+/// Cut across the long axis of the polygon's oriented bounding box, at `ratio` along it.
+/// The line is clipped to the polygon like any other cut, so concave plots still split where it crosses them.
+/// 
+/// # Returns
+/// The split polygons, and the stretches of the cut inside the polygon, one per pair of boundary crossings.
+fn bisect_poly_across_obb(
+    polygon: &Polygon,
+    ratio: f32,
+    angle_offset: f32,
+    separation: f32,
+) -> (Vec<Polygon>, Vec<(Vec2, Vec2)>) {
+    let obb = super::ops::oriented_bounding_box(polygon);
+    if obb.len() != 4 {
+        return (vec![polygon.clone()], Vec::new());
+    }
+
+    // the first edge runs along the box's first axis, the second across it
+    let (first, second) = (obb[1] - obb[0], obb[2] - obb[1]);
+    let (start, axis) = if first.length_squared() >= second.length_squared() { (obb[0], first) } else { (obb[1], second) };
+    split_across(polygon, start + axis * ratio, axis, angle_offset, separation)
+}
+
+/// Splits a polygon along the line through `cut_point` perpendicular to `axis`, turned by `angle_offset`.
+/// A line crossing a concave polygon more than twice splits it into every piece it separates.
+/// 
+/// # Returns
+/// The split polygons, and the stretches of the cut inside the polygon, one per pair of boundary crossings.
+fn split_across(
+    polygon: &Polygon,
+    cut_point: Vec2,
+    axis: Vec2,
+    angle_offset: f32,
+    separation: f32,
+) -> (Vec<Polygon>, Vec<(Vec2, Vec2)>) {
     // calculate perpendicular cutting vector
     // w/ angle offset
-    let perp = Vec2::new(-edge_dir.y, edge_dir.x).normalize();
+    let perp = Vec2::new(-axis.y, axis.x).normalize();
     let rotated = Vec2::new(
         perp.x * angle_offset.cos() - perp.y * angle_offset.sin(),
         perp.x * angle_offset.sin() + perp.y * angle_offset.cos()
@@ -175,7 +406,7 @@ pub fn bisect_poly(
     let line_start = cut_point - rotated * line_extent;
     let line_end = cut_point + rotated * line_extent;
     
-    // split polygon by line
+    // split polygon by line, a straight line crosses each edge at most once
     let mut intersections = Vec::new();
     for i in 0..polygon.len() {
         let j = (i + 1) % polygon.len();
@@ -188,61 +419,127 @@ pub fn bisect_poly(
         }
     }
     
-    // ensure clean split, requires exactly 2 intersections
-    if intersections.len() != 2 {
-        return vec![polygon.clone()];
+    // a clean split enters and leaves the polygon, so crossings come in pairs
+    if intersections.len() < 2 || intersections.len() % 2 != 0 {
+        return (vec![polygon.clone()], Vec::new());
     }
     
-    // sort intersections by edge idx
-    intersections.sort_by_key(|&(idx, _)| idx);
-    
-    let (idx1, int1) = intersections[0];
-    let (idx2, int2) = intersections[1];
-    
-    // simple split
-    let mut poly1 = Vec::new();
-    let mut poly2 = Vec::new();
-    
-    // first polygon
-    poly1.push(int1);
-    for i in (idx1 + 1)..=idx2 {
-        poly1.push(polygon[i]);
+    // outline with the crossings spliced in after their edge's start vertex
+    let mut nodes = Vec::with_capacity(polygon.len() + intersections.len());
+    let mut crossing_nodes = Vec::with_capacity(intersections.len());
+    let mut next_crossing = intersections.iter().peekable();
+    for (i, &v) in polygon.iter().enumerate() {
+        nodes.push(v);
+        if let Some(&(_, point)) = next_crossing.next_if(|&&(idx, _)| idx == i) {
+            crossing_nodes.push(nodes.len());
+            nodes.push(point);
+        }
     }
-    poly1.push(int2);
     
-    // second polygon
-    poly2.push(int2);
-    for i in (idx2 + 1)..polygon.len() {
-        poly2.push(polygon[i]);
-    }
-    for i in 0..=idx1 {
-        poly2.push(polygon[i]);
+    // pair the crossings in order along the line, the stretch between each pair lies inside the polygon
+    let mut along_line = crossing_nodes.clone();
+    along_line.sort_by(|&a, &b| (nodes[a] - cut_point).dot(rotated).total_cmp(&(nodes[b] - cut_point).dot(rotated)));
+    let mut partner = vec![None; nodes.len()];
+    let mut cuts = Vec::with_capacity(along_line.len() / 2);
+    for pair in along_line.chunks(2) {
+        let (a, b) = (pair[0].min(pair[1]), pair[0].max(pair[1]));
+        partner[a] = Some(b);
+        partner[b] = Some(a);
+        cuts.push((nodes[a], nodes[b]));
     }
-    poly2.push(int1);
     
-    let mut result = Vec::new();
-    if poly1.len() >= 3 && polygon_area(&poly1) > 0.1 {
-        if separation > 0.0 {
-            result.push(push_polygon_from_line(&poly1, line_start, line_end, separation * 0.5));
-        } else {
-            result.push(poly1);
+    // trace each piece: follow the outline from a crossing to the next one, then jump across its cut
+    let mut departed = vec![false; nodes.len()];
+    let mut pieces = Vec::new();
+    for &start in &crossing_nodes {
+        if departed[start] {
+            continue;
+        }
+        let mut piece = Vec::new();
+        let mut current = start;
+        for _ in 0..nodes.len() {
+            departed[current] = true;
+            piece.push(nodes[current]);
+            current = (current + 1) % nodes.len();
+            while partner[current].is_none() {
+                piece.push(nodes[current]);
+                current = (current + 1) % nodes.len();
+            }
+            piece.push(nodes[current]);
+            current = partner[current].unwrap();
+            if current == start {
+                break;
+            }
         }
+        pieces.push(piece);
     }
-    if poly2.len() >= 3 && polygon_area(&poly2) > 0.1 {
-        if separation > 0.0 {
-            result.push(push_polygon_from_line(&poly2, line_start, line_end, separation * 0.5));
-        } else {
-            result.push(poly2);
+    
+    let mut result = Vec::new();
+    for piece in pieces {
+        if piece.len() >= 3 && polygon_area(&piece) > 0.1 {
+            if separation > 0.0 {
+                result.push(push_polygon_from_line(&piece, line_start, line_end, separation * 0.5));
+            } else {
+                result.push(piece);
+            }
         }
     }
     
     if result.is_empty() {
-        vec![polygon.clone()]
+        (vec![polygon.clone()], Vec::new())
     } else {
-        result
+        (result, cuts)
     }
 }
 
+/// Subtracts a notch from a plot, giving an L shape (notch in a corner) or a U shape (notch in the middle of an edge).
+/// The notch is spliced into the outline directly, which is the exact difference for a notch touching the plot's edge.
+/// Meant for convex plots: the notch is inside as long as its inner corners are.
+/// 
+/// # Returns
+/// The concave footprint, or `None` when the plot has fewer than 4 vertices,
+/// the notch would poke out of the plot, or less than `min_sq` would be left.
+pub fn notch_plot(polygon: &Polygon, rng: &mut StdRng, min_sq: f32) -> Option<Polygon> {
+    let n = polygon.len();
+    if n < 4 {
+        return None;
+    }
+
+    let area = polygon_area(polygon);
+    let u_shape = rng.random_bool(0.5);
+    let edge_idx = rng.random_range(0..n);
+    let (a, b) = (polygon[edge_idx], polygon[(edge_idx + 1) % n]);
+
+    let (inner, notched) = if u_shape {
+        // rectangle cut into the middle of edge a -> b, depth relative to the plot's extent away from the edge
+        let edge = b - a;
+        let inward = if area > 0.0 { edge.perp() } else { -edge.perp() }.normalize_or_zero();
+        let extent = polygon.iter().map(|p| (*p - a).dot(inward)).fold(0.0, f32::max);
+        let depth = extent * rng.random_range(0.3..0.5);
+        let (p0, p1) = (a + edge * rng.random_range(0.25..0.4), a + edge * rng.random_range(0.6..0.75));
+
+        let inner = [p0 + inward * depth, p1 + inward * depth];
+        let mut notched = polygon.clone();
+        notched.splice(edge_idx + 1..edge_idx + 1, [p0, inner[0], inner[1], p1]);
+        (inner.to_vec(), notched)
+    } else {
+        // parallelogram in corner a, spanned by the two edges meeting there
+        let prev = polygon[(edge_idx + n - 1) % n];
+        let along_prev = (prev - a) * rng.random_range(0.3..0.5);
+        let along_next = (b - a) * rng.random_range(0.3..0.5);
+
+        let inner = a + along_prev + along_next;
+        let mut notched = polygon.clone();
+        notched.splice(edge_idx..edge_idx + 1, [a + along_prev, inner, a + along_next]);
+        (vec![inner], notched)
+    };
+
+    if !inner.iter().all(|p| point_in_polygon(p, polygon)) || polygon_area(&notched).abs() < min_sq {
+        return None;
+    }
+    Some(notched)
+}
+
 /// Shrinks a polygon away from a line by moving vertices that are close to the line.
 /// 
 /// # Returns
@@ -280,7 +577,7 @@ pub fn push_polygon_from_line(
             let t = vertex_vec.dot(line_vec) / line_vec.length_squared();
             
             // only shrink if vertex projects onto the actual line segment (not the infinite line)
-            if t >= -0.1 && t <= 1.1 { // small buffer to handle edge cases
+            if (-0.1..=1.1).contains(&t) { // small buffer to handle edge cases
                 vertex + separation_direction * distance
             } else {
                 vertex
@@ -360,21 +657,3 @@ pub fn constrain_road_generator_cells(
     
     result
 }
-
-/// Calculates shortest distance from a point to a line segment 2D
-/// 
-/// # Returns
-/// The PERPENDICULAR distance from `point` to the line segment defined by `line_start` and `line_end`.
-fn point_to_line_distance(point: Vec2, line_start: Vec2, line_end: Vec2) -> f32 {
-    let line_vec = line_end - line_start;
-    let point_vec = point - line_start;
-    let line_len = line_vec.length();
-    
-    if line_len < f32::EPSILON {
-        return point_vec.length();
-    }
-    
-    let t = (point_vec.dot(line_vec) / line_len.powi(2)).clamp(0.0, 1.0);
-    let projection = line_start + line_vec * t;
-    point.distance(projection)
-}
\ No newline at end of file
@@ -2,6 +2,7 @@
 
 use bevy::prelude::*;
 use spade::Point2;
+use std::collections::{HashMap, HashSet};
 use crate::systems::mesh::Polygon;
 
 /// find the intersection between two lines, lookup Cramer's rule
@@ -22,7 +23,7 @@ pub fn line_segment_intersection(p1: Vec2, p2: Vec2, p3: Vec2, p4: Vec2) -> Opti
     let t = (s2.x * (p1.y - p3.y) - s2.y * (p1.x - p3.x)) / denom;
     
     // check if intersection is within both segments
-    if s >= 0.0 && s <= 1.0 && t >= 0.0 && t <= 1.0 {
+    if (0.0..=1.0).contains(&s) && (0.0..=1.0).contains(&t) {
         Some(p1 + t * s1)
     } else {
         None
@@ -41,7 +42,7 @@ pub fn polygon_area(polygon: &Polygon) -> f32 {
     
     for i in 0..n {
         let j = (i + 1) % n;
-        area += polygon[i].x as f32 * polygon[j].y as f32 - polygon[j].x as f32 * polygon[i].y as f32;
+        area += polygon[i].x * polygon[j].y - polygon[j].x * polygon[i].y;
     }
     
     area / 2.0
@@ -65,8 +66,8 @@ pub fn polygon_centroid(polygon: &Polygon, area: f32) -> Vec2 {
     }
     
     let area_6 = 6.0 * area;
-    centroid.x = (centroid.x as f32 / area_6) as f32;
-    centroid.y = (centroid.y as f32 / area_6) as f32;
+    centroid.x /= area_6;
+    centroid.y /= area_6;
     
     centroid
 }
@@ -75,6 +76,14 @@ pub fn polygon_centroid(polygon: &Polygon, area: f32) -> Vec2 {
 /// # Returns a tuple `(x, y)` representing the circumcenter coordinates. 
 /// Falls back to the triangle centroid if points are collinear or circumcenter is extreme.
 pub fn calculate_circumcenter(p1: Point2<f64>, p2: Point2<f64>, p3: Point2<f64>) -> (f64, f64) {
+    calculate_weighted_circumcenter(p1, p2, p3, [0.0; 3])
+}
+
+/// Calculates the power center of a triangle: the point with equal power distance `|x - p|^2 - w` to all three corners.
+/// This is a power diagram vertex, the corner with the larger weight pushes it away. Zero weights give the circumcenter.
+/// # Returns a tuple `(x, y)` representing the power center coordinates.
+/// Falls back to the triangle centroid if points are collinear or the center is extreme.
+pub fn calculate_weighted_circumcenter(p1: Point2<f64>, p2: Point2<f64>, p3: Point2<f64>, weights: [f64; 3]) -> (f64, f64) {
     let ax = p1.x; // x1
     let ay = p1.y; // y1
     let bx = p2.x; // x2
@@ -98,16 +107,19 @@ pub fn calculate_circumcenter(p1: Point2<f64>, p2: Point2<f64>, p3: Point2<f64>)
     
     // now get circumcenter coordinates
     // these formulas are derived by solving:
-    // 1) (X - x1)^2 + (Y - y1)^2 = (X - x2)^2 + (Y - y2)^2
-    // 2) (X - x2)^2 + (Y - y2)^2 = (X - x3)^2 + (Y - y3)^2
-    // lookup (linearized equations), you get:
-    let ux = ((ax * ax + ay * ay) * (by - cy) 
-                 + (bx * bx + by * by) * (cy - ay) 
-                 + (cx * cx + cy * cy) * (ay - by)) / d;
+    // 1) (X - x1)^2 + (Y - y1)^2 - w1 = (X - x2)^2 + (Y - y2)^2 - w2
+    // 2) (X - x2)^2 + (Y - y2)^2 - w2 = (X - x3)^2 + (Y - y3)^2 - w3
+    // lookup (linearized equations), the weights only shift the squared lengths:
+    let a_sq = ax * ax + ay * ay - weights[0];
+    let b_sq = bx * bx + by * by - weights[1];
+    let c_sq = cx * cx + cy * cy - weights[2];
+    let ux = (a_sq * (by - cy) 
+                 + b_sq * (cy - ay) 
+                 + c_sq * (ay - by)) / d;
 
-    let uy = ((ax * ax + ay * ay) * (cx - bx) 
-                 + (bx * bx + by * by) * (ax - cx) 
-                 + (cx * cx + cy * cy) * (bx - ax)) / d;
+    let uy = (a_sq * (cx - bx) 
+                 + b_sq * (ax - cx) 
+                 + c_sq * (bx - ax)) / d;
     
     // validate circumcenter is within reasonable bounds
     let canvas_bound = crate::config::CANVAS_WIDTH as f64 * 5.0; // allow margin
@@ -147,4 +159,233 @@ pub fn point_in_polygon(point: &Vec2, polygon: &[Vec2]) -> bool {
     }
     
     inside
-}
\ No newline at end of file
+}
+
+/// Clips a polygon against the half-plane to the left of the directed line `a -> b` (Sutherland–Hodgman step).
+/// # Returns the clipped polygon, empty if nothing remains.
+pub fn clip_polygon_half_plane(polygon: &[Vec2], a: Vec2, b: Vec2) -> Polygon {
+    let dir = b - a;
+    let side = |p: Vec2| dir.perp_dot(p - a); // > 0 on the left
+    
+    let mut result = Vec::new();
+    for i in 0..polygon.len() {
+        let current = polygon[i];
+        let next = polygon[(i + 1) % polygon.len()];
+        let (d_current, d_next) = (side(current), side(next));
+        
+        if d_current >= 0.0 {
+            result.push(current);
+        }
+        // edge crosses the line, add the crossing point
+        if (d_current >= 0.0) != (d_next >= 0.0) {
+            let t = d_current / (d_current - d_next);
+            result.push(current + (next - current) * t);
+        }
+    }
+    
+    result
+}
+
+/// Clips a polygon to a convex polygon, one `clip_polygon_half_plane` pass per clip edge (Sutherland–Hodgman).
+/// The subject may be concave, the clip may be wound either way.
+/// A subject the clip cuts into several pieces comes back as one outline joined by zero-width bridges along the clip's edges.
+/// # Returns the overlap, in the subject's winding, empty if they don't overlap.
+pub fn clip_polygon(subject: &[Vec2], clip: &[Vec2]) -> Polygon {
+    if subject.len() < 3 || clip.len() < 3 {
+        return Vec::new();
+    }
+
+    // make the clip polygon CCW so "inside" is always to the left
+    let mut clip = clip.to_vec();
+    if polygon_area(&clip) < 0.0 {
+        clip.reverse();
+    }
+
+    let mut result = subject.to_vec();
+    for i in 0..clip.len() {
+        if result.len() < 3 {
+            return Vec::new();
+        }
+        result = clip_polygon_half_plane(&result, clip[i], clip[(i + 1) % clip.len()]);
+    }
+
+    if result.len() < 3 { Vec::new() } else { result }
+}
+
+/// Subtracts a convex polygon from another polygon.
+/// The remainder is split into one piece per clip edge: outside that edge, inside all previous ones.
+/// # Returns the non-degenerate remaining pieces, or the subject unchanged if they don't overlap.
+pub fn subtract_convex_polygon(subject: &[Vec2], clip: &[Vec2]) -> Vec<Polygon> {
+    if subject.len() < 3 || clip.len() < 3 {
+        return vec![subject.to_vec()];
+    }
+    
+    // make the clip polygon CCW so "inside" is always to the left
+    let mut clip = clip.to_vec();
+    if polygon_area(&clip) < 0.0 {
+        clip.reverse();
+    }
+    
+    let mut pieces = Vec::new();
+    let mut remaining = subject.to_vec();
+    for i in 0..clip.len() {
+        if remaining.len() < 3 {
+            break;
+        }
+        let a = clip[i];
+        let b = clip[(i + 1) % clip.len()];
+        
+        // part outside this edge is kept, part inside continues to the next edge
+        let outside = clip_polygon_half_plane(&remaining, b, a);
+        if outside.len() >= 3 && polygon_area(&outside).abs() > 1e-4 {
+            pieces.push(outside);
+        }
+        remaining = clip_polygon_half_plane(&remaining, a, b);
+    }
+    
+    pieces
+}
+
+/// Elongation of a polygon: the long side of its oriented bounding box over the short side.
+/// 
+/// # Returns
+/// 1 for a square, larger the thinner the polygon. Infinite for polygons without area.
+pub fn obb_aspect_ratio(polygon: &[Vec2]) -> f32 {
+    let obb = super::ops::oriented_bounding_box(polygon);
+    if obb.len() != 4 {
+        return f32::INFINITY;
+    }
+    let (a, b) = (obb[0].distance(obb[1]), obb[1].distance(obb[2]));
+    a.max(b) / a.min(b).max(f32::EPSILON)
+}
+
+/// Calculates shortest distance from a point to a line segment 2D
+/// 
+/// # Returns
+/// The PERPENDICULAR distance from `point` to the line segment defined by `line_start` and `line_end`.
+pub fn point_to_line_distance(point: Vec2, line_start: Vec2, line_end: Vec2) -> f32 {
+    let line_vec = line_end - line_start;
+    let point_vec = point - line_start;
+    let line_len = line_vec.length();
+    
+    if line_len < f32::EPSILON {
+        return point_vec.length();
+    }
+    
+    let t = (point_vec.dot(line_vec) / line_len.powi(2)).clamp(0.0, 1.0);
+    let projection = line_start + line_vec * t;
+    point.distance(projection)
+}
+
+/// Calculates the shortest distance between the outlines of two polygons.
+/// Touching or shared edges give 0, overlap is not detected (expects disjoint plots).
+/// 
+/// # Returns
+/// The minimum vertex-to-edge distance in either direction.
+pub fn polygon_distance(a: &[Vec2], b: &[Vec2]) -> f32 {
+    let one_way = |from: &[Vec2], to: &[Vec2]| {
+        let mut min = f32::MAX;
+        for &point in from {
+            for i in 0..to.len() {
+                min = min.min(point_to_line_distance(point, to[i], to[(i + 1) % to.len()]));
+            }
+        }
+        min
+    };
+    one_way(a, b).min(one_way(b, a))
+}
+
+/// Unions polygons given as index loops into `positions`, for polygons that share whole edges like adjacent Voronoi cells.
+/// Every loop is oriented CCW first, so an edge shared by two polygons shows up once in each direction and cancels.
+/// The remaining edges are walked into the outline.
+/// 
+/// # Returns
+/// The outline as a CCW index loop, or `None` when the union is not a single simple loop (disconnected, holes, pinched vertices).
+pub fn union_index_polygons(polygons: &[&[usize]], positions: &[Vec2]) -> Option<Vec<usize>> {
+    let mut edges: HashSet<(usize, usize)> = HashSet::new();
+    for polygon in polygons {
+        let mut indices = polygon.to_vec();
+        let points: Polygon = indices.iter().map(|&idx| positions[idx]).collect();
+        if polygon_area(&points) < 0.0 {
+            indices.reverse();
+        }
+
+        for i in 0..indices.len() {
+            let (a, b) = (indices[i], indices[(i + 1) % indices.len()]);
+            if !edges.remove(&(b, a)) {
+                edges.insert((a, b));
+            }
+        }
+    }
+
+    // a vertex with two outgoing edges means two loops touch there
+    let mut next: HashMap<usize, usize> = HashMap::new();
+    for &(a, b) in &edges {
+        if next.insert(a, b).is_some() {
+            return None;
+        }
+    }
+
+    let start = edges.iter().map(|&(a, _)| a).min()?;
+    let mut outline = vec![start];
+    let mut current = *next.get(&start)?;
+    while current != start {
+        if outline.len() > edges.len() {
+            return None;
+        }
+        outline.push(current);
+        current = *next.get(&current)?;
+    }
+
+    // leftover edges belong to a second loop, e.g. a hole
+    if outline.len() != edges.len() {
+        return None;
+    }
+    Some(outline)
+}
+
+/// Triangulates a simple polygon, convex or concave, by ear clipping.
+/// Collinear vertices are clipped as zero-area ears when no proper ear is left.
+/// 
+/// # Returns
+/// Index triples into `polygon`, CCW in the polygon's plane whatever the input winding. Empty for fewer than 3 vertices.
+pub fn triangulate_polygon(polygon: &[Vec2]) -> Vec<[usize; 3]> {
+    let n = polygon.len();
+    if n < 3 {
+        return Vec::new();
+    }
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    if polygon_area(&polygon.to_vec()) < 0.0 {
+        remaining.reverse();
+    }
+
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+    // strictly inside, points on the ear's edges don't block it
+    let inside = |p: Vec2, a: Vec2, b: Vec2, c: Vec2| cross(a, b, p) > 0.0 && cross(b, c, p) > 0.0 && cross(c, a, p) > 0.0;
+
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let corner = |i: usize| (remaining[(i + m - 1) % m], remaining[i], remaining[(i + 1) % m]);
+
+        let ear = (0..m).find(|&i| {
+            let (a, b, c) = corner(i);
+            let (pa, pb, pc) = (polygon[a], polygon[b], polygon[c]);
+            cross(pa, pb, pc) > 0.0 && remaining.iter()
+                .filter(|&&j| j != a && j != b && j != c)
+                .all(|&j| !inside(polygon[j], pa, pb, pc))
+        });
+        let collinear = || (0..m).find(|&i| {
+            let (a, b, c) = corner(i);
+            cross(polygon[a], polygon[b], polygon[c]).abs() <= f32::EPSILON
+        });
+        let i = ear.or_else(collinear).unwrap_or(0);
+
+        let (a, b, c) = corner(i);
+        triangles.push([a, b, c]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
@@ -1,15 +1,21 @@
 use bevy::prelude::*;
+use std::collections::HashMap;
 use spade::{DelaunayTriangulation, Point2, Triangulation as _, LastUsedVertexHintGenerator};
 
 use crate::systems::mesh::SkeletonData;
-use super::utils::{calculate_circumcenter, point_in_polygon};
+use super::utils::{calculate_weighted_circumcenter, clip_polygon, polygon_area};
 
 /// Constructs a Voronoi diagram from a set of generator points within a boundary polygon,
+/// every cell clipped to the boundary so the cells fill it edge to edge.
+/// With `weights` (one per generator, missing ones count as `GENERATOR_WEIGHT`) this is a power diagram:
+/// heavier generators own larger cells. Equal weights give the plain diagram.
 /// 
 /// # Returns
 /// A SkeletonData struct, the universal datatype
+#[allow(clippy::collapsible_if)]
 pub fn vpoly(
     generator_points: Vec<Vec3>, 
+    weights: Option<&[f32]>,
     boundary_polygon: &[Vec2], 
     merge_threshold: f32
 ) -> SkeletonData {
@@ -20,9 +26,17 @@ pub fn vpoly(
         .map(|p| Point2::new(p.x as f64, p.z as f64))
         .collect();
     
+    // power weights relative to the lightest generator, so equal weights are all zero
+    let generator_weight = |idx: usize| weights.and_then(|w| w.get(idx)).copied().unwrap_or(crate::config::GENERATOR_WEIGHT);
+    let lightest = (0..d_points.len()).map(generator_weight).fold(f32::INFINITY, f32::min);
+    let weighted = (0..d_points.len()).any(|idx| generator_weight(idx) > lightest);
+    
     let mut triangulation: DelaunayTriangulation<Point2<f64>, (), (), (), LastUsedVertexHintGenerator> = DelaunayTriangulation::new();
-    for point in d_points.iter() {
-        triangulation.insert(*point).ok();
+    let mut vertex_power = vec![0.0; d_points.len()];
+    for (idx, point) in d_points.iter().enumerate() {
+        if let Ok(vertex) = triangulation.insert(*point) {
+            vertex_power[vertex.index()] = ((generator_weight(idx) - lightest) * crate::config::GENERATOR_WEIGHT_SCALE) as f64;
+        }
     }
     
     // calculate circumcenters
@@ -37,7 +51,7 @@ pub fn vpoly(
         let p2 = v2.position();
         let p3 = v3.position();
         
-        let circumcenter = calculate_circumcenter(p1, p2, p3);
+        let circumcenter = calculate_weighted_circumcenter(p1, p2, p3, [v1, v2, v3].map(|v| vertex_power[v.fix().index()]));
 
         // validate that circumcenter is within reasonable bounds
         let circumcenter_x = circumcenter.0 as f32;
@@ -91,6 +105,7 @@ pub fn vpoly(
     // build separate Voronoi cells
     // group circumcenters by Voronoi points
     let mut cells = Vec::new();
+    let mut cell_generators = Vec::new();
     
     // build generator -> circumcenter mapping
     let mut voronoi_circumcenters = vec![Vec::new(); d_points.len()];
@@ -112,18 +127,19 @@ pub fn vpoly(
         }
     }
     
+    // cells are clipped to the boundary, the new corners are appended after the circumcenters
+    // corners closer than the merge threshold are shared, so neighbouring clipped cells still meet on common points
+    let mut points = circumcenters;
+    let circumcenter_count = points.len();
+    let weld_distance = merge_threshold.max(1e-4);
+    
     // create ordered Voronoi cells
     for (generator_idx, circumcenter_indices) in voronoi_circumcenters.iter().enumerate() {
         if circumcenter_indices.len() < 3 { continue; } // skip degenerate cells
         
-        // skip if generator is outside boundary polygon
-        let gen_pos = Vec2::new(d_points[generator_idx].x as f32, d_points[generator_idx].y as f32);
-        if !point_in_polygon(&gen_pos, boundary_polygon) {
-            continue;
-        }
-        
         // boundary detection
         // check if any face containing this generator is on the boundary
+        // those cells are open, only their finite part is known
         let mut is_boundary = false;
         for face in triangulation.inner_faces() {
             let [v1, v2, v3] = face.vertices();
@@ -139,38 +155,119 @@ pub fn vpoly(
                 if is_boundary { break; }
             }
         }
-        if is_boundary { continue; }
         
         // additional: check filter cells with circumcenters at extreme positions
         // for those very problematic cells
         let has_extreme_circumcenters = circumcenter_indices.iter().any(|&circumcenter_idx| {
-            let circumcenter = &circumcenters[circumcenter_idx];
+            let circumcenter = &points[circumcenter_idx];
             let dist_from_origin = (circumcenter.x.powi(2) + circumcenter.z.powi(2)).sqrt();
             dist_from_origin > crate::config::CANVAS_WIDTH * 3.0 // threshold
         });
         if has_extreme_circumcenters { continue; }
         
-        let generator_pos = Vec2::new(d_points[generator_idx].x as f32, d_points[generator_idx].y as f32);
-        
         // sort circumcenters by angle around generator
+        // an open cell's generator can lie outside its finite part, as can a weighted one, those sort around their own centre
+        let center = if is_boundary || weighted {
+            circumcenter_indices.iter()
+                .map(|&idx| Vec2::new(points[idx].x, points[idx].z))
+                .sum::<Vec2>() / circumcenter_indices.len() as f32
+        } else {
+            Vec2::new(d_points[generator_idx].x as f32, d_points[generator_idx].y as f32)
+        };
         let mut sorted_circumcenters = circumcenter_indices.clone();
         sorted_circumcenters.sort_by(|&a, &b| {
-            let a_pos = Vec2::new(circumcenters[a].x, circumcenters[a].z);
-            let b_pos = Vec2::new(circumcenters[b].x, circumcenters[b].z);
-            let angle_a = (a_pos.y - generator_pos.y).atan2(a_pos.x - generator_pos.x);
-            let angle_b = (b_pos.y - generator_pos.y).atan2(b_pos.x - generator_pos.x);
+            let a_pos = Vec2::new(points[a].x, points[a].z);
+            let b_pos = Vec2::new(points[b].x, points[b].z);
+            let angle_a = (a_pos.y - center.y).atan2(a_pos.x - center.x);
+            let angle_b = (b_pos.y - center.y).atan2(b_pos.x - center.x);
             angle_a.partial_cmp(&angle_b).unwrap()
         });
         
-        cells.push(sorted_circumcenters);
+        // clip to the boundary, the cell is convex so it clips the boundary rather than the other way round
+        let cell_polygon: Vec<Vec2> = sorted_circumcenters.iter()
+            .map(|&idx| Vec2::new(points[idx].x, points[idx].z))
+            .collect();
+        let mut clipped = clip_polygon(boundary_polygon, &cell_polygon);
+        if clipped.len() < 3 || polygon_area(&clipped).abs() < crate::config::MIN_CLIPPED_CELL_AREA {
+            continue;
+        }
+        if polygon_area(&clipped) < 0.0 {
+            clipped.reverse(); // same winding as the sorted cells
+        }
+        
+        // reuse the cell's own circumcenters and earlier clip corners, append the rest
+        let mut cell: Vec<usize> = Vec::with_capacity(clipped.len());
+        for corner in clipped {
+            let existing = sorted_circumcenters.iter().copied()
+                .chain(circumcenter_count..points.len())
+                .find(|&idx| Vec2::new(points[idx].x, points[idx].z).distance(corner) < weld_distance);
+            let idx = existing.unwrap_or_else(|| {
+                points.push(Vec3::new(corner.x, 0.0, corner.y));
+                points.len() - 1
+            });
+            if cell.last() != Some(&idx) {
+                cell.push(idx);
+            }
+        }
+        if cell.len() > 1 && cell.first() == cell.last() {
+            cell.pop();
+        }
+        if cell.len() < 3 { continue; }
+        
+        // a cell inside the boundary keeps its original order
+        let untouched = cell.len() == sorted_circumcenters.len() && cell.iter().all(|idx| sorted_circumcenters.contains(idx));
+        cells.push(if untouched { sorted_circumcenters } else { cell });
+        cell_generators.push(generator_idx);
     }
 
+    // cells sharing an edge, clipped cells share their boundary crossings too
+    let adjacency = cell_adjacency(&cells);
+
     SkeletonData {
         generator_points,
-        points: circumcenters,
+        generator_weights: weights.map(<[f32]>::to_vec).unwrap_or_default(),
+        points,
         cells,
+        cell_adjacency: adjacency,
+        cell_generators,
         road_path: Vec::new(),
+        road_widths: Vec::new(),
+        road_generators: 0..0,
+        boundary_generators: 0..0,
+        relaxation_steps: 0,
         boundary_polygon: boundary_polygon.to_vec(),
         boundary_vertex_offsets: vec![Vec2::ZERO; boundary_polygon.len()],
     }
-}
\ No newline at end of file
+}
+
+/// Neighbours of every cell, two cells are adjacent when they share a Voronoi edge:
+/// the same two consecutive circumcenter indices, in either direction.
+/// 
+/// # Returns
+/// One sorted neighbour list per cell, parallel to `cells`.
+pub fn cell_adjacency(cells: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut edge_cells: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (cell_idx, cell) in cells.iter().enumerate() {
+        for i in 0..cell.len() {
+            let (a, b) = (cell[i], cell[(i + 1) % cell.len()]);
+            edge_cells.entry((a.min(b), a.max(b))).or_default().push(cell_idx);
+        }
+    }
+
+    let mut adjacency = vec![Vec::new(); cells.len()];
+    for sharing in edge_cells.values() {
+        for (i, &a) in sharing.iter().enumerate() {
+            for &b in &sharing[i + 1..] {
+                if a != b {
+                    adjacency[a].push(b);
+                    adjacency[b].push(a);
+                }
+            }
+        }
+    }
+    for neighbours in adjacency.iter_mut() {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+    adjacency
+}
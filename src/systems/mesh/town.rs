@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy::pbr::NotShadowCaster;
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
 
@@ -21,14 +23,219 @@ pub struct Block {
     pub size_chaos: f32,
     pub empty_prob: f32,
     pub id: Option<u32>,
+    pub generator: Option<usize>, // generator that owns this block's cell
+    pub alleys: Vec<poly::subdivision::AlleyPolygon>, // gaps left between plots by alley cuts
 }
 
 #[derive(Component)]
 pub struct Building {
     pub id: u32,
     pub footprint: crate::systems::mesh::Polygon,
+    pub wall_height: f32,
+    pub roof_height: f32, // ridge height above the walls, 0 for a flat roof
+    pub floors: u32,      // whole floors that fit in wall_height
+    pub population: u32, // estimated occupants
+    pub color: Color,    // own wall colour, restored when an attribute overlay is switched off
+    pub mesh: Handle<Mesh>,     // full detail walls and roof
+    pub lod_mesh: Handle<Mesh>, // box drawn instead past the lod distance
 }
 
+// footprints are a darker, bluer shade of the building colour
+pub fn footprint_color(color: Color) -> Color {
+    let color = color.to_srgba();
+    Color::srgb(color.red * 0.8, color.green * 0.8, color.blue)
+}
+
+// walls plus the roof on top, flat when roof_height is 0
+// with an overhang the roof sits on a thin slab of the eave outline, its underside is the soffit
+// openings_seed places the windows and door, see openings_seed()
+pub fn building_mesh(footprint: &crate::systems::mesh::Polygon, wall_height: f32, roof_height: f32, params: &Params, openings_seed: u64) -> Mesh {
+    let openings = poly::mesh_gen::WallOpenings { window_density: params.window_density, seed: openings_seed };
+    let mut mesh = poly::mesh_gen::polygon_to_building(footprint, wall_height, params.base_embed, params.uv_mode, params.uv_tile_size, params.texture_meters_per_tile, params.floor_height, Some(openings));
+    if footprint.len() < 3 {
+        return mesh;
+    }
+
+    let mut roof_parts = Vec::new();
+    let mut eaves = footprint.clone();
+    let mut eave_height = wall_height;
+    if params.eave_overhang > 0.0 {
+        eaves = poly::mesh_gen::eave_outline(footprint, params.eave_overhang);
+        eave_height += crate::config::EAVE_THICKNESS;
+        // slab from the wall top up to the eaves, base_embed below 0 lifts its base
+        roof_parts.push(poly::mesh_gen::polygon_to_building(&eaves, eave_height, -wall_height, params.uv_mode, params.uv_tile_size, params.texture_meters_per_tile, 0.0, None));
+    }
+    if roof_height > 0.0 {
+        match params.roof_style {
+            RoofStyle::Flat => {}
+            RoofStyle::Gable => roof_parts.push(poly::mesh_gen::polygon_to_gable_roof(&eaves, eave_height, roof_height, params.smooth_normals)),
+            RoofStyle::Hip => roof_parts.push(poly::mesh_gen::polygon_to_hip_roof(&eaves, eave_height, roof_height, params.smooth_normals)),
+        }
+    }
+    // every part carries the same position, normal and uv formats as the walls, so merging can't fail
+    for part in roof_parts {
+        mesh.merge(&part).expect("roof parts share the wall mesh's vertex attributes");
+    }
+    mesh
+}
+
+// settlement core, the boundary centroid unless one was placed by hand
+pub fn settlement_core(params: &Params, boundary_polygon: &[Vec2]) -> Vec2 {
+    params.core_point.unwrap_or_else(|| {
+        if boundary_polygon.len() >= 3 {
+            let boundary = boundary_polygon.to_vec();
+            poly::utils::polygon_centroid(&boundary, poly::utils::polygon_area(&boundary))
+        } else {
+            Vec2::ZERO
+        }
+    })
+}
+
+// wall height multiplier, highest at the core and back to 1 at the boundary's farthest corner
+// a falloff of 0 is exactly 1 everywhere, so seeds keep their heights
+#[derive(Clone, Copy)]
+pub struct HeightGradient {
+    core: Vec2,
+    radius: f32,
+    falloff: f32,
+}
+
+impl HeightGradient {
+    pub fn new(params: &Params, boundary_polygon: &[Vec2]) -> Self {
+        let core = settlement_core(params, boundary_polygon);
+        let radius = boundary_polygon.iter().map(|v| v.distance(core)).fold(0.0, f32::max);
+        Self { core, radius: radius.max(f32::EPSILON), falloff: params.center_height_falloff }
+    }
+
+    pub fn scale(&self, footprint: &crate::systems::mesh::Polygon) -> f32 {
+        if self.falloff <= 0.0 {
+            return 1.0;
+        }
+        let centroid = poly::utils::polygon_centroid(footprint, poly::utils::polygon_area(footprint));
+        1.0 + self.falloff * (1.0 - (centroid.distance(self.core) / self.radius).min(1.0))
+    }
+}
+
+// window/door rng of one building, independent of the block rng
+// a building keeps its openings as long as the town seed and its id stay the same
+pub fn openings_seed(seed: u64, building_id: u32) -> u64 {
+    seed.wrapping_add(building_id as u64)
+}
+
+// roof height for the next building, only drawn for pitched roofs so flat towns keep their seeds
+fn draw_roof_height(params: &Params, rng: &mut StdRng) -> f32 {
+    if params.roof_style == RoofStyle::Flat {
+        0.0
+    } else {
+        rng.random_range(params.min_roof_height..=params.max_roof_height)
+    }
+}
+
+// whole floors of floor_height that fit in the walls, 0 when the walls are lower than one floor
+pub fn floor_count(wall_height: f32, floor_height: f32) -> u32 {
+    (wall_height / floor_height.max(f32::EPSILON)).floor() as u32
+}
+
+// occupants = floor area * storeys * density
+// every building has at least one storey
+pub fn estimate_population(footprint: &crate::systems::mesh::Polygon, wall_height: f32, floor_height: f32) -> u32 {
+    let storeys = floor_count(wall_height, floor_height).max(1) as f32;
+    let area = poly::utils::polygon_area(footprint).abs();
+    (area * storeys * crate::config::OCCUPANT_DENSITY).round() as u32
+}
+
+// markers for the mesh children of a building
+#[derive(Component)]
+pub struct BuildingFootprint;
+
+#[derive(Component)]
+pub struct BuildingMesh;
+
+// box standing in for the BuildingMesh of a distant building, never exported
+#[derive(Component)]
+pub struct BuildingMeshLod;
+
+// on a BuildingMesh hidden by the lod swap, exports still treat it as visible
+#[derive(Component)]
+pub struct LodCulled;
+
+// lod box height, walls plus half the roof so the silhouette keeps its bulk
+fn lod_height(wall_height: f32, roof_height: f32) -> f32 {
+    wall_height + roof_height * 0.5
+}
+
+// road surface, a flat ribbon or the corridor with curbs, child of the town
+#[derive(Component)]
+pub struct RoadMesh;
+
+// interior room of a large building, child of the building
+#[derive(Component)]
+pub struct Room {
+    pub polygon: crate::systems::mesh::Polygon,
+}
+
+// paved alley gap, child of its block
+#[derive(Component)]
+pub struct AlleyMesh;
+
+// previous generation kept around semi-transparent, at most one at a time
+// its blocks and buildings lose their components so picking/export/regen skip them
+#[derive(Component)]
+pub struct GhostTown;
+
+// every entity below a ghost town
+#[derive(Component)]
+pub struct Ghost;
+
+// queries needed to turn a town into a ghost
+#[derive(SystemParam)]
+pub struct GhostTowns<'w, 's> {
+    ghosts: Query<'w, 's, Entity, With<GhostTown>>,
+    children: Query<'w, 's, &'static Children>,
+    mesh_materials: Query<'w, 's, &'static MeshMaterial3d<StandardMaterial>>,
+}
+
+impl GhostTowns<'_, '_> {
+    // replaces the current ghost with this town
+    // every building has its own material, so they are faded in place
+    fn convert(&self, commands: &mut Commands, materials: &mut Assets<StandardMaterial>, town: Entity) {
+        self.despawn_all(commands);
+
+        commands.entity(town).remove::<Town>().insert(GhostTown);
+        for entity in self.children.iter_descendants(town) {
+            if let Ok(material) = self.mesh_materials.get(entity)
+                && let Some(material) = materials.get_mut(&material.0) {
+                material.base_color.set_alpha(crate::config::GHOST_ALPHA);
+                material.alpha_mode = AlphaMode::Blend;
+            }
+            commands.entity(entity)
+                .remove::<(Block, Building, Room)>()
+                .insert((Ghost, NotShadowCaster));
+        }
+    }
+
+    fn despawn_all(&self, commands: &mut Commands) {
+        for entity in self.ghosts.iter() {
+            commands.entity(entity).try_despawn();
+        }
+    }
+}
+
+// ghost removed as soon as the toggle is switched off
+pub fn despawn_disabled_ghosts(
+    mut commands: Commands,
+    params: Res<Params>,
+    ghosts: Query<Entity, With<GhostTown>>,
+) {
+    if params.keep_ghost {
+        return;
+    }
+    for entity in ghosts.iter() {
+        commands.entity(entity).try_despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn generate_town(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
@@ -36,6 +243,9 @@ pub fn generate_town(
     seed: u64,
     params: &Params,
     data: &mut ResMut<SkeletonData>,
+    overrides: &BlockOverrides,
+    block_seeds: &BlockSeeds,
+    wall_textures: &WallTextures,
     is_3d: bool,
 ) {
     let points = &data.points;
@@ -46,145 +256,502 @@ pub fn generate_town(
     }
 
     // spawn town entity
-    let town_entity = commands.spawn(Town {
+    // the Town itself is inserted once the buildings and their population exist
+    let town_entity = commands.spawn((Transform::default(), Visibility::default())).id();
+    let mut town_population = 0;
+
+    let mut building_id = 0;
+    let mut adjacency = Vec::new();
+
+    let polygonal_regions = block_regions(data, params, seed);
+    let heights = HeightGradient::new(params, &data.boundary_polygon);
+
+    // create block entities for each polygonal region
+    for (block_idx, block_polygon) in polygonal_regions {
+        let generator = data.cell_generators.get(block_idx).copied();
+        let Some(block) = spawn_block(commands, meshes, materials, seed, block_seeds.block_seed(seed, block_idx, &block_polygon), block_idx, block_polygon, generator, params, overrides, wall_textures, is_3d, building_id, heights) else {
+            continue;
+        };
+        commands.entity(town_entity).add_children(&[block.entity]);
+
+        town_population += block.population;
+        building_id += block.plots.len() as u32;
+        adjacency.extend(block_adjacency(&block.plots, adjacency_gap(params)));
+    } 
+
+    // road corridor with curbs, part of the scene so it exports with the buildings
+    let road_path = poly::point_gen::smooth_road_path(&data.road_path, params.road_smoothing);
+    let road_widths = data.smoothed_road_widths(params.road_smoothing);
+    if params.road_curbs {
+        let corridor = road_corridor_quads(&road_path, &road_widths);
+        if !corridor.is_empty() {
+            let road_entity = commands.spawn((
+                RoadMesh,
+                Mesh3d(meshes.add(poly::mesh_gen::road_corridor_to_mesh(&corridor, params.curb_height, crate::config::CURB_WIDTH))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::srgb(0.35, 0.35, 0.38),
+                    perceptual_roughness: 0.9,
+                    ..default()
+                })),
+                Transform::default(),
+                Visibility::Visible,
+            )).id();
+            commands.entity(town_entity).add_children(&[road_entity]);
+        }
+    } else if road_path.len() >= 2 {
+        // flat asphalt ribbon along the road path
+        let road_entity = commands.spawn((
+            RoadMesh,
+            Mesh3d(meshes.add(poly::mesh_gen::road_path_to_mesh(&road_path, &road_widths))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(0.12, 0.12, 0.13),
+                perceptual_roughness: 0.95,
+                ..default()
+            })),
+            Transform::default(),
+            Visibility::Visible,
+        )).id();
+        commands.entity(town_entity).add_children(&[road_entity]);
+    }
+
+    commands.insert_resource(BuildingAdjacency { edges: adjacency });
+    commands.entity(town_entity).insert(Town {
         name: "My Town".to_string(),
-        population: 100,
+        population: town_population,
         seed,
-    }).id();
+    });
+}
 
-    let mut building_id = 0;
+// what spawn_block produced, the caller parents the block and merges the rest into the town
+struct SpawnedBlock {
+    entity: Entity,
+    plots: Vec<(u32, crate::systems::mesh::Polygon)>, // building id + footprint
+    population: u32,
+}
+
+// plan one block and spawn it with its buildings, None when the block is dropped
+// building ids are handed out from first_building_id
+#[allow(clippy::too_many_arguments)]
+fn spawn_block(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    seed: u64,
+    block_seed: u64,
+    block_idx: usize,
+    block_polygon: crate::systems::mesh::Polygon,
+    generator: Option<usize>,
+    params: &Params,
+    overrides: &BlockOverrides,
+    wall_textures: &WallTextures,
+    is_3d: bool,
+    first_building_id: u32,
+    heights: HeightGradient,
+) -> Option<SpawnedBlock> {
+    let mut block_rng = StdRng::seed_from_u64(block_seed);
+    let (buildings, alleys) = plan_block(block_idx, &block_polygon, params, overrides, &mut block_rng)?;
+
+    let block = Block {
+        polygon: block_polygon,
+        min_sq: params.min_sq,
+        grid_chaos: params.grid_chaos,
+        size_chaos: params.size_chaos,
+        empty_prob: params.empty_prob,
+        id: Some(block_idx as u32),
+        generator,
+        alleys: alleys.clone(),
+    };
+
+    let block_entity = commands.spawn((block, Transform::default(), Visibility::default())).id();
+
+    // collect building entities for this block
+    let mut building_entities = Vec::new();
+    let mut block_plots: Vec<(u32, crate::systems::mesh::Polygon)> = Vec::new();
+    let mut block_population = 0;
+
+    // create building entities
+    for (building_id, building_poly) in (first_building_id..).zip(buildings) {
+        // apply param values
+        let wall_height = block_rng.random_range(params.min_wall_height..params.max_wall_height) * heights.scale(&building_poly);
+        let roof_height = draw_roof_height(params, &mut block_rng);
+
+        // generate meshes
+        let footprint_mesh = poly::mesh_gen::polygon_to_layer_zero(&building_poly, params.uv_mode, params.uv_tile_size);
+        let building_3d_mesh = building_mesh(&building_poly, wall_height, roof_height, params, openings_seed(seed, building_id));
+
+        let footprint_handle = meshes.add(footprint_mesh);
+        let building_3d_handle = meshes.add(building_3d_mesh);
+        let lod_handle = meshes.add(poly::mesh_gen::polygon_to_building_lod(&building_poly, lod_height(wall_height, roof_height), 1));
+
+        // color variations
+        let base_r = (0.8 + block_rng.random_range(-0.05_f32..0.05_f32)).clamp(0.0, 1.0);
+        let base_g = (0.8 + block_rng.random_range(-0.05_f32..0.05_f32)).clamp(0.0, 1.0);
+        let base_b = (0.9 + block_rng.random_range(-0.05_f32..0.05_f32)).clamp(0.0, 1.0);
+
+        let color = Color::srgb(base_r, base_g, base_b);
+
+        // footprint material
+        let footprint_material = materials.add(StandardMaterial {
+            base_color: footprint_color(color),
+            alpha_mode: AlphaMode::Opaque,
+            ..default()
+        });
+
+        // 3D building material, the colour tints the wall texture when one is used
+        let building_3d_material = materials.add(StandardMaterial {
+            base_color: color,
+            base_color_texture: if params.wall_textures { wall_textures.pick(seed, building_id) } else { None },
+            alpha_mode: AlphaMode::Opaque,
+            ..default()
+        });
+
+        let rooms = if params.show_interiors && poly::utils::polygon_area(&building_poly).abs() >= params.interior_min_area {
+            plan_rooms(&building_poly, block_seed.wrapping_add(building_id as u64))
+        } else {
+            Vec::new()
+        };
+
+        block_plots.push((building_id, building_poly.clone()));
+        let population = estimate_population(&building_poly, wall_height, params.floor_height);
+        block_population += population;
+
+        // create main building entity (parent)
+        let building_entity = commands.spawn((
+            Building {
+                id: building_id,
+                footprint: building_poly,
+                wall_height,
+                roof_height,
+                floors: floor_count(wall_height, params.floor_height),
+                population,
+                color,
+                mesh: building_3d_handle.clone(),
+                lod_mesh: lod_handle.clone(),
+            },
+            Transform::default(),
+        )).id();
 
-    // convert Voronoi cells to polygonal regions and shrink road cells
-    let mut polygonal_regions: Vec<Vec<Vec2>> = data.cells.iter()
-        .map(|cell| {
-            cell.iter()
+        // create footprint entity
+        let footprint_entity = commands.spawn((
+            BuildingFootprint,
+            Mesh3d(footprint_handle),
+            MeshMaterial3d(footprint_material),
+            Transform::default(),
+            Visibility::Visible,
+        )).id();
+
+        // create 3D building entity
+        let building_3d_entity = commands.spawn((
+            BuildingMesh,
+            Mesh3d(building_3d_handle),
+            MeshMaterial3d(building_3d_material.clone()),
+            Transform::default(),
+            if is_3d { Visibility::Visible } else { Visibility::Hidden },
+        )).id();
+
+        // lod box, shares the material so overlays and ghosting apply to it too
+        // shown by update_building_lod
+        let lod_entity = commands.spawn((
+            BuildingMeshLod,
+            Mesh3d(lod_handle),
+            MeshMaterial3d(building_3d_material),
+            Transform::default(),
+            Visibility::Hidden,
+        )).id();
+
+        // add mesh entities as children of building
+        commands.entity(building_entity).add_children(&[footprint_entity, building_3d_entity, lod_entity]);
+
+        // interior rooms for the cutaway view, a building that doesn't split stays one room without walls
+        if rooms.len() > 1 {
+            let room_entities: Vec<Entity> = rooms.into_iter()
+                .map(|polygon| commands.spawn(Room { polygon }).id())
+                .collect();
+            commands.entity(building_entity).add_children(&room_entities);
+        }
+
+        building_entities.push(building_entity);
+    }
+
+    // add building entities as children of block entity 
+    commands.entity(block_entity).add_children(&building_entities);
+
+    // alley floors, a shade lighter than the road so they read as pedestrian only
+    // sunk just below the footprints, the pushed plot edges only approximate the gap
+    if params.alley_paths && !alleys.is_empty() {
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.55, 0.5, 0.44),
+            perceptual_roughness: 0.95,
+            ..default()
+        });
+        let alley_entities: Vec<Entity> = alleys.iter()
+            .map(|alley| commands.spawn((
+                AlleyMesh,
+                Mesh3d(meshes.add(poly::mesh_gen::polygon_to_layer_zero(&alley.quad, params.uv_mode, params.uv_tile_size))),
+                MeshMaterial3d(material.clone()),
+                Transform::from_xyz(0.0, -0.005, 0.0),
+                Visibility::Visible,
+            )).id())
+            .collect();
+        commands.entity(block_entity).add_children(&alley_entities);
+    }
+
+    Some(SpawnedBlock { entity: block_entity, plots: block_plots, population: block_population })
+}
+
+// convert Voronoi cells to polygonal regions (merged blocks included) and shrink road cells
+// regions are keyed by the block's lowest cell index
+pub fn block_regions(data: &SkeletonData, params: &Params, seed: u64) -> Vec<(usize, crate::systems::mesh::Polygon)> {
+    let mut polygonal_regions: Vec<(usize, Vec<Vec2>)> = data.merged_blocks(params, seed).into_iter()
+        .map(|(cells, outline)| {
+            let polygon = outline.iter()
                 .map(|&idx| Vec2::new(data.points[idx].x, data.points[idx].z))
-                .collect()
+                .collect();
+            (cells[0], polygon)
         })
         .collect();
     
     // shrink road generator cells away from road line to create corridor
-    let road_generator_count = poly::point_gen::generate_road_generators(&data.road_path).len();
-    for i in 0..polygonal_regions.len().min(road_generator_count) {
-        for j in 0..(data.road_path.len() - 1) {
-            let road_start = Vec2::new(data.road_path[j].x, data.road_path[j].z);
-            let road_end = Vec2::new(data.road_path[j + 1].x, data.road_path[j + 1].z);
+    // road cells are never merged, so a road block is keyed by its own cell
+    let road_path = poly::point_gen::smooth_road_path(&data.road_path, params.road_smoothing);
+    let road_widths = data.smoothed_road_widths(params.road_smoothing);
+    for (_, region) in polygonal_regions.iter_mut().filter(|(cell_idx, _)| road_path.len() >= 2 && data.is_road_cell(*cell_idx)) {
+        for j in 0..(road_path.len() - 1) {
+            let road_start = Vec2::new(road_path[j].x, road_path[j].z);
+            let road_end = Vec2::new(road_path[j + 1].x, road_path[j + 1].z);
             
             if road_start.distance(road_end) > 0.1 {
-                polygonal_regions[i] = poly::subdivision::push_polygon_from_line(
-                    &polygonal_regions[i], 
+                // segment width is the mean of its two end points
+                *region = poly::subdivision::push_polygon_from_line(
+                    region, 
                     road_start, 
                     road_end, 
-                    crate::config::ROAD_WIDTH * 0.5
+                    (road_widths[j] + road_widths[j + 1]) * 0.25
                 );
             }
         }
     }
 
-    // create block entities for each polygonal region
-    for (block_idx, block_polygon) in polygonal_regions.iter().enumerate() {
-
-        let block = Block {
-            polygon: block_polygon.clone(),
-            min_sq: params.min_sq,
-            grid_chaos: params.grid_chaos,
-            size_chaos: params.size_chaos,
-            empty_prob: params.empty_prob,
-            id: Some(block_idx as u32),
-        };
+    polygonal_regions
+}
 
-        let block_entity = commands.spawn(block.clone()).id();
-        commands.entity(town_entity).add_children(&[block_entity]);
-
-        // subdivide block into buildings
-        let mut block_rng = StdRng::seed_from_u64(seed.wrapping_add(block_idx as u64));
-        let buildings = poly::subdivision::subdivide_to_plots(
-            &block_polygon,
-            block.min_sq,
-            block.grid_chaos,
-            block.size_chaos,
-            block.empty_prob,
-            0,
-            &mut block_rng,
-            params.max_recursion_depth,
-            params.alley_chance,
-            params.alley_width,
-        );
-        
+// building plots and alley gaps for one block, None when the block is dropped entirely
+// per-block overrides take precedence over the global params
+pub fn plan_block(
+    block_idx: usize,
+    block_polygon: &crate::systems::mesh::Polygon,
+    params: &Params,
+    overrides: &BlockOverrides,
+    rng: &mut StdRng,
+) -> Option<(Vec<crate::systems::mesh::Polygon>, Vec<poly::subdivision::AlleyPolygon>)> {
+    // street frontage, the block is pulled back from its cell edges before it is cut up
+    // a block the setback would shrink below one plot keeps its outline
+    let setback = (params.block_setback > 0.0)
+        .then(|| poly::subdivision::polygon_inset(block_polygon, params.block_setback))
+        .flatten()
+        .filter(|inset| poly::utils::polygon_area(inset).abs() >= params.min_sq);
+    let block_polygon = setback.as_ref().unwrap_or(block_polygon);
 
-        // collect building entities for this block
-        let mut building_entities = Vec::new();
+    // fringe slivers are not worth subdividing, unless the user overrode this block
+    let small_block = poly::utils::polygon_area(block_polygon).abs() < params.min_block_area;
+    if small_block && params.drop_small_blocks && !overrides.0.contains_key(&block_idx) {
+        return None;
+    }
 
-        // create building entities
-        for building_poly in buildings {
-            // apply param values
-            let wall_height = block_rng.random_range(params.min_wall_height..params.max_wall_height);
+    let mut alleys = Vec::new();
+    let plots = match overrides.0.get(&block_idx) {
+        Some(BlockOverride::Whole) => vec![block_polygon.clone()],
+        None if small_block => vec![block_polygon.clone()],
+        override_level => {
+            let finer = match override_level {
+                Some(BlockOverride::Finer(n)) => *n,
+                _ => 0,
+            };
+            // each level roughly halves the plot size
+            poly::subdivision::subdivide_to_plots(
+                block_polygon,
+                params.min_sq / 2f32.powi(finer as i32),
+                params.max_sq,
+                params.grid_chaos,
+                params.split_bias,
+                params.size_chaos,
+                params.empty_prob,
+                0,
+                rng,
+                params.max_recursion_depth + finer as usize,
+                params.alley_chance,
+                params.alley_width,
+                &mut alleys,
+                params.subdivision_strategy,
+                params.max_aspect_ratio,
+                params.courtyard_prob,
+            )
+        }
+    };
 
-            // generate meshes
-            let footprint_mesh = poly::mesh_gen::polygon_to_layer_zero(&building_poly);
-            let building_3d_mesh = poly::mesh_gen::polygon_to_building(&building_poly, wall_height);
+    // shrinking and bisecting leave tiny edges, those become degenerate wall quads
+    let mut plots: Vec<_> = plots.iter()
+        .map(|plot| poly::ops::simplify(plot, params.min_edge_length))
+        .filter(|plot| plot.len() >= 3)
+        .collect();
 
-            let footprint_handle = meshes.add(footprint_mesh);
-            let building_3d_handle = meshes.add(building_3d_mesh);
+    // occasional L/U shaped buildings
+    // the rng is only touched when enabled, so existing seeds keep their layout
+    if params.irregular_footprints {
+        for plot in plots.iter_mut() {
+            if rng.random::<f32>() < params.irregular_footprint_prob
+                && let Some(notched) = poly::subdivision::notch_plot(plot, rng, params.min_sq) {
+                *plot = notched;
+            }
+        }
+    }
 
-            // color variations
-            let base_r = (0.8 + block_rng.random_range(-0.05_f32..0.05_f32)).clamp(0.0, 1.0);
-            let base_g = (0.8 + block_rng.random_range(-0.05_f32..0.05_f32)).clamp(0.0, 1.0);
-            let base_b = (0.9 + block_rng.random_range(-0.05_f32..0.05_f32)).clamp(0.0, 1.0);
+    // setbacks, inset each building into its plot and shift it within the freed margin
+    // same as above, no draws unless enabled
+    if params.setback_margin > 0.0 {
+        for plot in plots.iter_mut() {
+            let margin = params.setback_margin * (1.0 - 0.5 * params.setback_jitter * rng.random::<f32>());
+            let shift = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU)) * margin * params.setback_jitter * rng.random::<f32>();
+            // a plot too small for the setback keeps its full footprint
+            if let Some(inset) = poly::ops::inset(plot, margin) {
+                *plot = inset.iter().map(|p| *p + shift).collect();
+            }
+        }
+    }
+    Some((plots, alleys))
+}
 
-            // footprint material
-            let footprint_material = materials.add(StandardMaterial {
-                base_color: Color::srgb(base_r * 0.8, base_g * 0.8, base_b),
-                alpha_mode: AlphaMode::Opaque,
-                ..default()
-            });
+// split a footprint into rooms, the plot subdivision with room sized cells and no gaps
+// rooms get their own rng, so toggling interiors doesn't shift the block layout
+fn plan_rooms(footprint: &crate::systems::mesh::Polygon, seed: u64) -> Vec<crate::systems::mesh::Polygon> {
+    let mut rng = StdRng::seed_from_u64(seed ^ crate::config::ROOM_SALT);
+    poly::subdivision::subdivide_to_plots(
+        footprint,
+        crate::config::ROOM_MIN_AREA,
+        f32::MAX, // never forced, small buildings simply stay one room
+        0.2,
+        0.5,
+        0.3,
+        0.0,
+        0,
+        &mut rng,
+        crate::config::ROOM_MAX_DEPTH,
+        0.0,
+        0.0,
+        &mut Vec::new(),
+        SubdivisionStrategy::LongestEdge,
+        0.0,
+        0.0,
+    )
+}
 
-            // 3D building material
-            let building_3d_material = materials.add(StandardMaterial {
-                base_color: Color::srgb(base_r, base_g, base_b),
-                alpha_mode: AlphaMode::Opaque,
-                ..default()
-            });
-
-            // create main building entity (parent)
-            let building_entity = commands.spawn((
-                Building {
-                    id: building_id,
-                    footprint: building_poly,
-                },
-                Transform::default(),
-            )).id();
+// building meshes for a skeleton without spawning anything, used for batch exports
+// draws from the block rngs in the same order as generate_town, so heights match the live scene
+pub fn town_building_meshes(seed: u64, params: &Params, data: &SkeletonData) -> Vec<Mesh> {
+    let mut meshes = Vec::new();
+    let no_overrides = BlockOverrides::default();
+    let no_rerolls = BlockSeeds::default();
+    let mut building_id = 0;
+    let heights = HeightGradient::new(params, &data.boundary_polygon);
+    for (block_idx, block_polygon) in block_regions(data, params, seed) {
+        let mut block_rng = StdRng::seed_from_u64(no_rerolls.block_seed(seed, block_idx, &block_polygon));
+        let Some((plots, _)) = plan_block(block_idx, &block_polygon, params, &no_overrides, &mut block_rng) else {
+            continue;
+        };
+        for plot in plots {
+            let wall_height = block_rng.random_range(params.min_wall_height..params.max_wall_height) * heights.scale(&plot);
+            let roof_height = draw_roof_height(params, &mut block_rng);
+            // colour variation draws in generate_town
+            for _ in 0..3 {
+                block_rng.random_range(-0.05_f32..0.05_f32);
+            }
+            meshes.push(building_mesh(&plot, wall_height, roof_height, params, openings_seed(seed, building_id)));
+            building_id += 1;
+        }
+    }
+    meshes
+}
 
-            // create footprint entity
-            let footprint_entity = commands.spawn((
-                Mesh3d(footprint_handle),
-                MeshMaterial3d(footprint_material),
-                Transform::default(),
-                Visibility::Visible,
-            )).id();
+// setbacks pull neighbours apart, they still count as adjacent across the yards
+fn adjacency_gap(params: &Params) -> f32 {
+    crate::config::ADJACENCY_GAP + 2.0 * params.setback_margin
+}
 
-            // create 3D building entity
-            let building_3d_entity = commands.spawn((
-                Mesh3d(building_3d_handle),
-                MeshMaterial3d(building_3d_material),
-                Transform::default(),
-                if is_3d { Visibility::Visible } else { Visibility::Hidden },
-            )).id();
+// adjacency edges between plots of one block
+// plots sharing an edge have distance 0, plots across an alley are within the gap
+fn block_adjacency(plots: &[(u32, crate::systems::mesh::Polygon)], gap: f32) -> Vec<(u32, u32)> {
+    let mut edges = Vec::new();
+    for i in 0..plots.len() {
+        for j in (i + 1)..plots.len() {
+            if poly::utils::polygon_distance(&plots[i].1, &plots[j].1) <= gap {
+                edges.push((plots[i].0, plots[j].0));
+            }
+        }
+    }
+    edges
+}
 
-            // add mesh entities as children of building
-            commands.entity(building_entity).add_children(&[footprint_entity, building_3d_entity]);
+// generated points, regular generators first, then the fixed road and boundary generators
+pub struct Generators {
+    pub points: Vec<Vec3>,
+    pub road: std::ops::Range<usize>,
+    pub boundary: std::ops::Range<usize>,
+    pub relaxation_steps: usize, // lloyd's passes actually run
+}
 
-            building_entities.push(building_entity);
-            building_id += 1;
-        }
+// full generator pipeline
+// fixed road + boundary generators, relaxed regular generators, then spacing enforcement
+pub fn generate_generators(params: &Params, boundary_polygon: &[Vec2], road_path: &[Vec3], seed: u64) -> Generators {
+    let boundary_generators = poly::point_gen::generate_boundary_generators(boundary_polygon, params.boundary_spacing, params.boundary_inner_offset);
+    let road_generators = poly::point_gen::generate_road_generators(road_path, params.road_smoothing);
+    let bounds = poly::point_gen::generator_bounds(boundary_polygon, crate::config::GENERATOR_BOUNDS_MARGIN);
+    // even spacing for generator_count points over a boundary of boundary_scale radius
+    let spacing = params.boundary_scale * (std::f32::consts::PI / params.generator_count.max(1) as f32).sqrt();
+    let regular_generators = match params.point_distribution {
+        PointDistribution::Spiral => poly::point_gen::pgen(
+            params.generator_count, 
+            bounds, 
+            crate::config::SPIRAL_SPREAD, 
+            params.density_gradient,
+            params.boundary_scale,
+            seed
+        ),
+        PointDistribution::Poisson => poly::point_gen::poisson_points(
+            params.generator_count,
+            bounds.width(),
+            bounds.height(),
+            spacing * crate::config::POISSON_SPACING_FACTOR,
+            params.density_gradient,
+            params.boundary_scale,
+            seed
+        ).into_iter().map(|p| p + Vec3::new(bounds.min.x, 0.0, bounds.min.y)).collect(),
+        PointDistribution::Grid => poly::point_gen::grid_points(params.generator_count, bounds, spacing),
+    };
+    let regular_count = regular_generators.len();
+    let road = regular_count..regular_count + road_generators.len();
+    let boundary = road.end..road.end + boundary_generators.len();
+    
+    let mut fixed_generators = road_generators;
+    fixed_generators.extend(boundary_generators);
+    
+    let (all_generators, relaxation_steps) = poly::point_gen::prelax(
+        regular_generators,
+        fixed_generators,
+        params.relaxation_steps, 
+        params.relaxation_epsilon,
+        bounds
+    );
 
-        // add building entities as children of block entity 
-        commands.entity(block_entity).add_children(&building_entities);
-    } 
+    // denser toward the core, spacing below keeps the middle from collapsing
+    let core = settlement_core(params, boundary_polygon);
+    let all_generators = poly::point_gen::bias_toward_core(all_generators, regular_count, core, params.core_bias);
+    
+    let points = poly::point_gen::enforce_min_spacing(all_generators, regular_count, params.min_generator_spacing);
+    Generators { points, road, boundary, relaxation_steps }
 }
 
-fn rebuild_boundary_with_offsets(vertex_count: usize, scale: f32, seed: u64, offsets: &[Vec2]) -> crate::systems::mesh::Polygon {
-    let mut base = poly::point_gen::generate_boundary_polygon(vertex_count, scale, seed);
+fn rebuild_boundary_with_offsets(params: &Params, seed: u64, offsets: &[Vec2]) -> crate::systems::mesh::Polygon {
+    let mut base = poly::point_gen::generate_boundary_polygon(params.boundary_vertex_count, params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, seed);
     for (i, &offset) in offsets.iter().enumerate() {
         if i < base.len() { 
             base[i] += offset; 
@@ -193,9 +760,11 @@ fn rebuild_boundary_with_offsets(vertex_count: usize, scale: f32, seed: u64, off
     base
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_regeneration(
     mut commands: Commands,
     mut events: EventReader<RegenerateEvent>,
+    mut rebuild_events: EventReader<RebuildEvent>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut seed: ResMut<Seed>,
@@ -205,12 +774,31 @@ pub fn handle_regeneration(
     is_3d: Res<crate::systems::ui::Is3D>,
     generation_mode: Res<GenerationMode>,
     edit_mode: Res<EditMode>,
+    mut block_overrides: ResMut<BlockOverrides>,
+    mut block_seeds: ResMut<BlockSeeds>,
+    wall_textures: Res<WallTextures>,
+    ghost_towns: GhostTowns,
 ) {
-    for event in events.read() {
+    // towns already turned into a ghost this frame, later events must not despawn them
+    let mut ghosted = HashSet::new();
+    // rebuilds take the skeleton as the sender left it and skip the generation step
+    let requests: Vec<(RegenerateEvent, bool)> = events.read()
+        .map(|event| (RegenerateEvent { seed: event.seed, user_edit: event.user_edit }, false))
+        .chain(rebuild_events.read().map(|event| (RegenerateEvent { seed: event.seed, user_edit: true }, true)))
+        .collect();
+    for (event, rebuild) in requests {
         // println!("Regeneration triggered with seed: {}", event.seed);
-        // cleanup existing town
+        // cleanup existing town, or keep it as the ghost
         for entity in query.iter() {
-            commands.entity(entity).try_despawn();
+            if ghosted.contains(&entity) {
+                continue;
+            }
+            if params.keep_ghost {
+                ghost_towns.convert(&mut commands, &mut materials, entity);
+                ghosted.insert(entity);
+            } else {
+                commands.entity(entity).try_despawn();
+            }
         }
         
         // quick fix
@@ -218,104 +806,52 @@ pub fn handle_regeneration(
         let seed_changed = seed.0 != event.seed;
         seed.0 = event.seed;
         
-        if *generation_mode == GenerationMode::Auto && seed_changed {
+        if rebuild {
+            // skeleton already set, nothing to generate
+        } else if *generation_mode == GenerationMode::Auto && seed_changed {
             // AUTO MODE:
             // redo the entire generation pipeline
-            let boundary_generators = poly::point_gen::generate_boundary_generators(&skeleton_data.boundary_polygon, params.boundary_spacing, params.boundary_inner_offset);
-            let road_generators = poly::point_gen::generate_road_generators(&skeleton_data.road_path);
-            let regular_generators = poly::point_gen::pgen(
-                params.generator_count, 
-                crate::config::CANVAS_WIDTH, 
-                crate::config::CANVAS_HEIGHT, 
-                crate::config::SPIRAL_SPREAD, 
-                event.seed
-            );
-            
-            let mut fixed_generators = road_generators;
-            fixed_generators.extend(boundary_generators);
+            let generators = generate_generators(&params, &skeleton_data.boundary_polygon, &skeleton_data.road_path, event.seed);
+            skeleton_data.set_generators(generators);
             
-            let all_generators = poly::point_gen::prelax(
-                regular_generators,
-                fixed_generators,
-                4, 
-                crate::config::CANVAS_WIDTH, 
-                crate::config::CANVAS_HEIGHT
-            );
-            
-            let voronoi_data = poly::voronoi::vpoly(all_generators.clone(), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
-            skeleton_data.generator_points = all_generators;
-            skeleton_data.points = voronoi_data.points;
-            skeleton_data.cells = voronoi_data.cells;
+            let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), None, &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+            skeleton_data.apply_voronoi(voronoi_data);
+
+            // block indices refer to the old cells
+            block_overrides.0.clear();
+            block_seeds.0.clear();
         } else if *generation_mode == GenerationMode::Manual {
             // MANUAL MODE:
             match *edit_mode {
                 EditMode::Generators => {
                     // only regenerate points if preserve_generators is false (slider change)
                     if !event.user_edit {
-                        let boundary_generators = poly::point_gen::generate_boundary_generators(&skeleton_data.boundary_polygon, params.boundary_spacing, params.boundary_inner_offset);
-                        let road_generators = poly::point_gen::generate_road_generators(&skeleton_data.road_path);
-                        let regular_generators = poly::point_gen::pgen(
-                            params.generator_count, 
-                            crate::config::CANVAS_WIDTH, 
-                            crate::config::CANVAS_HEIGHT, 
-                            crate::config::SPIRAL_SPREAD, 
-                            event.seed
-                        );
-                        
-                        let mut fixed_generators = road_generators;
-                        fixed_generators.extend(boundary_generators);
-                        
-                        let all_generators = poly::point_gen::prelax(
-                            regular_generators,
-                            fixed_generators,
-                            4, 
-                            crate::config::CANVAS_WIDTH, 
-                            crate::config::CANVAS_HEIGHT
-                        );
-                        skeleton_data.generator_points = all_generators;
+                        let generators = generate_generators(&params, &skeleton_data.boundary_polygon, &skeleton_data.road_path, event.seed);
+                        skeleton_data.set_generators(generators);
                     }
                     
                     // always recalculate Voronoi diagram
-                    let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
-                    skeleton_data.points = voronoi_data.points;
-                    skeleton_data.cells = voronoi_data.cells;
+                    let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), Some(&skeleton_data.generator_weights), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+                    skeleton_data.apply_voronoi(voronoi_data);
                 }
                 EditMode::Circumcenters => {
                     if !event.user_edit {
                         // parameter change -> recalculate Voronoi to apply changes (e.g. merge threshold)
-                        let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
-                        skeleton_data.points = voronoi_data.points;
-                        skeleton_data.cells = voronoi_data.cells;
+                        let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), Some(&skeleton_data.generator_weights), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+                        skeleton_data.apply_voronoi(voronoi_data);
                     }
                     // if preserve_generators=true, keep existing circumcenters (manual edits preserved)
                 }
+                EditMode::Blocks => {
+                    // blocks mode -> skeleton untouched, only the overrides changed
+                }
                 EditMode::Roads => {
                     // roads mode -> regenerate with road constraints as fixed generators
-                    let boundary_generators = poly::point_gen::generate_boundary_generators(&skeleton_data.boundary_polygon, params.boundary_spacing, params.boundary_inner_offset);
-                    let road_generators = poly::point_gen::generate_road_generators(&skeleton_data.road_path);
-                    let regular_generators = poly::point_gen::pgen(
-                        params.generator_count, 
-                        crate::config::CANVAS_WIDTH, 
-                        crate::config::CANVAS_HEIGHT, 
-                        crate::config::SPIRAL_SPREAD, 
-                        event.seed
-                    );
-                    
-                    let mut fixed_generators = road_generators;
-                    fixed_generators.extend(boundary_generators);
+                    let generators = generate_generators(&params, &skeleton_data.boundary_polygon, &skeleton_data.road_path, event.seed);
+                    skeleton_data.set_generators(generators);
                     
-                    let all_generators = poly::point_gen::prelax(
-                        regular_generators,
-                        fixed_generators,
-                        4, 
-                        crate::config::CANVAS_WIDTH, 
-                        crate::config::CANVAS_HEIGHT
-                    );
-                    skeleton_data.generator_points = all_generators;
-                    
-                    let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
-                    skeleton_data.points = voronoi_data.points;
-                    skeleton_data.cells = voronoi_data.cells;
+                    let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), None, &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+                    skeleton_data.apply_voronoi(voronoi_data);
                 }
                 EditMode::Boundary => {
                     // boundary mode -> use offset-based system
@@ -329,49 +865,84 @@ pub fn handle_regeneration(
                     
                     // always rebuild: base polygon + user offsets
                     skeleton_data.boundary_polygon = rebuild_boundary_with_offsets(
-                        params.boundary_vertex_count, 
-                        params.boundary_scale, 
+                        &params,
                         event.seed, 
                         &skeleton_data.boundary_vertex_offsets
                     );
                     
-                    let boundary_generators = poly::point_gen::generate_boundary_generators(&skeleton_data.boundary_polygon, params.boundary_spacing, params.boundary_inner_offset);
-                    let road_generators = poly::point_gen::generate_road_generators(&skeleton_data.road_path);
-                    let regular_generators = poly::point_gen::pgen(
-                        params.generator_count, 
-                        crate::config::CANVAS_WIDTH, 
-                        crate::config::CANVAS_HEIGHT, 
-                        crate::config::SPIRAL_SPREAD, 
-                        event.seed
-                    );
-                    
-                    let mut fixed_generators = road_generators;
-                    fixed_generators.extend(boundary_generators);
+                    let generators = generate_generators(&params, &skeleton_data.boundary_polygon, &skeleton_data.road_path, event.seed);
+                    skeleton_data.set_generators(generators);
                     
-                    let all_generators = poly::point_gen::prelax(
-                        regular_generators,
-                        fixed_generators,
-                        4, 
-                        crate::config::CANVAS_WIDTH, 
-                        crate::config::CANVAS_HEIGHT
-                    );
-                    
-                    let voronoi_data = poly::voronoi::vpoly(all_generators.clone(), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
-                    skeleton_data.generator_points = all_generators;
-                    skeleton_data.points = voronoi_data.points;
-                    skeleton_data.cells = voronoi_data.cells;
+                    let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), None, &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+                    skeleton_data.apply_voronoi(voronoi_data);
                 }
             }
         }
 
-        generate_town(&mut commands, &mut meshes, &mut materials, event.seed, &params, &mut skeleton_data, is_3d.0);
+        generate_town(&mut commands, &mut meshes, &mut materials, event.seed, &params, &mut skeleton_data, &block_overrides, &block_seeds, &wall_textures, is_3d.0);
+    }
+}
+
+// re-roll one block's subdivision with a fresh sub-seed, the rest of the town is left alone
+// the block is respawned from its stored polygon, so the skeleton isn't touched
+#[allow(clippy::too_many_arguments)]
+pub fn handle_block_reroll(
+    mut commands: Commands,
+    mut events: EventReader<RerollBlockEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<Params>,
+    overrides: Res<BlockOverrides>,
+    mut block_seeds: ResMut<BlockSeeds>,
+    wall_textures: Res<WallTextures>,
+    is_3d: Res<crate::systems::ui::Is3D>,
+    skeleton_data: Res<SkeletonData>,
+    blocks: Query<(Entity, &Block, &ChildOf, Option<&Children>)>,
+    buildings: Query<&Building>,
+    mut towns: Query<&mut Town>,
+    mut adjacency: ResMut<BuildingAdjacency>,
+) {
+    for event in events.read() {
+        let block_idx = event.0;
+        // ghost blocks lost their Block component, so this is the live town's block
+        let Some((block_entity, block, child_of, children)) = blocks.iter()
+            .find(|(_, block, _, _)| block.id == Some(block_idx as u32)) else {
+            continue; // dropped block, nothing to re-roll
+        };
+        let Ok(mut town) = towns.get_mut(child_of.parent()) else {
+            continue;
+        };
+
+        *block_seeds.0.entry(block_idx).or_default() += 1;
+
+        let old_buildings: Vec<&Building> = children.into_iter()
+            .flatten()
+            .filter_map(|&child| buildings.get(child).ok())
+            .collect();
+        let old_ids: HashSet<u32> = old_buildings.iter().map(|building| building.id).collect();
+        let old_population: u32 = old_buildings.iter().map(|building| building.population).sum();
+
+        // new ids go past every existing one, ids stay unique across the town
+        let first_building_id = buildings.iter().map(|building| building.id + 1).max().unwrap_or(0);
+
+        commands.entity(block_entity).try_despawn();
+        adjacency.edges.retain(|(a, b)| !old_ids.contains(a) && !old_ids.contains(b));
+        town.population = town.population.saturating_sub(old_population);
+
+        let block_seed = block_seeds.block_seed(town.seed, block_idx, &block.polygon);
+        if let Some(spawned) = spawn_block(&mut commands, &mut meshes, &mut materials, town.seed, block_seed, block_idx, block.polygon.clone(), block.generator, &params, &overrides, &wall_textures, is_3d.0, first_building_id, HeightGradient::new(&params, &skeleton_data.boundary_polygon)) {
+            commands.entity(child_of.parent()).add_children(&[spawned.entity]);
+            adjacency.edges.extend(block_adjacency(&spawned.plots, adjacency_gap(&params)));
+            town.population += spawned.population;
+        }
     }
 }
 
+#[allow(clippy::type_complexity)]
 pub fn handle_clear(
     mut commands: Commands,
     mut events: EventReader<ClearEvent>,
-    query: Query<Entity, With<Town>>,
+    query: Query<Entity, Or<(With<Town>, With<GhostTown>)>>,
     mut skeleton_data: ResMut<SkeletonData>,
 ) {
     for _event in events.read() {
@@ -383,8 +954,293 @@ pub fn handle_clear(
         
         // clear all skeleton vertices
         skeleton_data.generator_points.clear();
+        skeleton_data.generator_weights.clear();
         skeleton_data.points.clear();
         skeleton_data.cells.clear();
-        skeleton_data.boundary_polygon = poly::point_gen::generate_boundary_polygon(4, 50.0, crate::config::INITIAL_SEED);
+        skeleton_data.cell_generators.clear();
+        skeleton_data.boundary_polygon = poly::point_gen::generate_boundary_polygon(4, 50.0, 1.0, crate::config::BOUNDARY_NOISE_FREQ, crate::config::BOUNDARY_NOISE_AMP, crate::config::INITIAL_SEED);
+    }
+}
+
+// snap the boundary back to a clean regular polygon
+// offsets are stored relative to the jittered base polygon, so store the difference
+pub fn handle_regularize_boundary(
+    mut events: EventReader<RegularizeBoundaryEvent>,
+    mut regen_events: EventWriter<RegenerateEvent>,
+    mut skeleton_data: ResMut<SkeletonData>,
+    seed: Res<Seed>,
+    params: Res<Params>,
+) {
+    for _event in events.read() {
+        let base = poly::point_gen::generate_boundary_polygon(params.boundary_vertex_count, params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, seed.0);
+        let regular = poly::point_gen::generate_regular_polygon(params.boundary_vertex_count, params.boundary_scale, params.boundary_aspect);
+
+        skeleton_data.boundary_vertex_offsets = regular.iter()
+            .zip(base.iter())
+            .map(|(r, b)| *r - *b)
+            .collect();
+        skeleton_data.boundary_polygon = regular;
+
+        regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
     }
-}
\ No newline at end of file
+}
+
+// replace the boundary with the convex hull of the regular generators, cut down to the allowed vertex count
+// regular and road generators are kept, the boundary generators follow the new outline
+pub fn handle_fit_boundary(
+    mut events: EventReader<FitBoundaryEvent>,
+    mut rebuild_events: EventWriter<RebuildEvent>,
+    mut notifications: EventWriter<crate::systems::ui::NotificationEvent>,
+    mut skeleton_data: ResMut<SkeletonData>,
+    mut params: ResMut<Params>,
+    seed: Res<Seed>,
+) {
+    for _event in events.read() {
+        // only the regular generators, the road and boundary constraints would push the hull outward on every fit
+        let points: Vec<Vec2> = skeleton_data.generator_points.iter()
+            .enumerate()
+            .filter(|(idx, _)| !skeleton_data.road_generators.contains(idx) && !skeleton_data.boundary_generators.contains(idx))
+            .map(|(_, p)| Vec2::new(p.x, p.z))
+            .collect();
+        let hull = poly::ops::convex_hull(&points);
+        if hull.len() < 3 {
+            let message = "Fit boundary skipped: need at least 3 non-collinear generators".to_string();
+            eprintln!("{}", message);
+            notifications.write(crate::systems::ui::NotificationEvent { message, is_error: true });
+            continue;
+        }
+
+        // push the hull out a little so the generators on it count as inside
+        let centroid = poly::utils::polygon_centroid(&hull, poly::utils::polygon_area(&hull));
+        let boundary: crate::systems::mesh::Polygon = hull.iter()
+            .map(|&v| v + (v - centroid).normalize_or_zero() * crate::config::HULL_MARGIN)
+            .collect();
+        let boundary = poly::ops::limit_vertices(&boundary, crate::config::BOUNDARY_VERTEX_RANGE);
+
+        // keep the offset system consistent: the hull becomes base polygon + offsets
+        params.boundary_vertex_count = boundary.len();
+        let base = poly::point_gen::generate_boundary_polygon(boundary.len(), params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, seed.0);
+        skeleton_data.boundary_vertex_offsets = boundary.iter()
+            .zip(base.iter())
+            .map(|(b, base)| *b - *base)
+            .collect();
+        skeleton_data.boundary_polygon = boundary;
+
+        // the old boundary generators line the old outline, replace them with ones along the fitted hull
+        let old_boundary = skeleton_data.boundary_generators.clone();
+        for _ in old_boundary.clone() {
+            skeleton_data.remove_generator(old_boundary.start);
+        }
+        let boundary_generators = poly::point_gen::generate_boundary_generators(&skeleton_data.boundary_polygon, params.boundary_spacing, params.boundary_inner_offset);
+        let start = skeleton_data.generator_points.len();
+        skeleton_data.generator_points.extend(boundary_generators);
+        skeleton_data.boundary_generators = start..skeleton_data.generator_points.len();
+
+        let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), Some(&skeleton_data.generator_weights), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+        skeleton_data.apply_voronoi(voronoi_data);
+
+        // the diagram is done, a regeneration would redo it or throw the fitted boundary away
+        rebuild_events.write(RebuildEvent { seed: seed.0 });
+    }
+}
+
+// reweight one generator and rebuild the power diagram around the new weights
+// a slider drag is recorded like a point drag, one undo step from press to release
+pub fn handle_generator_weight(
+    mut events: EventReader<GeneratorWeightEvent>,
+    mut regen_events: EventWriter<RegenerateEvent>,
+    mut skeleton_data: ResMut<SkeletonData>,
+    mut history: ResMut<EditHistory>,
+    params: Res<Params>,
+    seed: Res<Seed>,
+) {
+    for event in events.read() {
+        if event.generator >= skeleton_data.generator_points.len() {
+            continue;
+        }
+        if !history.is_dragging() {
+            history.begin_drag(&skeleton_data);
+        }
+
+        let generator_count = skeleton_data.generator_points.len();
+        skeleton_data.generator_weights.resize(generator_count, crate::config::GENERATOR_WEIGHT);
+        skeleton_data.generator_weights[event.generator] = event.weight;
+        let voronoi_data = poly::voronoi::vpoly(skeleton_data.generator_points.clone(), Some(&skeleton_data.generator_weights), &skeleton_data.boundary_polygon, params.circumcenter_merge_threshold);
+        skeleton_data.apply_voronoi(voronoi_data);
+
+        if event.release {
+            history.end_drag(&skeleton_data);
+        }
+        regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+    }
+}
+
+// set one road point's corridor width, the rest of the path keeps its widths
+pub fn handle_road_width(
+    mut events: EventReader<RoadWidthEvent>,
+    mut regen_events: EventWriter<RegenerateEvent>,
+    mut skeleton_data: ResMut<SkeletonData>,
+    mut history: ResMut<EditHistory>,
+    seed: Res<Seed>,
+) {
+    for event in events.read() {
+        if event.point >= skeleton_data.road_path.len() {
+            continue;
+        }
+        if !history.is_dragging() {
+            history.begin_drag(&skeleton_data);
+        }
+
+        let point_count = skeleton_data.road_path.len();
+        skeleton_data.road_widths.resize(point_count, crate::config::ROAD_WIDTH);
+        skeleton_data.road_widths[event.point] = event.width;
+
+        if event.release {
+            history.end_drag(&skeleton_data);
+        }
+        regen_events.write(RegenerateEvent { seed: seed.0, user_edit: true });
+    }
+}
+
+// road corridor as one quad per road segment
+// widths are per road point, a segment takes the mean of its two ends
+// quads are extended by half the width at both ends so joints are covered
+pub fn road_corridor_quads(road_path: &[Vec3], widths: &[f32]) -> Vec<crate::systems::mesh::Polygon> {
+    road_path.windows(2)
+        .zip(widths.windows(2))
+        .filter_map(|(segment, widths)| {
+            let half_width = (widths[0] + widths[1]) * 0.25;
+            let start = Vec2::new(segment[0].x, segment[0].z);
+            let end = Vec2::new(segment[1].x, segment[1].z);
+            if start.distance(end) <= 0.1 {
+                return None; // skip degenerate segments
+            }
+            let dir = (end - start).normalize();
+            let perp = dir.perp() * half_width;
+            let (start, end) = (start - dir * half_width, end + dir * half_width);
+            Some(vec![start - perp, end - perp, end + perp, start + perp])
+        })
+        .collect()
+}
+
+// carve the current road corridor out of existing buildings, without regenerating
+// buildings keep their largest remaining piece, or are removed if too little is left
+#[allow(clippy::too_many_arguments)]
+pub fn handle_road_carve(
+    mut commands: Commands,
+    mut events: EventReader<CarveRoadEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    params: Res<Params>,
+    skeleton_data: Res<SkeletonData>,
+    mut buildings: Query<(Entity, &mut Building, &Children)>,
+    footprints: Query<&Mesh3d, With<BuildingFootprint>>,
+    mut rooms: Query<&mut Room>,
+    mut adjacency: ResMut<BuildingAdjacency>,
+    mut towns: Query<&mut Town>,
+) {
+    for _event in events.read() {
+        let road_path = poly::point_gen::smooth_road_path(&skeleton_data.road_path, params.road_smoothing);
+        let corridor = road_corridor_quads(&road_path, &skeleton_data.smoothed_road_widths(params.road_smoothing));
+        if corridor.is_empty() {
+            continue;
+        }
+
+        let town_seed = towns.iter().next().map_or(0, |town| town.seed);
+        let mut removed = HashSet::new();
+        let mut lost_population = 0;
+        for (entity, mut building, children) in buildings.iter_mut() {
+            let mut pieces = vec![building.footprint.clone()];
+            for quad in &corridor {
+                pieces = pieces.iter()
+                    .flat_map(|piece| poly::utils::subtract_convex_polygon(piece, quad))
+                    .collect();
+            }
+
+            // untouched building, single piece identical to the original
+            if pieces.len() == 1 && pieces[0] == building.footprint {
+                continue;
+            }
+
+            let largest = pieces.into_iter()
+                .max_by(|a, b| poly::utils::polygon_area(a).abs().total_cmp(&poly::utils::polygon_area(b).abs()));
+
+            match largest {
+                Some(footprint) if poly::utils::polygon_area(&footprint).abs() >= params.min_sq * 0.5 => {
+                    // remesh in place, materials and entities are kept
+                    meshes.insert(&building.mesh, building_mesh(&footprint, building.wall_height, building.roof_height, &params, openings_seed(town_seed, building.id)));
+                    meshes.insert(&building.lod_mesh, poly::mesh_gen::polygon_to_building_lod(&footprint, lod_height(building.wall_height, building.roof_height), 1));
+                    for child in children.iter() {
+                        if let Ok(mesh3d) = footprints.get(child) {
+                            meshes.insert(&mesh3d.0, poly::mesh_gen::polygon_to_layer_zero(&footprint, params.uv_mode, params.uv_tile_size));
+                        } else if let Ok(mut room) = rooms.get_mut(child) {
+                            // rooms are cut back to what's left of the building
+                            let remaining = poly::ops::clip_to(&room.polygon, &footprint).into_iter()
+                                .max_by(|a, b| poly::utils::polygon_area(a).abs().total_cmp(&poly::utils::polygon_area(b).abs()));
+                            match remaining {
+                                Some(polygon) => room.polygon = polygon,
+                                None => commands.entity(child).try_despawn(),
+                            }
+                        }
+                    }
+                    let population = estimate_population(&footprint, building.wall_height, params.floor_height);
+                    lost_population += building.population.saturating_sub(population);
+                    building.population = population;
+                    building.footprint = footprint;
+                }
+                _ => {
+                    commands.entity(entity).try_despawn();
+                    removed.insert(building.id);
+                    lost_population += building.population;
+                }
+            }
+        }
+
+        adjacency.edges.retain(|(a, b)| !removed.contains(a) && !removed.contains(b));
+        for mut town in towns.iter_mut() {
+            town.population = town.population.saturating_sub(lost_population);
+        }
+    }
+}
+
+// swap distant buildings to their lod box, by distance from the camera to the building's middle
+// two buckets, full detail within lod_distance and the box past it
+// only runs in 3D view, in 2D every building mesh stays hidden
+#[allow(clippy::type_complexity)]
+pub fn update_building_lod(
+    mut commands: Commands,
+    settings: Res<crate::systems::render::RenderSettings>,
+    is_3d: Res<crate::systems::ui::Is3D>,
+    mut camera: crate::systems::interaction::MainCamera,
+    buildings: Query<(&Building, &Children)>,
+    mut building_meshes: Query<(&mut Visibility, Has<BuildingMeshLod>), Or<(With<BuildingMesh>, With<BuildingMeshLod>)>>,
+) {
+    if !is_3d.0 {
+        return;
+    }
+    let Some((_, camera_transform)) = camera.get("update_building_lod") else {
+        return;
+    };
+    let eye = camera_transform.translation();
+
+    for (building, children) in buildings.iter() {
+        let far = settings.building_lod && {
+            let centre = poly::utils::polygon_centroid(&building.footprint, poly::utils::polygon_area(&building.footprint));
+            eye.distance(Vec3::new(centre.x, building.wall_height * 0.5, centre.y)) > settings.lod_distance
+        };
+        for child in children.iter() {
+            let Ok((mut visibility, is_lod)) = building_meshes.get_mut(child) else { continue };
+            let wanted = if far == is_lod { Visibility::Visible } else { Visibility::Hidden };
+            if *visibility == wanted {
+                continue;
+            }
+            *visibility = wanted;
+            if !is_lod {
+                if far {
+                    commands.entity(child).insert(LodCulled);
+                } else {
+                    commands.entity(child).remove::<LodCulled>();
+                }
+            }
+        }
+    }
+}
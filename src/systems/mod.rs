@@ -3,3 +3,6 @@ pub mod grid;
 pub mod ui;
 pub mod interaction;
 pub mod export;
+pub mod import;
+pub mod persistence;
+pub mod render;
\ No newline at end of file
@@ -0,0 +1,158 @@
+// saves and loads the whole skeleton as RON
+// manual edits (dragged points, boundary, road) live in SkeletonData, so this keeps them across sessions
+// named parameter presets are kept the same way, in one RON file
+
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::systems::mesh::{BlockOverrides, BlockSeeds, Params, RebuildEvent, Seed, SkeletonData};
+use crate::systems::ui::NotificationEvent;
+
+// scene save/load events
+#[derive(Event)]
+pub struct SaveSceneEvent {
+    pub filename: String,
+}
+
+#[derive(Event)]
+pub struct LoadSceneEvent {
+    pub filename: String,
+}
+
+// scene file path as typed in the UI
+#[derive(Resource)]
+pub struct SceneFile(pub String);
+
+impl Default for SceneFile {
+    fn default() -> Self {
+        Self("scene.ron".to_string())
+    }
+}
+
+pub fn save_skeleton(skeleton_data: &SkeletonData, filename: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let text = ron::ser::to_string_pretty(skeleton_data, ron::ser::PrettyConfig::default())?;
+    std::fs::write(filename, text)?;
+    Ok(())
+}
+
+// read a saved skeleton, rejected when its indices don't fit its own points
+// so a hand-edited or truncated file can't panic the town build
+pub fn load_skeleton(filename: &str) -> Result<SkeletonData, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(filename)?;
+    let mut skeleton_data: SkeletonData = ron::from_str(&text)?;
+
+    if skeleton_data.boundary_polygon.len() < 3 {
+        return Err("boundary needs at least 3 points".into());
+    }
+    if let Some(cell_idx) = skeleton_data.cells.iter().position(|cell| cell.iter().any(|&idx| idx >= skeleton_data.points.len())) {
+        return Err(format!("cell {} refers to a missing circumcenter", cell_idx).into());
+    }
+    if skeleton_data.cell_generators.iter().any(|&idx| idx >= skeleton_data.generator_points.len()) {
+        return Err("cell refers to a missing generator".into());
+    }
+    let generator_count = skeleton_data.generator_points.len();
+    for (kind, range) in [("road", &skeleton_data.road_generators), ("boundary", &skeleton_data.boundary_generators)] {
+        if range.start > range.end || range.end > generator_count {
+            return Err(format!("{} generators {:?} don't fit the {} generators", kind, range, generator_count).into());
+        }
+    }
+    if !skeleton_data.road_generators.is_empty() && skeleton_data.road_path.len() < 2 {
+        return Err("road generators without a road path".into());
+    }
+    skeleton_data.cell_adjacency = crate::systems::mesh::poly::voronoi::cell_adjacency(&skeleton_data.cells);
+    Ok(skeleton_data)
+}
+
+pub fn handle_save_scene(
+    mut events: EventReader<SaveSceneEvent>,
+    mut notifications: EventWriter<NotificationEvent>,
+    skeleton_data: Res<SkeletonData>,
+) {
+    for event in events.read() {
+        match save_skeleton(&skeleton_data, &event.filename) {
+            Ok(()) => {
+                println!("Saved scene to {}", event.filename);
+                notifications.write(NotificationEvent { message: format!("Saved scene to {}", event.filename), is_error: false });
+            }
+            Err(e) => {
+                eprintln!("Scene save failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Save failed: {}", e), is_error: true });
+            }
+        }
+    }
+}
+
+// replace the skeleton and rebuild the town around it
+// the regen is a user edit with the current seed, so auto mode builds on the loaded skeleton as is
+#[allow(clippy::too_many_arguments)]
+pub fn handle_load_scene(
+    mut events: EventReader<LoadSceneEvent>,
+    mut rebuild_events: EventWriter<RebuildEvent>,
+    mut notifications: EventWriter<NotificationEvent>,
+    mut skeleton_data: ResMut<SkeletonData>,
+    mut params: ResMut<Params>,
+    mut block_overrides: ResMut<BlockOverrides>,
+    mut block_seeds: ResMut<BlockSeeds>,
+    seed: Res<Seed>,
+) {
+    for event in events.read() {
+        let loaded = match load_skeleton(&event.filename) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("Scene load failed: {}", e);
+                notifications.write(NotificationEvent { message: format!("Load failed: {}", e), is_error: true });
+                continue;
+            }
+        };
+        *skeleton_data = loaded;
+        // boundary-mode regeneration rebuilds the outline when the count doesn't match it
+        params.boundary_vertex_count = skeleton_data.boundary_polygon.len();
+
+        // block indices refer to the old cells
+        block_overrides.0.clear();
+        block_seeds.0.clear();
+
+        println!("Loaded scene from {}", event.filename);
+        notifications.write(NotificationEvent { message: format!("Loaded scene from {}", event.filename), is_error: false });
+
+        // build the town from the loaded skeleton as is, edit-mode regeneration would replace it
+        rebuild_events.write(RebuildEvent { seed: seed.0 });
+    }
+}
+
+// named Params snapshots, persisted to a RON map of name -> params
+// sorted by name so the combo box and the file keep a stable order
+#[derive(Resource, Default)]
+pub struct PresetStore {
+    pub path: String,
+    pub presets: BTreeMap<String, Params>,
+    pub selected: Option<String>,
+    pub name_input: String, // name typed for the next save
+}
+
+impl PresetStore {
+    // a missing file is an empty store, an unreadable one is reported and left untouched on disk until the next save
+    pub fn load(path: &str) -> Self {
+        let presets = match std::fs::read_to_string(path) {
+            Ok(text) => ron::from_str::<BTreeMap<String, Params>>(&text).unwrap_or_else(|e| {
+                eprintln!("Could not read presets from {}: {}", path, e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        Self { path: path.to_string(), presets, ..default() }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let text = ron::ser::to_string_pretty(&self.presets, ron::ser::PrettyConfig::default())?;
+        std::fs::write(&self.path, text)?;
+        Ok(())
+    }
+
+    // preset params pulled into the slider ranges, a hand-edited file can't push the UI out of bounds
+    pub fn get(&self, name: &str) -> Option<Params> {
+        let mut params = self.presets.get(name)?.clone();
+        params.clamp_to_ranges();
+        Some(params)
+    }
+}
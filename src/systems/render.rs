@@ -0,0 +1,386 @@
+use bevy::prelude::*;
+use bevy::pbr::wireframe::WireframeConfig;
+use bevy::window::PresentMode;
+use std::time::{Duration, Instant};
+use bevy::gizmos::config::{GizmoConfigGroup, GizmoConfigStore};
+
+use crate::systems::mesh::{poly, HoveredBuilding, Params, SelectedBuilding, SkeletonData};
+use crate::systems::mesh::town::{footprint_color, Building, BuildingFootprint, BuildingMesh, Room};
+use crate::systems::ui::AttributeLegend;
+
+// runtime render settings (antialiasing, lighting etc.)
+// kept as a resource so the UI can modify them, then applied to the camera
+pub struct RenderSettingsPlugin;
+
+// separate gizmo group for building outlines,
+// these should be occluded by buildings unlike the debug gizmos
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct OutlineGizmoGroup;
+
+impl Plugin for RenderSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(RenderSettings::default())
+            .init_gizmo_group::<OutlineGizmoGroup>()
+            .add_systems(Startup, (setup_gizmos, spawn_fill_light))
+            .add_systems(Update, (apply_render_settings, apply_building_colors, draw_building_outlines, draw_building_selection, draw_room_walls))
+            .add_systems(Last, limit_frame_rate);
+    }
+}
+
+// how building edges are drawn
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WireframeStyle {
+    Triangles, // global wireframe, every triangle edge
+    Outline,   // footprint outline only
+}
+
+// per-building attribute shown as a colour ramp, a thematic map of the settlement
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorBy {
+    Default,      // each building's own colour
+    AreaTier,     // footprint area, in equal-width classes
+    WallHeight,
+    VertexCount,  // footprint corners
+    RoadDistance, // footprint centroid to the road path
+}
+
+impl ColorBy {
+    pub const ALL: [ColorBy; 5] = [ColorBy::Default, ColorBy::AreaTier, ColorBy::WallHeight, ColorBy::VertexCount, ColorBy::RoadDistance];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorBy::Default => "None",
+            ColorBy::AreaTier => "Area Tier",
+            ColorBy::WallHeight => "Wall Height",
+            ColorBy::VertexCount => "Vertex Count",
+            ColorBy::RoadDistance => "Distance to Road",
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            ColorBy::AreaTier => " m²",
+            ColorBy::WallHeight | ColorBy::RoadDistance => " m",
+            ColorBy::Default | ColorBy::VertexCount => "",
+        }
+    }
+
+    // classes the ramp is cut into, None for a continuous ramp
+    pub fn classes(&self) -> Option<usize> {
+        match self {
+            ColorBy::AreaTier => Some(crate::config::AREA_TIER_CLASSES),
+            _ => None,
+        }
+    }
+
+    // raw attribute value, None for the default colouring
+    fn value(&self, building: &Building, road_path: &[Vec2]) -> Option<f32> {
+        match self {
+            ColorBy::Default => None,
+            ColorBy::AreaTier => Some(poly::utils::polygon_area(&building.footprint).abs()),
+            ColorBy::WallHeight => Some(building.wall_height),
+            ColorBy::VertexCount => Some(building.footprint.len() as f32),
+            ColorBy::RoadDistance => {
+                let area = poly::utils::polygon_area(&building.footprint);
+                let centroid = poly::utils::polygon_centroid(&building.footprint, area);
+                let distance = road_path.windows(2)
+                    .map(|segment| poly::utils::point_to_line_distance(centroid, segment[0], segment[1]))
+                    .fold(f32::INFINITY, f32::min);
+                Some(if distance.is_finite() { distance } else { 0.0 })
+            }
+        }
+    }
+}
+
+// blue (low) -> yellow -> red (high), t in 0..1
+pub fn attribute_ramp(t: f32) -> Color {
+    let low = Vec3::new(0.2, 0.35, 0.85);
+    let mid = Vec3::new(0.95, 0.85, 0.3);
+    let high = Vec3::new(0.85, 0.2, 0.2);
+    let t = t.clamp(0.0, 1.0);
+    let rgb = if t < 0.5 { low.lerp(mid, t * 2.0) } else { mid.lerp(high, t * 2.0 - 1.0) };
+    Color::srgb(rgb.x, rgb.y, rgb.z)
+}
+
+// position on the ramp, snapped to the middle of its class when the attribute is classed
+pub fn ramp_position(t: f32, classes: Option<usize>) -> f32 {
+    match classes {
+        Some(classes) if classes > 1 => {
+            let class = ((t * classes as f32) as usize).min(classes - 1);
+            (class as f32 + 0.5) / classes as f32
+        }
+        _ => t,
+    }
+}
+
+#[derive(Resource)]
+pub struct RenderSettings {
+    pub msaa: Msaa,
+    pub wireframe: WireframeStyle,
+    // lighting
+    pub ambient_brightness: f32,
+    pub ambient_color: [f32; 3], // linear rgb, egui edits this directly
+    pub fill_light: bool,
+    pub fill_illuminance: f32,
+    // analysis overlay
+    pub color_by: ColorBy,
+    // frame pacing
+    pub vsync: bool,
+    pub limit_fps: bool,
+    pub max_fps: f32,
+    // distant buildings drawn as their lod box
+    pub building_lod: bool,
+    pub lod_distance: f32,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            msaa: Msaa::Sample4, // bevy default
+            wireframe: WireframeStyle::Triangles,
+            ambient_brightness: crate::config::AMBIENT_BRIGHTNESS,
+            ambient_color: [1.0, 1.0, 1.0],
+            fill_light: false,
+            fill_illuminance: crate::config::FILL_LIGHT_ILLUMINANCE,
+            color_by: ColorBy::Default,
+            vsync: true, // bevy default
+            limit_fps: false,
+            max_fps: crate::config::MAX_FPS,
+            building_lod: true,
+            lod_distance: crate::config::LOD_DISTANCE,
+        }
+    }
+}
+
+// secondary light opposite the sun, lifts the facades that face away from it
+#[derive(Component)]
+pub struct FillLight;
+
+// MSAA options exposed in the UI, label + value
+pub const MSAA_OPTIONS: [(&str, Msaa); 3] = [
+    ("Off", Msaa::Off),
+    ("2x", Msaa::Sample2),
+    ("4x", Msaa::Sample4),
+];
+
+// push the msaa setting onto every camera
+// the wireframe pipeline is specialized per view sample count,
+// so the wireframe overlay follows the camera's msaa without extra work
+#[allow(clippy::too_many_arguments)]
+fn apply_render_settings(
+    settings: Res<RenderSettings>,
+    mut cameras: Query<&mut Msaa, With<Camera>>,
+    mut wireframe_config: ResMut<WireframeConfig>,
+    mut ambient: ResMut<AmbientLight>,
+    mut fill_lights: Query<(&mut DirectionalLight, &mut Visibility), With<FillLight>>,
+    mut windows: Query<&mut Window>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for mut msaa in cameras.iter_mut() {
+        if *msaa != settings.msaa {
+            *msaa = settings.msaa;
+        }
+    }
+
+    wireframe_config.global = settings.wireframe == WireframeStyle::Triangles;
+
+    let [r, g, b] = settings.ambient_color;
+    ambient.color = Color::linear_rgb(r, g, b);
+    ambient.brightness = settings.ambient_brightness;
+
+    for (mut light, mut visibility) in fill_lights.iter_mut() {
+        light.illuminance = settings.fill_illuminance;
+        *visibility = if settings.fill_light { Visibility::Visible } else { Visibility::Hidden };
+    }
+
+    // only touch the window when it differs, a present mode change reconfigures the surface
+    let present_mode = if settings.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+    for mut window in windows.iter_mut() {
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+}
+
+// recolour every building's materials by the chosen attribute, or back to its own colour
+// values are normalized over the current buildings, the legend gets the same range
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_building_colors(
+    settings: Res<RenderSettings>,
+    skeleton_data: Res<SkeletonData>,
+    params: Res<Params>,
+    buildings: Query<(&Building, &Children)>,
+    changed: Query<(), Changed<Building>>,
+    building_materials: Query<(&MeshMaterial3d<StandardMaterial>, Has<BuildingFootprint>), Or<(With<BuildingFootprint>, With<BuildingMesh>)>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut legend: ResMut<AttributeLegend>,
+) {
+    let color_by = settings.color_by;
+    let overlay_stale = color_by != ColorBy::Default && (!changed.is_empty() || skeleton_data.is_changed());
+    if !settings.is_changed() && !overlay_stale {
+        return;
+    }
+
+    // measure against the smoothed road that is actually meshed
+    let road_path: Vec<Vec2> = poly::point_gen::smooth_road_path(&skeleton_data.road_path, params.road_smoothing).iter().map(|p| Vec2::new(p.x, p.z)).collect();
+    let values: Vec<Option<f32>> = buildings.iter()
+        .map(|(building, _)| color_by.value(building, &road_path))
+        .collect();
+    let (min, max) = values.iter().flatten()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| (min.min(value), max.max(value)));
+
+    for ((building, children), value) in buildings.iter().zip(&values) {
+        let color = match value {
+            Some(value) => {
+                let t = if max > min { (value - min) / (max - min) } else { 0.5 };
+                attribute_ramp(ramp_position(t, color_by.classes()))
+            }
+            None => building.color,
+        };
+        for child in children.iter() {
+            let Ok((material, is_footprint)) = building_materials.get(child) else { continue };
+            let Some(material) = materials.get_mut(&material.0) else { continue };
+            // the default footprints are a shade off the walls, the overlay colours both the same
+            material.base_color = if value.is_none() && is_footprint { footprint_color(color) } else { color };
+        }
+    }
+
+    let legend_value = if color_by == ColorBy::Default || min > max { None } else { Some((min, max)) };
+    *legend = AttributeLegend { color_by, range: legend_value };
+}
+
+// sleep off the rest of the frame budget when the limiter is on
+// runs last so the sleep covers the whole frame's work
+fn limit_frame_rate(
+    settings: Res<RenderSettings>,
+    mut frame_start: Local<Option<Instant>>,
+) {
+    if settings.limit_fps && settings.max_fps > 0.0 && let Some(start) = *frame_start {
+        let budget = Duration::from_secs_f32(1.0 / settings.max_fps);
+        let elapsed = start.elapsed();
+        if elapsed < budget {
+            std::thread::sleep(budget - elapsed);
+        }
+    }
+    *frame_start = Some(Instant::now());
+}
+
+// no shadows, it only needs to soften the dark side
+fn spawn_fill_light(
+    mut commands: Commands,
+    settings: Res<RenderSettings>,
+) {
+    commands.spawn((
+        FillLight,
+        DirectionalLight {
+            illuminance: settings.fill_illuminance,
+            shadows_enabled: false,
+            ..default()
+        },
+        // roughly opposite the main light in main.rs, coming in lower
+        Transform::from_xyz(-50000.0, 25000.0, -30000.0).looking_at(Vec3::ZERO, Vec3::Y),
+        if settings.fill_light { Visibility::Visible } else { Visibility::Hidden },
+    ));
+}
+
+fn setup_gizmos(
+    mut config_store: ResMut<GizmoConfigStore>
+) {
+    let (config, _) = config_store.config_mut::<OutlineGizmoGroup>();
+    config.depth_bias = -0.001; // just enough to win against the faces they sit on
+}
+
+// draw footprint outlines per building, instead of the triangle wireframe
+// base ring, top ring and vertical corner edges when in 3D
+fn draw_building_outlines(
+    mut gizmos: Gizmos<OutlineGizmoGroup>,
+    settings: Res<RenderSettings>,
+    is_3d: Res<crate::systems::ui::Is3D>,
+    buildings: Query<&Building>,
+) {
+    if settings.wireframe != WireframeStyle::Outline {
+        return;
+    }
+
+    let color = Color::BLACK;
+    for building in buildings.iter() {
+        let footprint = &building.footprint;
+        for i in 0..footprint.len() {
+            let a = footprint[i];
+            let b = footprint[(i + 1) % footprint.len()];
+
+            gizmos.line(Vec3::new(a.x, 0.0, a.y), Vec3::new(b.x, 0.0, b.y), color);
+
+            if is_3d.0 {
+                let h = building.wall_height;
+                gizmos.line(Vec3::new(a.x, h, a.y), Vec3::new(b.x, h, b.y), color);
+                gizmos.line(Vec3::new(a.x, 0.0, a.y), Vec3::new(a.x, h, a.y), color);
+            }
+        }
+    }
+}
+
+// outline the hovered and selected buildings
+// lifted slightly so it shows above the regular edges
+fn draw_building_selection(
+    mut gizmos: Gizmos<OutlineGizmoGroup>,
+    is_3d: Res<crate::systems::ui::Is3D>,
+    hovered: Res<HoveredBuilding>,
+    selected: Res<SelectedBuilding>,
+    buildings: Query<&Building>,
+) {
+    let highlights = [
+        (hovered.0, Color::srgb(1.0, 0.5, 0.0)),  // orange for hovered
+        (selected.0, Color::srgb(1.0, 1.0, 0.0)), // yellow for selected, drawn last
+    ];
+
+    for (entity, color) in highlights {
+        let Some(building) = entity.and_then(|entity| buildings.get(entity).ok()) else { continue };
+        let footprint = &building.footprint;
+        let h = if is_3d.0 { building.wall_height } else { 0.0 };
+
+        for i in 0..footprint.len() {
+            let a = footprint[i];
+            let b = footprint[(i + 1) % footprint.len()];
+
+            gizmos.line(Vec3::new(a.x, 0.02, a.y), Vec3::new(b.x, 0.02, b.y), color);
+            if is_3d.0 {
+                gizmos.line(Vec3::new(a.x, h + 0.02, a.y), Vec3::new(b.x, h + 0.02, b.y), color);
+                gizmos.line(Vec3::new(a.x, 0.0, a.y), Vec3::new(a.x, h, a.y), color);
+            }
+        }
+    }
+}
+
+// interior walls as low outlines, default gizmos so they show through roofs and walls
+// rooms share walls, so shared edges are simply drawn twice
+fn draw_room_walls(
+    mut gizmos: Gizmos,
+    is_3d: Res<crate::systems::ui::Is3D>,
+    rooms: Query<(&Room, &ChildOf)>,
+    buildings: Query<&Building>,
+) {
+    let color = Color::srgba(0.95, 0.75, 0.35, 0.8);
+    for (room, child_of) in rooms.iter() {
+        let h = match buildings.get(child_of.parent()) {
+            Ok(building) if is_3d.0 => crate::config::ROOM_WALL_HEIGHT.min(building.wall_height),
+            _ => 0.0,
+        };
+
+        let polygon = &room.polygon;
+        for i in 0..polygon.len() {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+
+            gizmos.line(Vec3::new(a.x, 0.01, a.y), Vec3::new(b.x, 0.01, b.y), color);
+            if h > 0.0 {
+                gizmos.line(Vec3::new(a.x, h, a.y), Vec3::new(b.x, h, b.y), color);
+                gizmos.line(Vec3::new(a.x, 0.0, a.y), Vec3::new(a.x, h, a.y), color);
+            }
+        }
+    }
+}
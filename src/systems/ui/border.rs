@@ -4,6 +4,7 @@ use crate::systems::mesh::{EditMode, GenerationMode};
 
 // screen border thing
 // visual indicator to tell user that they are in edit mode
+#[allow(clippy::collapsible_if)]
 pub fn screen_border(
     mut contexts: EguiContexts,
     generation_mode: Res<GenerationMode>,
@@ -104,7 +105,7 @@ pub fn screen_border(
                                 egui::Rect::from_min_size(egui::pos2(screen_rect.width() - border_width, 0.0), egui::vec2(border_width, screen_rect.height())),
                                 0.0, color);
                         }
-                        EditMode::Boundary => {
+                        EditMode::Boundary | EditMode::Blocks => {
                             // solid white border
                             let color = egui::Color32::WHITE;
                             
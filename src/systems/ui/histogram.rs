@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use crate::systems::mesh::poly;
+use crate::systems::mesh::town::Building;
+
+// building footprint area distribution, recomputed whenever buildings change
+#[derive(Resource, Default)]
+pub struct AreaHistogram {
+    pub bins: Vec<usize>,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub median: f32,
+    pub count: usize,
+}
+
+pub fn update_area_histogram(
+    mut histogram: ResMut<AreaHistogram>,
+    buildings: Query<&Building>,
+    changed: Query<(), Changed<Building>>,
+    mut removed: RemovedComponents<Building>,
+) {
+    // regeneration spawns new buildings, carving edits/despawns them
+    let removed_any = removed.read().count() > 0;
+    if changed.is_empty() && !removed_any {
+        return;
+    }
+
+    let mut areas: Vec<f32> = buildings.iter()
+        .map(|building| poly::utils::polygon_area(&building.footprint).abs())
+        .collect();
+    if areas.is_empty() {
+        *histogram = AreaHistogram::default();
+        return;
+    }
+    areas.sort_by(|a, b| a.total_cmp(b));
+
+    let count = areas.len();
+    let min = areas[0];
+    let max = areas[count - 1];
+    let mean = areas.iter().sum::<f32>() / count as f32;
+    let median = if count.is_multiple_of(2) {
+        (areas[count / 2 - 1] + areas[count / 2]) * 0.5
+    } else {
+        areas[count / 2]
+    };
+
+    // equal width bins over min..max, max falls in the last bin
+    let bin_count = crate::config::HISTOGRAM_BINS;
+    let width = (max - min).max(f32::EPSILON) / bin_count as f32;
+    let mut bins = vec![0; bin_count];
+    for area in &areas {
+        let bin = (((area - min) / width) as usize).min(bin_count - 1);
+        bins[bin] += 1;
+    }
+
+    *histogram = AreaHistogram { bins, min, max, mean, median, count };
+}
+
+// bars drawn by hand, mean (red) and median (yellow) as vertical markers
+pub fn render_area_histogram(
+    histogram: Res<AreaHistogram>,
+    mut contexts: EguiContexts,
+) {
+    if let Ok(ctx) = contexts.ctx_mut() {
+        egui::Window::new("Building Areas")
+            .default_open(false)
+            .default_pos(egui::pos2(340.0, 60.0))
+            .resizable(false)
+            .show(ctx, |ui| {
+                if histogram.count == 0 {
+                    ui.label("No buildings");
+                    return;
+                }
+
+                let (response, painter) = ui.allocate_painter(egui::vec2(280.0, 120.0), egui::Sense::hover());
+                let rect = response.rect;
+                painter.rect_filled(rect, 2.0, egui::Color32::from_rgb(30, 33, 40));
+
+                let tallest = histogram.bins.iter().copied().max().unwrap_or(1).max(1) as f32;
+                let bar_width = rect.width() / histogram.bins.len() as f32;
+                for (i, &bin) in histogram.bins.iter().enumerate() {
+                    let height = rect.height() * bin as f32 / tallest;
+                    let x = rect.left() + i as f32 * bar_width;
+                    painter.rect_filled(
+                        egui::Rect::from_min_max(egui::pos2(x + 1.0, rect.bottom() - height), egui::pos2(x + bar_width - 1.0, rect.bottom())),
+                        0.0,
+                        egui::Color32::from_rgb(90, 130, 190),
+                    );
+                }
+
+                // area -> x within the plot
+                let span = (histogram.max - histogram.min).max(f32::EPSILON);
+                let to_x = |area: f32| rect.left() + rect.width() * (area - histogram.min) / span;
+                for (value, color) in [
+                    (histogram.mean, egui::Color32::from_rgb(220, 80, 80)),
+                    (histogram.median, egui::Color32::from_rgb(230, 200, 60)),
+                ] {
+                    let x = to_x(value);
+                    painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], egui::Stroke::new(2.0, color));
+                }
+
+                // hovered bar
+                if let Some(pointer) = response.hover_pos() {
+                    let bin = (((pointer.x - rect.left()) / bar_width) as usize).min(histogram.bins.len() - 1);
+                    let bin_span = span / histogram.bins.len() as f32;
+                    let from = histogram.min + bin as f32 * bin_span;
+                    response.on_hover_text(format!("{:.1} - {:.1} m²: {}", from, from + bin_span, histogram.bins[bin]));
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:.1} m²", histogram.min));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(format!("{:.1} m²", histogram.max));
+                    });
+                });
+                ui.label(format!("Buildings: {}", histogram.count));
+                ui.colored_label(egui::Color32::from_rgb(220, 80, 80), format!("Mean: {:.1} m²", histogram.mean));
+                ui.colored_label(egui::Color32::from_rgb(230, 200, 60), format!("Median: {:.1} m²", histogram.median));
+            });
+    }
+}
@@ -42,6 +42,32 @@ impl Default for GenerationModeIndicator {
 #[derive(Event)]
 pub struct GenerationModeChangeEvent(pub GenerationMode);
 
+// transient toast for one-off results (exports etc.)
+#[derive(Resource)]
+pub struct Notification {
+    pub message: String,
+    pub is_error: bool,
+    pub timer: f32,
+    pub duration: f32,
+}
+
+impl Default for Notification {
+    fn default() -> Self {
+        Self {
+            message: String::new(),
+            is_error: false,
+            timer: 0.0,
+            duration: 3.0,
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct NotificationEvent {
+    pub message: String,
+    pub is_error: bool,
+}
+
 pub fn update_mode_indicator(
     mut mode_indicator: ResMut<ModeIndicator>,
     mut events: EventReader<ModeChangeEvent>,
@@ -78,6 +104,26 @@ pub fn update_generation_mode_indicator(
     }
 }
 
+pub fn update_notification(
+    mut notification: ResMut<Notification>,
+    mut events: EventReader<NotificationEvent>,
+    time: Res<Time>,
+) {
+    // latest one wins if several arrive in the same frame
+    for event in events.read() {
+        notification.message = event.message.clone();
+        notification.is_error = event.is_error;
+        notification.timer = notification.duration;
+    }
+
+    if notification.timer > 0.0 {
+        notification.timer -= time.delta_secs();
+        if notification.timer < 0.0 {
+            notification.timer = 0.0;
+        }
+    }
+}
+
 // submode indicator
 pub fn render_mode_indicator(
     mode_indicator: Res<ModeIndicator>,
@@ -102,6 +148,7 @@ pub fn render_mode_indicator(
             EditMode::Circumcenters => ("CIRCUMCENTERS", egui::Color32::from_rgb(136, 46, 217)),
             EditMode::Roads => ("ROADS", egui::Color32::from_rgb(60, 140, 80)),
             EditMode::Boundary => ("BOUNDARY", egui::Color32::from_rgb(180, 60, 60)),
+            EditMode::Blocks => ("BLOCKS", egui::Color32::from_rgb(190, 120, 40)),
         };
         
         egui::Area::new(egui::Id::new(format!("mode_indicator_{:?}", mode_indicator.mode)))
@@ -230,4 +277,46 @@ pub fn render_generation_mode_indicator(
                 });
             });
     }
-}
\ No newline at end of file
+}
+
+// toast at the bottom of the screen, red for errors
+pub fn render_notification(
+    notification: Res<Notification>,
+    mut contexts: EguiContexts,
+) {
+    if notification.timer <= 0.0 {
+        return;
+    }
+
+    if let Ok(ctx) = contexts.ctx_mut() {
+        let alpha = (notification.timer / notification.duration).clamp(0.0, 1.0);
+
+        let bg_color = if notification.is_error {
+            egui::Color32::from_rgb(180, 60, 60)
+        } else {
+            egui::Color32::from_rgb(40, 44, 52)
+        };
+
+        egui::Area::new(egui::Id::new("notification"))
+            .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -40.0))
+            .show(ctx, |ui| {
+                let frame = egui::Frame::new()
+                    .fill(egui::Color32::from_rgba_unmultiplied(
+                        bg_color.r(), bg_color.g(), bg_color.b(),
+                        (220.0 * alpha) as u8
+                    ))
+                    .stroke(egui::Stroke::new(
+                        1.5,
+                        egui::Color32::from_rgba_unmultiplied(255, 255, 255, (180.0 * alpha) as u8)
+                    ))
+                    .inner_margin(egui::Margin::symmetric(12, 6))
+                    .corner_radius(egui::CornerRadius::same(6));
+
+                frame.show(ui, |ui| {
+                    ui.label(egui::RichText::new(&notification.message)
+                        .size(14.0)
+                        .color(egui::Color32::from_rgba_unmultiplied(255, 255, 255, (255.0 * alpha) as u8)));
+                });
+            });
+    }
+}
@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use crate::systems::render::{attribute_ramp, ramp_position, ColorBy};
+
+// range of the active building colour overlay, set by the render settings plugin when it recolours
+#[derive(Resource)]
+pub struct AttributeLegend {
+    pub color_by: ColorBy,
+    pub range: Option<(f32, f32)>, // min, max over the current buildings, None when the overlay is off
+}
+
+impl Default for AttributeLegend {
+    fn default() -> Self {
+        Self { color_by: ColorBy::Default, range: None }
+    }
+}
+
+// gradient bar with the range underneath, only shown while an overlay is active
+pub fn render_attribute_legend(
+    legend: Res<AttributeLegend>,
+    mut contexts: EguiContexts,
+) {
+    let Some((min, max)) = legend.range else { return };
+    if let Ok(ctx) = contexts.ctx_mut() {
+        egui::Window::new("Legend")
+            .default_pos(egui::pos2(340.0, 260.0))
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(legend.color_by.label());
+
+                let (response, painter) = ui.allocate_painter(egui::vec2(220.0, 16.0), egui::Sense::hover());
+                let rect = response.rect;
+                // one column per pixel, classed attributes come out as flat bands
+                let steps = rect.width() as usize;
+                for step in 0..steps {
+                    let t = ramp_position(step as f32 / steps.max(1) as f32, legend.color_by.classes());
+                    let [r, g, b, _] = attribute_ramp(t).to_srgba().to_u8_array();
+                    let x = rect.left() + step as f32;
+                    painter.rect_filled(
+                        egui::Rect::from_min_max(egui::pos2(x, rect.top()), egui::pos2(x + 1.0, rect.bottom())),
+                        0.0,
+                        egui::Color32::from_rgb(r, g, b),
+                    );
+                }
+
+                let unit = legend.color_by.unit();
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:.1}{}", min, unit));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(format!("{:.1}{}", max, unit));
+                    });
+                });
+            });
+    }
+}
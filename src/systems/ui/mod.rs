@@ -1,23 +1,139 @@
 use bevy::prelude::*;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin}; // fps
 use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
-use crate::systems::mesh::{Seed, Params, GenerationMode, EditMode, RegenerateEvent, SkeletonData};
-use crate::systems::export::ExportEvent;
+use crate::systems::mesh::{Seed, Params, GenerationMode, EditMode, RegenerateEvent, RegularizeBoundaryEvent, CarveRoadEvent, FitBoundaryEvent, JumpToIssueEvent, RerollBlockEvent, GeneratorWeightEvent, RoadWidthEvent, SelectedPoint, EditEvents, SkeletonData, RandomParam, ParamLocks, SteppedParam, UvMode, RoofStyle, SubdivisionStrategy, PointDistribution, BlockOverrides};
+use crate::config::*;
+use crate::systems::import::{ImportBoundaryEvent, ImportGeneratorsEvent};
+use crate::systems::persistence::{SaveSceneEvent, LoadSceneEvent, PresetStore};
+use crate::systems::export::{ExportEvent, ExportFormat, ExportCentroidsEvent, ExportJsonEvent, ExportGeoJsonEvent, ExportPlanEvent, ExportMorphEvent, ExportTilesEvent, ExportEvents};
+use crate::systems::render::{ColorBy, RenderSettings, WireframeStyle, MSAA_OPTIONS};
+use crate::systems::grid::GridConfig;
+use bevy::ecs::system::SystemParam;
 
 pub mod indicator;
 pub mod border;
+pub mod histogram;
+pub mod legend;
+pub mod stats;
 
 // re-export the main items that other modules need
 pub use indicator::{ModeIndicator, ModeChangeEvent, GenerationModeIndicator, GenerationModeChangeEvent};
 pub use indicator::{update_mode_indicator, render_mode_indicator, update_generation_mode_indicator, render_generation_mode_indicator};
+pub use indicator::{Notification, NotificationEvent, update_notification, render_notification};
+pub use histogram::{AreaHistogram, update_area_histogram, render_area_histogram};
+pub use legend::{AttributeLegend, render_attribute_legend};
+pub use stats::{TownStats, update_town_stats};
 pub use border::screen_border;
 
 #[derive(Resource)]
 pub struct GizmosVisible(pub bool);
 
+// view settings: 2D/3D, render options, gizmo layers and the grid
+#[derive(SystemParam)]
+pub struct SceneSettings<'w> {
+    pub is_3d: ResMut<'w, Is3D>,
+    pub render: ResMut<'w, RenderSettings>,
+    pub gizmo_layers: ResMut<'w, GizmoLayers>,
+    pub grid: ResMut<'w, GridConfig>,
+}
+
 #[derive(Resource)]
 pub struct Is3D(pub bool);
 
+// rolling frame-time graph under the fps counter, F3 or clicking the counter toggles it
+#[derive(Resource, Default)]
+pub struct ShowFrameGraph(pub bool);
+
+// per-layer toggles for the debug gizmo overlay
+#[derive(Resource)]
+pub struct GizmoLayers {
+    pub generators: bool,
+    pub circumcenters: bool,
+    pub cell_edges: bool,
+    pub road: bool,
+    pub road_corridor: bool, // road outlined at its carved width
+    pub boundary: bool,
+    pub generator_stars: bool, // generator -> cell vertex lines, off by default
+}
+
+impl Default for GizmoLayers {
+    fn default() -> Self {
+        Self {
+            generators: true,
+            circumcenters: true,
+            cell_edges: true,
+            road: true,
+            road_corridor: true,
+            boundary: true,
+            generator_stars: false,
+        }
+    }
+}
+
+// how the seed is shown in the UI, plus the paste/entry buffer
+#[derive(Resource, Default)]
+pub struct SeedDisplay {
+    pub hex: bool,
+    pub input: String,
+}
+
+// format a seed for display/copying
+pub fn format_seed(seed: u64, hex: bool) -> String {
+    if hex {
+        format!("0x{:016X}", seed)
+    } else {
+        seed.to_string()
+    }
+}
+
+// parse a pasted seed, decimal or 0x-prefixed hex
+pub fn parse_seed(text: &str) -> Option<u64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+// parameter nudged by +/-, index into SteppedParam::ALL, cycled with [ and ]
+#[derive(Resource, Default)]
+pub struct FocusedParam(pub usize);
+
+impl FocusedParam {
+    pub fn param(&self) -> SteppedParam {
+        SteppedParam::ALL[self.0 % SteppedParam::ALL.len()]
+    }
+
+    pub fn cycle(&mut self, offset: isize) {
+        let len = SteppedParam::ALL.len() as isize;
+        self.0 = (self.0 as isize + offset).rem_euclid(len) as usize;
+    }
+}
+
+// whether parameter edits rebuild right away or wait for Apply
+// pending is set by edits made while auto-apply is off, any regeneration clears it
+#[derive(Resource)]
+pub struct ApplyMode {
+    pub auto_apply: bool,
+    pub pending: bool,
+}
+
+impl Default for ApplyMode {
+    fn default() -> Self {
+        Self { auto_apply: true, pending: false }
+    }
+}
+
+// state only the side panel uses: seed entry, focused param, apply mode, presets and town stats
+#[derive(SystemParam)]
+pub struct PanelState<'w> {
+    pub seed_display: ResMut<'w, SeedDisplay>,
+    pub focused_param: ResMut<'w, FocusedParam>,
+    pub apply_mode: ResMut<'w, ApplyMode>,
+    pub presets: ResMut<'w, PresetStore>,
+    pub town_stats: Res<'w, TownStats>,
+}
+
 // #[derive(Resource)]
 // pub struct RoofsVisible(pub bool);
 
@@ -29,14 +145,24 @@ impl Plugin for UIPlugin {
         app
             .insert_resource(GizmosVisible(false))
             .insert_resource(Is3D(true))
+            .insert_resource(GizmoLayers::default())
+            .insert_resource(SeedDisplay::default())
+            .insert_resource(FocusedParam::default())
+            .insert_resource(ApplyMode::default())
+            .insert_resource(ShowFrameGraph::default())
             .insert_resource(ModeIndicator::default())
             .insert_resource(GenerationModeIndicator::default())
+            .insert_resource(Notification::default())
+            .insert_resource(AreaHistogram::default())
+            .insert_resource(TownStats::default())
+            .insert_resource(AttributeLegend::default())
             // .insert_resource(RoofsVisible(true))
             .insert_resource(GenerationMode::default())
             .add_event::<ModeChangeEvent>()
             .add_event::<indicator::GenerationModeChangeEvent>()
-            .add_systems(Update, (key_input, update_mode_indicator, update_generation_mode_indicator))
-            .add_systems(EguiPrimaryContextPass, (ui_main, fps, screen_border, render_mode_indicator, render_generation_mode_indicator)); // UI rendering here
+            .add_event::<NotificationEvent>()
+            .add_systems(Update, (key_input, param_stepping, clear_pending_changes, update_mode_indicator, update_generation_mode_indicator, update_notification, update_area_histogram, update_town_stats))
+            .add_systems(EguiPrimaryContextPass, (ui_main, fps, screen_border, render_mode_indicator, render_generation_mode_indicator, render_notification, render_area_histogram, render_attribute_legend)); // UI rendering here
     }
 }
 
@@ -47,7 +173,12 @@ fn key_input(
     mut gen_mode_events: EventWriter<indicator::GenerationModeChangeEvent>,
     mut edit_mode_events: EventWriter<ModeChangeEvent>,
     edit_mode: Res<EditMode>,
+    mut show_frame_graph: ResMut<ShowFrameGraph>,
 ) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        show_frame_graph.0 = !show_frame_graph.0;
+    }
+
     if keyboard_input.just_pressed(KeyCode::Tab) {
         *generation_mode = match *generation_mode {
             GenerationMode::Auto => GenerationMode::Manual,
@@ -67,6 +198,66 @@ fn key_input(
     }
 }
 
+// [ / ] cycle the focused parameter, +/- step it and regenerate
+#[allow(clippy::too_many_arguments)]
+fn param_stepping(
+    mut contexts: EguiContexts,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut focused: ResMut<FocusedParam>,
+    mut params: ResMut<Params>,
+    current_seed: Res<Seed>,
+    generation_mode: Res<GenerationMode>,
+    edit_mode: Res<EditMode>,
+    mut apply_mode: ResMut<ApplyMode>,
+    mut regen_events: EventWriter<RegenerateEvent>,
+) {
+    // typing into a text field
+    if let Ok(ctx) = contexts.ctx_mut() && ctx.wants_keyboard_input() {
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::BracketRight) {
+        focused.cycle(1);
+    }
+    if keyboard.just_pressed(KeyCode::BracketLeft) {
+        focused.cycle(-1);
+    }
+
+    // +/- belong to the block overrides in blocks mode
+    if *generation_mode == GenerationMode::Manual && *edit_mode == EditMode::Blocks {
+        return;
+    }
+
+    let steps = if keyboard.just_pressed(KeyCode::Equal) || keyboard.just_pressed(KeyCode::NumpadAdd) {
+        1
+    } else if keyboard.just_pressed(KeyCode::Minus) || keyboard.just_pressed(KeyCode::NumpadSubtract) {
+        -1
+    } else {
+        return;
+    };
+
+    // bypass so an unchanged value at the limit doesn't look like an edit
+    if params.bypass_change_detection().nudge(focused.param(), steps) {
+        params.set_changed();
+        if apply_mode.auto_apply {
+            regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
+        } else {
+            apply_mode.pending = true;
+        }
+    }
+}
+
+// any regeneration picks up the current params, so nothing is pending afterwards
+fn clear_pending_changes(
+    mut events: EventReader<RegenerateEvent>,
+    mut apply_mode: ResMut<ApplyMode>,
+) {
+    if events.read().count() > 0 && apply_mode.pending {
+        apply_mode.pending = false;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ui_main(
     mut contexts: EguiContexts,
     current_seed: Res<Seed>,
@@ -74,12 +265,20 @@ fn ui_main(
     mut regen_events: EventWriter<RegenerateEvent>,
     // _clear_events: EventWriter<ClearEvent>,
     // _relax_events: EventWriter<RelaxEvent>,
-    mut export_events: EventWriter<ExportEvent>,
+    mut export_events: ExportEvents,
     generation_mode: Res<GenerationMode>,
     edit_mode: Res<EditMode>,
-    mut is_3d: ResMut<Is3D>,
+    scene: SceneSettings,
     skeleton_data: Res<SkeletonData>,
+    mut edit_events: EditEvents,
+    mut param_locks: ResMut<ParamLocks>,
+    mut block_overrides: ResMut<BlockOverrides>,
+    panel: PanelState,
+    selected_point: Res<SelectedPoint>,
 ) {
+    let SceneSettings { mut is_3d, render: mut render_settings, mut gizmo_layers, mut grid } = scene;
+    let PanelState { mut seed_display, mut focused_param, mut apply_mode, mut presets, town_stats } = panel;
+
     if let Ok(ctx) = contexts.ctx_mut() {
         egui::SidePanel::left("config_panel")
             .default_width(200.0)
@@ -133,17 +332,196 @@ fn ui_main(
                     .changed() {
                     regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
                 }
+                ui.checkbox(&mut params.keep_ghost, "Ghost Previous")
+                    .on_hover_text("Keep the previous generation as a transparent overlay to compare against. It is not exported.");
+
+                // antialiasing, applied to the camera by the render settings plugin
+                ui.horizontal(|ui| {
+                    ui.label("MSAA:")
+                        .on_hover_text("Multisample antialiasing. Lower values trade edge quality for performance.");
+                    for (label, msaa) in MSAA_OPTIONS {
+                        if ui.selectable_label(render_settings.msaa == msaa, label).clicked() {
+                            render_settings.msaa = msaa;
+                        }
+                    }
+                });
+
+                // wireframe style, full triangle wireframe or just the footprint outlines
+                ui.horizontal(|ui| {
+                    ui.label("Edges:")
+                        .on_hover_text("Draw every triangle edge, or only the building outlines.");
+                    for (label, style) in [("Triangles", WireframeStyle::Triangles), ("Outline", WireframeStyle::Outline)] {
+                        if ui.selectable_label(render_settings.wireframe == style, label).clicked() {
+                            render_settings.wireframe = style;
+                        }
+                    }
+                });
+
+                // thematic colouring, the legend window shows the ramp's range
+                ui.horizontal(|ui| {
+                    ui.label("Color By:")
+                        .on_hover_text("Colour buildings by an attribute instead of their own colour, low values blue, high values red.");
+                    egui::ComboBox::from_id_salt("color_by")
+                        .selected_text(render_settings.color_by.label())
+                        .show_ui(ui, |ui| {
+                            for color_by in ColorBy::ALL {
+                                if ui.selectable_label(render_settings.color_by == color_by, color_by.label()).clicked() {
+                                    render_settings.color_by = color_by;
+                                }
+                            }
+                        });
+                });
+
+                // ambient + fill light, applied by the render settings plugin
+                egui::CollapsingHeader::new("Lighting")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                    // sliders hand out &mut every frame, only flag a change when something moved
+                    let lighting = render_settings.bypass_change_detection();
+                    let mut changed = ui.add(egui::Slider::new(&mut lighting.ambient_brightness, AMBIENT_BRIGHTNESS_RANGE)
+                        .text("Ambient Brightness"))
+                        .on_hover_text("Uniform light on every surface, lifts the shadowed sides out of pure black.")
+                        .changed();
+                    ui.horizontal(|ui| {
+                        ui.label("Ambient Color:");
+                        changed |= ui.color_edit_button_rgb(&mut lighting.ambient_color).changed();
+                    });
+                    changed |= ui.checkbox(&mut lighting.fill_light, "Fill Light")
+                        .on_hover_text("Second light opposite the sun, without shadows.")
+                        .changed();
+                    changed |= ui.add_enabled(lighting.fill_light, egui::Slider::new(&mut lighting.fill_illuminance, FILL_LIGHT_RANGE)
+                        .text("Fill Illuminance")
+                        .suffix(" lx"))
+                        .changed();
+
+                    if changed {
+                        render_settings.set_changed();
+                    }
+                });
+
+                // vsync + optional frame limiter, keeps the gpu from spinning at thousands of fps
+                egui::CollapsingHeader::new("Frame Rate")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                    let pacing = render_settings.bypass_change_detection();
+                    let mut changed = ui.checkbox(&mut pacing.vsync, "VSync")
+                        .on_hover_text("Sync frames to the display refresh rate.")
+                        .changed();
+                    changed |= ui.checkbox(&mut pacing.limit_fps, "Limit FPS")
+                        .on_hover_text("Cap the frame rate, reduces power draw and fan noise while editing.")
+                        .changed();
+                    changed |= ui.add_enabled(pacing.limit_fps, egui::Slider::new(&mut pacing.max_fps, MAX_FPS_RANGE)
+                        .text("Max FPS"))
+                        .changed();
+                    changed |= ui.checkbox(&mut pacing.building_lod, "Building LOD")
+                        .on_hover_text("Draw distant buildings as plain boxes, keeps large settlements responsive.")
+                        .changed();
+                    changed |= ui.add_enabled(pacing.building_lod, egui::Slider::new(&mut pacing.lod_distance, LOD_DISTANCE_RANGE)
+                        .text("LOD Distance (m)")
+                        .suffix(" m"))
+                        .on_hover_text("Camera distance past which a building switches to its box.")
+                        .changed();
+
+                    if changed {
+                        render_settings.set_changed();
+                    }
+                });
+
+                // grid placement, rotating it lines the grid (and snapping) up with the settlement
+                egui::CollapsingHeader::new("Scene")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                    ui.checkbox(&mut grid.enabled, "Grid");
+                    ui.checkbox(&mut grid.snap, "Snap to Grid")
+                        .on_hover_text("Snap dragged and placed points to the nearest minor grid intersection. Hold Alt to place freely.");
+                    ui.horizontal(|ui| {
+                        ui.label("Origin:");
+                        ui.add(egui::DragValue::new(&mut grid.origin.x).speed(0.1).prefix("x: "));
+                        ui.add(egui::DragValue::new(&mut grid.origin.y).speed(0.1).prefix("z: "));
+                    });
+                    let mut degrees = grid.rotation.to_degrees();
+                    if ui.add(egui::Slider::new(&mut degrees, GRID_ROTATION_RANGE)
+                        .text("Rotation")
+                        .suffix("°"))
+                        .changed() {
+                        grid.rotation = degrees.to_radians();
+                    }
+                    if ui.button("Reset Grid").clicked() {
+                        grid.origin = Vec2::ZERO;
+                        grid.rotation = 0.0;
+                    }
+                });
                 
                 ui.separator();
                 
                 // ui.label("All parameters scaled to real-world meters.");
                 ui.label("Generation Parameters:");
+
+                // auto-apply off: edits pile up until Apply
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut apply_mode.auto_apply, "Auto-apply")
+                        .on_hover_text("Rebuild as soon as a parameter changes. Turn off to set several parameters, then apply them at once.")
+                        .changed() && apply_mode.auto_apply && apply_mode.pending {
+                        regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
+                    }
+                    if !apply_mode.auto_apply {
+                        if ui.add_enabled(apply_mode.pending, egui::Button::new("Apply")).clicked() {
+                            regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
+                        }
+                        if apply_mode.pending {
+                            ui.colored_label(egui::Color32::from_rgb(230, 200, 60), "● Pending changes");
+                        }
+                    }
+                });
+
+                // keyboard stepping target
+                ui.horizontal(|ui| {
+                    if ui.small_button("<").clicked() {
+                        focused_param.cycle(-1);
+                    }
+                    if ui.small_button(">").clicked() {
+                        focused_param.cycle(1);
+                    }
+                    let param = focused_param.param();
+                    ui.label(format!("{}: {:.2}", param.label(), param.value(&params)))
+                        .on_hover_text(format!("[ / ] to pick a parameter, +/- to step it by {}.", param.step()));
+                });
                 
                 // seed
                 egui::CollapsingHeader::new("Seed")
                     .default_open(true)
                     .show(ui, |ui| {
-                    ui.label(format!("Current: {}", current_seed.0));
+                    let seed_text = format_seed(current_seed.0, seed_display.hex);
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Current: {}", seed_text));
+                        if ui.small_button("Copy")
+                            .on_hover_text("Copy the seed to the clipboard.")
+                            .clicked() {
+                            ui.ctx().copy_text(seed_text.clone());
+                        }
+                    });
+                    ui.checkbox(&mut seed_display.hex, "Show as hex");
+
+                    // pasted/typed seeds, decimal or 0x hex
+                    ui.horizontal(|ui| {
+                        let response = ui.add(egui::TextEdit::singleline(&mut seed_display.input)
+                            .hint_text("paste seed")
+                            .desired_width(140.0));
+                        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                        let parsed = parse_seed(&seed_display.input);
+                        let load = ui.add_enabled(parsed.is_some(), egui::Button::new("Load"))
+                            .on_hover_text("Regenerate with this seed, decimal or 0x hex.")
+                            .clicked();
+                        if let Some(seed) = parsed.filter(|_| load || submitted) {
+                            regen_events.write(RegenerateEvent { seed, user_edit: false });
+                            seed_display.input.clear();
+                        }
+                    });
+                    if !seed_display.input.trim().is_empty() && parse_seed(&seed_display.input).is_none() {
+                        ui.colored_label(egui::Color32::from_rgb(200, 80, 80), "Not a valid seed");
+                    }
+                    ui.add_space(4.0);
                     
                     // tint green in manual mode
                     let button_color = if *generation_mode == GenerationMode::Manual {
@@ -161,26 +539,183 @@ fn ui_main(
                         let new_seed = rand::random();
                         regen_events.write(RegenerateEvent { seed: new_seed, user_edit: false });
                     }
+
+                    // randomize the parameters themselves, seeded by the new seed
+                    if ui.button("Surprise Me")
+                        .on_hover_text("Randomize unlocked parameters within their slider ranges and regenerate.")
+                        .clicked() {
+                        let new_seed = rand::random();
+                        params.randomize(new_seed, &param_locks);
+                        regen_events.write(RegenerateEvent { seed: new_seed, user_edit: false });
+                    }
+
+                    egui::CollapsingHeader::new("Locked Parameters")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                        for param in RandomParam::ALL {
+                            let mut locked = param_locks.0.contains(&param);
+                            if ui.checkbox(&mut locked, param.label()).changed() {
+                                if locked {
+                                    param_locks.0.insert(param);
+                                } else {
+                                    param_locks.0.remove(&param);
+                                }
+                            }
+                        }
+                    });
                 });
                 
+                // named parameter snapshots, stored in presets.ron
+                egui::CollapsingHeader::new("Presets")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                    let selected_text = presets.selected.clone().unwrap_or_else(|| "Select preset".to_string());
+                    let mut picked = None;
+                    egui::ComboBox::from_id_salt("preset")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for name in presets.presets.keys() {
+                                if ui.selectable_label(presets.selected.as_ref() == Some(name), name).clicked() {
+                                    picked = Some(name.clone());
+                                }
+                            }
+                        });
+                    if let Some(name) = picked {
+                        if let Some(preset) = presets.get(&name) {
+                            *params = preset;
+                            regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
+                        }
+                        presets.name_input = name.clone();
+                        presets.selected = Some(name);
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut presets.name_input)
+                            .hint_text("preset name")
+                            .desired_width(120.0));
+                        let name = presets.name_input.trim().to_string();
+                        if ui.add_enabled(!name.is_empty(), egui::Button::new("Save Preset"))
+                            .on_hover_text("Store the current parameters under this name, overwriting a preset with the same name.")
+                            .clicked() {
+                            presets.presets.insert(name.clone(), params.clone());
+                            presets.selected = Some(name);
+                            if let Err(e) = presets.save() {
+                                eprintln!("Could not save presets: {}", e);
+                            }
+                        }
+                    });
+                    if ui.add_enabled(presets.selected.is_some(), egui::Button::new("Delete Preset"))
+                        .on_hover_text("Remove the selected preset.")
+                        .clicked() && let Some(name) = presets.selected.take() {
+                        presets.presets.remove(&name);
+                        if let Err(e) = presets.save() {
+                            eprintln!("Could not save presets: {}", e);
+                        }
+                    }
+                });
+
+                // read-only numbers for comparing parameter sets
+                egui::CollapsingHeader::new("Town Statistics")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                    for (label, value) in [
+                        ("Buildings:", town_stats.buildings.to_string()),
+                        ("Blocks:", town_stats.blocks.to_string()),
+                        ("Footprint Area:", format!("{:.0} m²", town_stats.footprint_area)),
+                        ("Average Building:", format!("{:.1} m²", town_stats.mean_area)),
+                    ] {
+                        ui.horizontal(|ui| {
+                            ui.label(label);
+                            ui.label(value);
+                        });
+                    }
+                });
+
                 // building parameters
                 egui::CollapsingHeader::new("Building Generation")
                     .default_open(true)
                     .show(ui, |ui| {
-                    regenerate |= ui.add(egui::Slider::new(&mut params.min_sq, 10.0..=25.0)
-                        .text("Min Building Area (m²)")
-                        .suffix(" m²"))
+                    regenerate |= slider_with_entry(ui, &mut params.min_sq, MIN_SQ_RANGE, "Min Building Area (m²)", " m²")
                         .on_hover_text("Minimum area required for a building plot. Smaller values create denser settlements.")
                         .changed();
-                    regenerate |= ui.add(egui::Slider::new(&mut params.grid_chaos, 0.0..=1.0)
+                    regenerate |= ui.add(egui::Slider::new(&mut params.max_sq, MAX_SQ_RANGE)
+                        .text("Max Building Area (m²)")
+                        .suffix(" m²"))
+                        .on_hover_text("Plots larger than this are always cut further, even past the recursion limit.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.min_block_area, MIN_BLOCK_AREA_RANGE)
+                        .text("Min Block Area (m²)")
+                        .suffix(" m²"))
+                        .on_hover_text("Blocks smaller than this are not subdivided. Cleans up slivers along the settlement edge.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.block_merge_prob, BLOCK_MERGE_PROB_RANGE)
+                        .text("Block Merging"))
+                        .on_hover_text("Chance that neighbouring cells join into one larger, irregular block before subdivision.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.block_setback, BLOCK_SETBACK_RANGE)
+                        .text("Block Setback (m)")
+                        .suffix(" m"))
+                        .on_hover_text("Pull every block back from its cell edges before subdivision, leaving streets twice this wide between blocks. Blocks too small to take it keep their full outline.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.courtyard_prob, COURTYARD_PROB_RANGE)
+                        .text("Courtyard Probability"))
+                        .on_hover_text("Chance that a large block is only built along its edges, leaving an empty courtyard in the middle.")
+                        .changed();
+                    regenerate |= ui.checkbox(&mut params.drop_small_blocks, "Drop Small Blocks")
+                        .on_hover_text("Remove blocks below the minimum area instead of keeping them as a single building.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.min_edge_length, MIN_EDGE_LENGTH_RANGE)
+                        .text("Min Edge Length (m)")
+                        .suffix(" m"))
+                        .on_hover_text("Footprint edges shorter than this are merged away, avoiding sliver walls at building corners.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.max_aspect_ratio, MAX_ASPECT_RATIO_RANGE)
+                        .text("Max Plot Aspect"))
+                        .on_hover_text("Plots longer than this many times their width are cut across instead of kept, and dropped when too small to cut. 0 allows any shape.")
+                        .changed();
+                    regenerate |= ui.checkbox(&mut params.irregular_footprints, "L/U Shaped Buildings")
+                        .on_hover_text("Cut a notch out of some plots, giving L shaped (corner) and U shaped (edge) footprints.")
+                        .changed();
+                    if params.irregular_footprints {
+                        regenerate |= ui.add(egui::Slider::new(&mut params.irregular_footprint_prob, IRREGULAR_FOOTPRINT_PROB_RANGE)
+                            .text("Notch Probability"))
+                            .on_hover_text("Chance that a plot gets a notch.")
+                            .changed();
+                    }
+                    regenerate |= ui.add(egui::Slider::new(&mut params.setback_margin, SETBACK_MARGIN_RANGE)
+                        .text("Setback (m)")
+                        .suffix(" m"))
+                        .on_hover_text("Inset buildings into their plots, leaving small yards between them. 0 fills the plot.")
+                        .changed();
+                    if params.setback_margin > 0.0 {
+                        regenerate |= ui.add(egui::Slider::new(&mut params.setback_jitter, SETBACK_JITTER_RANGE)
+                            .text("Setback Jitter"))
+                            .on_hover_text("Vary the setback per building and shift buildings off-centre within their plots.")
+                            .changed();
+                    }
+                    regenerate |= ui.checkbox(&mut params.show_interiors, "Show Interiors")
+                        .on_hover_text("Split large buildings into rooms, drawn as low walls visible through the roof.")
+                        .changed();
+                    if params.show_interiors {
+                        regenerate |= ui.add(egui::Slider::new(&mut params.interior_min_area, INTERIOR_MIN_AREA_RANGE)
+                            .text("Interior Min Area (m²)")
+                            .suffix(" m²"))
+                            .on_hover_text("Only buildings with a footprint at least this large get rooms.")
+                            .changed();
+                    }
+                    regenerate |= ui.add(egui::Slider::new(&mut params.grid_chaos, GRID_CHAOS_RANGE)
                         .text("Grid Irregularity"))
                         .on_hover_text("Controls how irregular the street grid becomes.")
                         .changed();
-                    regenerate |= ui.add(egui::Slider::new(&mut params.size_chaos, 0.0..=1.0)
+                    regenerate |= ui.add(egui::Slider::new(&mut params.split_bias, SPLIT_BIAS_RANGE)
+                        .text("Split Bias"))
+                        .on_hover_text("Where plots are cut along their edge: 0.5 halves them, 0.618 cuts at the golden ratio. Grid Irregularity spreads cuts around this.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.size_chaos, SIZE_CHAOS_RANGE)
                         .text("Size Variation"))
                         .on_hover_text("How much building sizes vary within plots.")
                         .changed();
-                    regenerate |= ui.add(egui::Slider::new(&mut params.empty_prob, 0.0..=0.6)
+                    regenerate |= ui.add(egui::Slider::new(&mut params.empty_prob, EMPTY_PROB_RANGE)
                         .text("Empty Plot Probability"))
                         .on_hover_text("Chance that a plot remains empty.")
                         .changed();
@@ -190,15 +725,18 @@ fn ui_main(
                 egui::CollapsingHeader::new("Alleys")
                     .default_open(true)
                     .show(ui, |ui| {
-                    regenerate |= ui.add(egui::Slider::new(&mut params.alley_width, 0.5..=1.5)
+                    regenerate |= ui.add(egui::Slider::new(&mut params.alley_width, ALLEY_WIDTH_RANGE)
                         .text("Width (m)")
                         .suffix(" m"))
                         .on_hover_text("Width of narrow alleys between buildings.")
                         .changed();
-                    regenerate |= ui.add(egui::Slider::new(&mut params.alley_chance, 0.0..=1.0)
+                    regenerate |= ui.add(egui::Slider::new(&mut params.alley_chance, ALLEY_CHANCE_RANGE)
                         .text("Frequency"))
                         .on_hover_text("How often narrow alleys appear between building blocks.")
                         .changed();
+                    regenerate |= ui.checkbox(&mut params.alley_paths, "Alley Paths")
+                        .on_hover_text("Pave the alley gaps with narrow footpaths, so they show up in the scene and in OBJ exports.")
+                        .changed();
                     
                 });
                 
@@ -206,29 +744,132 @@ fn ui_main(
                 egui::CollapsingHeader::new("Building Heights") 
                     .default_open(true)
                     .show(ui, |ui| {
-                    const MARGIN: f32 = 0.5;
+                    let (wall_min, wall_max) = (*WALL_HEIGHT_RANGE.start(), *WALL_HEIGHT_RANGE.end());
                     
-                    let max_wall_limit = (params.max_wall_height - MARGIN).max(2.0);
-                    regenerate |= ui.add(egui::Slider::new(&mut params.min_wall_height, 2.0..=max_wall_limit)
-                        .text("Min Wall Height (m)")
-                        .suffix(" m"))
+                    let max_wall_limit = (params.max_wall_height - WALL_HEIGHT_MARGIN).max(wall_min);
+                    regenerate |= slider_with_entry(ui, &mut params.min_wall_height, wall_min..=max_wall_limit, "Min Wall Height (m)", " m")
                         .on_hover_text("Minimum wall height for buildings.")
                         .changed();
                     
-                    let min_wall_limit = (params.min_wall_height + MARGIN).min(8.0);
-                    regenerate |= ui.add(egui::Slider::new(&mut params.max_wall_height, min_wall_limit..=8.0)
-                        .text("Max Wall Height (m)")
-                        .suffix(" m"))
+                    let min_wall_limit = (params.min_wall_height + WALL_HEIGHT_MARGIN).min(wall_max);
+                    regenerate |= slider_with_entry(ui, &mut params.max_wall_height, min_wall_limit..=wall_max, "Max Wall Height (m)", " m")
                         .on_hover_text("Maximum wall height for buildings.")
                         .changed();
-                    
-                    // let max_roof_limit = (params.max_roof_height - MARGIN).max(0.1);
-                    // regenerate |= ui.add(egui::Slider::new(&mut params.min_roof_height, 0.1..=max_roof_limit)
-                    //     .text("Min Roof")).changed();
-                    
-                    // let min_roof_limit = (params.min_roof_height + MARGIN).min(1.5);
-                    // regenerate |= ui.add(egui::Slider::new(&mut params.max_roof_height, min_roof_limit..=1.5)
-                    //     .text("Max Roof")).changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.center_height_falloff, CENTER_HEIGHT_FALLOFF_RANGE)
+                        .text("Center Height Falloff"))
+                        .on_hover_text("Raises buildings toward the settlement core: 1 doubles wall heights at the core, fading to none at the boundary. 0 keeps heights uniform.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.base_embed, BASE_EMBED_RANGE)
+                        .text("Base Embed (m)")
+                        .suffix(" m"))
+                        .on_hover_text("Extends walls below ground so buildings look planted rather than placed.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.eave_overhang, EAVE_OVERHANG_RANGE)
+                        .text("Eave Overhang (m)")
+                        .suffix(" m"))
+                        .on_hover_text("How far roofs reach past the walls. Reduced where it would fold over a narrow notch.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.window_density, WINDOW_DENSITY_RANGE)
+                        .text("Window Density"))
+                        .on_hover_text("Chance of a recessed window in each slot along the walls, one row per storey. Above 0 every building also gets a door on its longest wall.")
+                        .changed();
+                    regenerate |= ui.add(egui::Slider::new(&mut params.floor_height, FLOOR_HEIGHT_RANGE)
+                        .text("Floor Height (m)")
+                        .suffix(" m"))
+                        .on_hover_text("Floor-to-floor height. Walls get a texture seam at each floor line, and the floor count sets window rows and population.")
+                        .changed();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Roof:")
+                            .on_hover_text("Gable ridges run along each building's long axis (hip on near-square footprints), Hip roofs follow any footprint shape.");
+                        egui::ComboBox::from_id_salt("roof_style")
+                            .selected_text(params.roof_style.label())
+                            .show_ui(ui, |ui| {
+                                for style in RoofStyle::ALL {
+                                    if ui.selectable_label(params.roof_style == style, style.label()).clicked() && params.roof_style != style {
+                                        params.roof_style = style;
+                                        regenerate = true;
+                                    }
+                                }
+                            });
+                    });
+                    if params.roof_style != RoofStyle::Flat {
+                        let (roof_min, roof_max) = (*ROOF_HEIGHT_RANGE.start(), *ROOF_HEIGHT_RANGE.end());
+
+                        let max_roof_limit = (params.max_roof_height - ROOF_HEIGHT_MARGIN).max(roof_min);
+                        regenerate |= ui.add(egui::Slider::new(&mut params.min_roof_height, roof_min..=max_roof_limit)
+                            .text("Min Roof Height (m)")
+                            .suffix(" m"))
+                            .on_hover_text("Minimum ridge height above the walls.")
+                            .changed();
+
+                        let min_roof_limit = (params.min_roof_height + ROOF_HEIGHT_MARGIN).min(roof_max);
+                        regenerate |= ui.add(egui::Slider::new(&mut params.max_roof_height, min_roof_limit..=roof_max)
+                            .text("Max Roof Height (m)")
+                            .suffix(" m"))
+                            .on_hover_text("Maximum ridge height above the walls.")
+                            .changed();
+
+                        regenerate |= ui.checkbox(&mut params.smooth_normals, "Smooth Roof Shading")
+                            .on_hover_text("Average normals across ridges and hips for rounded looking roofs. Walls stay flat shaded.")
+                            .changed();
+                    }
+                });
+
+                // texturing
+                egui::CollapsingHeader::new("Texturing")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("UVs:")
+                            .on_hover_text("How footprint and roof UVs are mapped. World Planar tiles a texture continuously across buildings.");
+                        for (label, mode) in [("Bounds Fit", UvMode::BoundsFit), ("World Planar", UvMode::WorldPlanar)] {
+                            if ui.selectable_label(params.uv_mode == mode, label).clicked() && params.uv_mode != mode {
+                                params.uv_mode = mode;
+                                regenerate = true;
+                            }
+                        }
+                    });
+                    if params.uv_mode == UvMode::WorldPlanar {
+                        regenerate |= ui.add(egui::Slider::new(&mut params.uv_tile_size, UV_TILE_SIZE_RANGE)
+                            .text("Tile Size (m)")
+                            .suffix(" m"))
+                            .on_hover_text("World size of one texture repeat.")
+                            .changed();
+                    }
+                    regenerate |= ui.add(egui::Slider::new(&mut params.texture_meters_per_tile, TEXTURE_METERS_PER_TILE_RANGE)
+                        .text("Wall Tile Size (m)")
+                        .suffix(" m"))
+                        .on_hover_text("Real-world size of one wall texture repeat, the same on every wall regardless of length or height.")
+                        .changed();
+                    regenerate |= ui.checkbox(&mut params.wall_textures, "Wall Textures")
+                        .on_hover_text("Give every building one of the wall textures below, tinted by its colour.")
+                        .changed();
+                    if params.wall_textures {
+                        for name in WALL_TEXTURE_NAMES {
+                            ui.label(format!("• {}", name));
+                        }
+                    }
+                });
+
+                // road corridor mesh
+                egui::CollapsingHeader::new("Road Surface")
+                    .default_open(false)
+                    .show(ui, |ui| {
+                    regenerate |= ui.checkbox(&mut params.road_curbs, "Road Mesh with Curbs")
+                        .on_hover_text("Spawn the road corridor with raised curbs along both edges instead of the flat road ribbon.")
+                        .changed();
+                    if params.road_curbs {
+                        regenerate |= ui.add(egui::Slider::new(&mut params.curb_height, CURB_HEIGHT_RANGE)
+                            .text("Curb Height (m)")
+                            .suffix(" m"))
+                            .on_hover_text("Height of the curbs above the road surface.")
+                            .changed();
+                    }
+                    regenerate |= ui.add(egui::Slider::new(&mut params.road_smoothing, ROAD_SMOOTHING_RANGE)
+                        .text("Road Smoothing"))
+                        .on_hover_text("Points inserted per road segment along a Catmull-Rom curve. The placed road points stay editable, only the corridor is smoothed. 0 keeps straight segments.")
+                        .changed();
                 });
 
                 // advanced settings
@@ -236,10 +877,25 @@ fn ui_main(
                     egui::CollapsingHeader::new("Advanced")
                         .default_open(true)
                         .show(ui, |ui| {
-                        regenerate |= ui.add(egui::Slider::new(&mut params.max_recursion_depth, 1..=14)
+                        regenerate |= ui.add(egui::Slider::new(&mut params.max_recursion_depth, MAX_RECURSION_RANGE)
                             .text("Max Recursion"))
                             .on_hover_text("Maximum depth for recursive subdivision algorithms.")
                             .changed();
+
+                        ui.horizontal(|ui| {
+                            ui.label("Cuts:")
+                                .on_hover_text("Longest Edge cuts across each plot's longest edge. Bounding Box Axis cuts across the long axis of the plot's oriented bounding box, which gives squarer, more building-like plots on irregular cells.");
+                            egui::ComboBox::from_id_salt("subdivision_strategy")
+                                .selected_text(params.subdivision_strategy.label())
+                                .show_ui(ui, |ui| {
+                                    for strategy in SubdivisionStrategy::ALL {
+                                        if ui.selectable_label(params.subdivision_strategy == strategy, strategy.label()).clicked() && params.subdivision_strategy != strategy {
+                                            params.subdivision_strategy = strategy;
+                                            regenerate = true;
+                                        }
+                                    }
+                                });
+                        });
                     });
                 }
                 
@@ -255,6 +911,7 @@ fn ui_main(
                             EditMode::Circumcenters => ("CIRCUMCENTERS", egui::Color32::from_rgb(136, 46, 217), "Edit polygon vertices directly"),
                             EditMode::Roads => ("ROADS", egui::Color32::from_rgb(60, 140, 80), "Place and edit road point paths"),
                             EditMode::Boundary => ("BOUNDARY", egui::Color32::from_rgb(180, 60, 60), "Edit boundary vertices"),
+                            EditMode::Blocks => ("BLOCKS", egui::Color32::from_rgb(190, 120, 40), "Override subdivision per block"),
                         };
                         
                         let frame = egui::Frame::new()
@@ -273,6 +930,21 @@ fn ui_main(
                         ui.label("(QE to switch)");
                     });
                     
+                    // gizmo layer toggles
+                    egui::CollapsingHeader::new("Gizmo Layers")
+                        .default_open(false)
+                        .show(ui, |ui| {
+                        ui.checkbox(&mut gizmo_layers.generators, "Generators");
+                        ui.checkbox(&mut gizmo_layers.circumcenters, "Circumcenters");
+                        ui.checkbox(&mut gizmo_layers.cell_edges, "Cell Edges");
+                        ui.checkbox(&mut gizmo_layers.road, "Road");
+                        ui.add_enabled(gizmo_layers.road, egui::Checkbox::new(&mut gizmo_layers.road_corridor, "Road Corridor"))
+                            .on_hover_text("Outline the road at the width it is carved with, not just its center line.");
+                        ui.checkbox(&mut gizmo_layers.boundary, "Boundary");
+                        ui.checkbox(&mut gizmo_layers.generator_stars, "Generator Stars")
+                            .on_hover_text("Lines from each generator to the vertices of the cell it owns.");
+                    });
+
                     // instructions based on mode
                     ui.separator();
                     match *edit_mode {
@@ -283,8 +955,10 @@ fn ui_main(
                             ui.label("• Purple circles: resulting polygon vertices");
                             ui.add_space(4.0);
                             ui.label("• Left-click & drag: move generators");
+                            ui.label("• Drag on empty space: box select, then drag the group");
                             ui.label("• Right-click: place new generator");
                             ui.label("• Delete/X: remove selected generator");
+                            ui.label("• C: place settlement core");
                             
                             ui.add_space(8.0);
                             
@@ -292,12 +966,95 @@ fn ui_main(
                             egui::CollapsingHeader::new("Generator Settings")
                                 .default_open(true)
                                 .show(ui, |ui| {
-                                if ui.add(egui::Slider::new(&mut params.generator_count, 0..=80)
-                                    .text("Point Generation Count"))
+                                regenerate |= slider_with_entry(ui, &mut params.generator_count, GENERATOR_COUNT_RANGE, "Point Generation Count", "")
                                     .on_hover_text("Number of seed points to automatically generate. More points create more complex settlements.")
-                                    .changed() {
+                                    .changed();
+
+                                ui.horizontal(|ui| {
+                                    ui.label("Distribution:")
+                                        .on_hover_text("Spiral packs points in the middle and thins them outward. Poisson Disk spreads them evenly at random, Grid on a square lattice. Relaxation runs on all of them.");
+                                    egui::ComboBox::from_id_salt("point_distribution")
+                                        .selected_text(params.point_distribution.label())
+                                        .show_ui(ui, |ui| {
+                                            for distribution in PointDistribution::ALL {
+                                                if ui.selectable_label(params.point_distribution == distribution, distribution.label()).clicked() && params.point_distribution != distribution {
+                                                    params.point_distribution = distribution;
+                                                    regenerate = true;
+                                                }
+                                            }
+                                        });
+                                });
+
+                                if params.point_distribution != PointDistribution::Grid {
+                                    regenerate |= ui.add(egui::Slider::new(&mut params.density_gradient, DENSITY_GRADIENT_RANGE)
+                                        .text("Density Gradient"))
+                                        .on_hover_text("Skews where generators are sampled: positive packs them toward the middle for small central blocks, negative toward the edge. 0 samples evenly.")
+                                        .changed();
+                                }
+
+                                regenerate |= ui.add(egui::Slider::new(&mut params.relaxation_steps, RELAXATION_STEPS_RANGE)
+                                    .text("Relaxation Steps"))
+                                    .on_hover_text("Lloyd's relaxation passes, each moves generators to the middle of their cell. 0 shows the raw distribution, more evens out cell sizes.")
+                                    .changed();
+                                regenerate |= ui.add(egui::Slider::new(&mut params.relaxation_epsilon, RELAXATION_EPSILON_RANGE)
+                                    .text("Relaxation Epsilon")
+                                    .suffix(" m"))
+                                    .on_hover_text("Stops relaxing early once a pass moves all generators less than this in total. 0 always runs every step.")
+                                    .changed();
+                                // passes behind the current generators, fewer than the slider once the epsilon cut relaxation short
+                                ui.label(format!("Relaxation ran {} of {} steps", skeleton_data.relaxation_steps, params.relaxation_steps));
+
+                                regenerate |= ui.add(egui::Slider::new(&mut params.min_generator_spacing, GENERATOR_SPACING_RANGE)
+                                    .text("Min Spacing")
+                                    .suffix(" m"))
+                                    .on_hover_text("Pushes apart generators closer than this after relaxation. Avoids tiny degenerate cells.")
+                                    .changed();
+
+                                regenerate |= ui.add(egui::Slider::new(&mut params.core_bias, CORE_BIAS_RANGE)
+                                    .text("Core Bias"))
+                                    .on_hover_text("Packs generators toward the settlement core (C to place, boundary centre otherwise) and thins them toward the edge.")
+                                    .changed();
+                                if params.core_point.is_some() && ui.button("Reset Core")
+                                    .on_hover_text("Use the boundary centre as the core again.")
+                                    .clicked() {
+                                    params.core_point = None;
                                     regenerate = true;
-                                    regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
+                                }
+
+                                // power diagram weight of the selected generator
+                                if let Some(generator) = selected_point.0.filter(|&idx| idx < skeleton_data.generator_points.len()) {
+                                    let mut weight = skeleton_data.generator_weight(generator);
+                                    let response = ui.add(egui::Slider::new(&mut weight, GENERATOR_WEIGHT_RANGE)
+                                        .text("Selected Weight"))
+                                        .on_hover_text("Grows the selected generator's cell at its neighbours' expense. Equal weights give the plain diagram.");
+                                    if response.changed() || response.drag_stopped() {
+                                        edit_events.generator_weight.write(GeneratorWeightEvent { generator, weight, release: !response.dragged() });
+                                    }
+                                }
+
+                                if ui.button("Fit Boundary to Points")
+                                    .on_hover_text("Replace the boundary with the convex hull of the current generators.")
+                                    .clicked() {
+                                    edit_events.fit_boundary.write(FitBoundaryEvent);
+                                }
+
+                                // external point data instead of pgen
+                                ui.add_space(4.0);
+                                ui.horizontal(|ui| {
+                                    ui.label("File:");
+                                    ui.add(egui::TextEdit::singleline(&mut edit_events.import_settings.generators_file)
+                                        .hint_text("generators.csv")
+                                        .desired_width(140.0));
+                                });
+                                ui.checkbox(&mut edit_events.import_settings.relax_generators, "Relax Imported Points")
+                                    .on_hover_text("Run relaxation on the imported points, evening out their spacing.");
+                                if ui.button("Import Generators")
+                                    .on_hover_text("Load generator positions from a CSV of x,z lines and build the diagram around them.")
+                                    .clicked() {
+                                    edit_events.import_generators.write(ImportGeneratorsEvent {
+                                        filename: edit_events.import_settings.generators_file.trim().to_string(),
+                                        relax: edit_events.import_settings.relax_generators,
+                                    });
                                 }
                             });
                         }
@@ -308,6 +1065,8 @@ fn ui_main(
                             ui.label("• Blue squares: original generators (reference)");
                             ui.add_space(4.0);
                             ui.label("• Left-click & drag: move vertices");
+                            ui.label("• Drag on empty space: box select, then drag the group");
+                            ui.label("• Delete/X: remove selected vertex");
                             
                             ui.add_space(8.0);
                             
@@ -315,7 +1074,7 @@ fn ui_main(
                             egui::CollapsingHeader::new("Voronoi Quality")
                                 .default_open(true)
                                 .show(ui, |ui| {
-                                regenerate |= ui.add(egui::Slider::new(&mut params.circumcenter_merge_threshold, 0.01..=3.0)
+                                regenerate |= ui.add(egui::Slider::new(&mut params.circumcenter_merge_threshold, MERGE_THRESHOLD_RANGE)
                                     .text("Merge Threshold (m)")
                                     .suffix(" m"))
                                     .on_hover_text("Merges block vertices closer than this distance.")
@@ -336,9 +1095,30 @@ fn ui_main(
                             ui.label("• Green lines: road segments");
                             ui.add_space(4.0);
                             ui.label("• Left-click & drag: select and move road points");
+                            ui.label("• Drag on empty space: box select, then drag the group");
                             ui.label("• Right-click: place new road point");
                             ui.label("• Delete/X: remove selected point");
                             ui.label("• Backspace: Remove last point");
+
+                            // corridor width at the selected road point
+                            if let Some(point) = selected_point.0.filter(|&idx| idx < skeleton_data.road_path.len()) {
+                                ui.add_space(4.0);
+                                let mut width = skeleton_data.road_width(point);
+                                let response = ui.add(egui::Slider::new(&mut width, ROAD_WIDTH_RANGE)
+                                    .text("Selected Width (m)")
+                                    .suffix(" m"))
+                                    .on_hover_text("Corridor width at the selected road point. Segments take the mean of their two ends.");
+                                if response.changed() || response.drag_stopped() {
+                                    edit_events.road_width.write(RoadWidthEvent { point, width, release: !response.dragged() });
+                                }
+                            }
+
+                            ui.add_space(8.0);
+                            if ui.button("Apply Carve")
+                                .on_hover_text("Cut the road corridor out of the existing buildings without regenerating.")
+                                .clicked() {
+                                edit_events.carve.write(CarveRoadEvent);
+                            }
                         }
                         EditMode::Boundary => {
                             ui.label("Boundary Mode:");
@@ -352,6 +1132,9 @@ fn ui_main(
                             ui.label("• Red lines: boundary polygon edges");
                             ui.add_space(4.0);
                             ui.label("• Left-click & drag: move boundary vertices");
+                            ui.label("• Drag on empty space: box select, then drag the group");
+                            ui.label("• Right-click on an edge: insert a vertex");
+                            ui.label(format!("• Delete/X: remove selected vertex (minimum {})", BOUNDARY_VERTEX_RANGE.start()));
                             
                             ui.add_space(8.0);
                             
@@ -364,34 +1147,99 @@ fn ui_main(
                                         .on_hover_text("Number of vertices in the settlement boundary.");
                                     ui.label(params.boundary_vertex_count.to_string());
                                     if ui.button("-")
-                                        .on_hover_text("Reduce boundary vertices (minimum 4)")
-                                        .clicked() && params.boundary_vertex_count > 4 {
+                                        .on_hover_text(format!("Reduce boundary vertices (minimum {})", BOUNDARY_VERTEX_RANGE.start()))
+                                        .clicked() && params.boundary_vertex_count > *BOUNDARY_VERTEX_RANGE.start() {
                                         params.boundary_vertex_count -= 1;
                                         regenerate = true;
                                     }
                                     if ui.button("+")
-                                        .on_hover_text("Add boundary vertices (maximum 12)")
-                                        .clicked() && params.boundary_vertex_count < 12 {
+                                        .on_hover_text(format!("Add boundary vertices (maximum {})", BOUNDARY_VERTEX_RANGE.end()))
+                                        .clicked() && params.boundary_vertex_count < *BOUNDARY_VERTEX_RANGE.end() {
                                         params.boundary_vertex_count += 1;
                                         regenerate = true;
                                     }
                                 });
-                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_scale, 30.0..=150.0)
-                                    .text("Settlement Radius (m)")
-                                    .suffix(" m"))
+                                regenerate |= slider_with_entry(ui, &mut params.boundary_scale, BOUNDARY_SCALE_RANGE, "Settlement Radius (m)", " m")
                                     .on_hover_text("Overall size of the settlement boundary. Scalar")
                                     .changed();
-                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_spacing, 6.0..=24.0)
+                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_aspect, BOUNDARY_ASPECT_RANGE)
+                                    .text("Aspect (X:Z)")
+                                    .logarithmic(true))
+                                    .on_hover_text("Stretches the boundary along X (>1) or Z (<1) for elongated settlements. Area is kept the same.")
+                                    .changed();
+                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_noise_amp, BOUNDARY_NOISE_AMP_RANGE)
+                                    .text("Wobble"))
+                                    .on_hover_text("How far the outline bulges in and out, as a fraction of the radius.")
+                                    .changed();
+                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_noise_freq, BOUNDARY_NOISE_FREQ_RANGE)
+                                    .text("Wobble Frequency"))
+                                    .on_hover_text("Number of lumps around the outline. Lower values give smoother, rounder bulges.")
+                                    .changed();
+                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_spacing, BOUNDARY_SPACING_RANGE)
                                     .text("Generator Spacing (m)")
                                     .suffix(" m"))
                                     .on_hover_text("Distance of boundary generators from one another.")
                                     .changed();
-                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_inner_offset, 0.5..=2.0)
+                                regenerate |= ui.add(egui::Slider::new(&mut params.boundary_inner_offset, BOUNDARY_INNER_OFFSET_RANGE)
                                     .text("Inner Offset (m)")
                                     .suffix(" m"))
                                     .on_hover_text("Distance of boundary generators from edge.")
                                     .changed();
+
+                                if ui.button("Regularize Boundary")
+                                    .on_hover_text("Reset the boundary to a regular polygon, discarding vertex edits.")
+                                    .clicked() {
+                                    edit_events.regularize.write(RegularizeBoundaryEvent);
+                                }
+
+                                // real site outline instead of the generated one
+                                ui.add_space(4.0);
+                                ui.horizontal(|ui| {
+                                    ui.label("File:");
+                                    ui.add(egui::TextEdit::singleline(&mut edit_events.import_settings.boundary_file)
+                                        .hint_text("boundary.csv")
+                                        .desired_width(140.0));
+                                });
+                                if ui.button("Import Boundary")
+                                    .on_hover_text("Load a site outline from a file of x,z lines (comma or space separated), one vertex per line in order.")
+                                    .clicked() {
+                                    edit_events.import_boundary.write(ImportBoundaryEvent {
+                                        filename: edit_events.import_settings.boundary_file.trim().to_string(),
+                                    });
+                                }
+                            });
+                        }
+                        EditMode::Blocks => {
+                            ui.label("Blocks Mode:");
+                            ui.add_space(2.0);
+                            ui.horizontal(|ui| {
+                                ui.label("Overrides:");
+                                ui.label(format!("{}", block_overrides.0.len()));
                             });
+                            ui.add_space(4.0);
+                            ui.label("• Red outline: not subdivided");
+                            ui.label("• Cyan outline: subdivided finer");
+                            ui.add_space(4.0);
+                            ui.label("• Left-click: select block");
+                            ui.label("• Space: toggle no subdivision");
+                            ui.label("• +/-: subdivide finer/coarser");
+                            ui.label("• R: re-roll block layout");
+                            ui.label("• Delete/X: reset block to global params");
+
+                            ui.add_space(8.0);
+                            if ui.add_enabled(selected_point.0.is_some(), egui::Button::new("Re-roll Block"))
+                                .on_hover_text("Subdivide the selected block again with a fresh seed, the rest of the town is kept.")
+                                .clicked() && let Some(block_idx) = selected_point.0 {
+                                edit_events.reroll_block.write(RerollBlockEvent(block_idx));
+                            }
+
+                            ui.add_space(8.0);
+                            if ui.button("Clear Overrides")
+                                .on_hover_text("Reset every block back to the global subdivision params.")
+                                .clicked() && !block_overrides.0.is_empty() {
+                                block_overrides.0.clear();
+                                regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: true });
+                            }
                         }
                     }
 
@@ -408,9 +1256,10 @@ fn ui_main(
                     // });
                     
                     // validity indicator
+                    let issues = skeleton_data.validate();
                     ui.horizontal(|ui| {
                         ui.label("Diagram valid:");
-                        let valid = skeleton_data.is_valid();
+                        let valid = issues.is_empty();
                         let status_text = if valid { "Valid" } else { "Invalid" };
                         let status_color = if valid { 
                             egui::Color32::from_rgb(34, 139, 34) 
@@ -419,28 +1268,172 @@ fn ui_main(
                         };
                         ui.label(egui::RichText::new(status_text).color(status_color));
                     });
+
+                    // list the first few problems, click one to jump to it
+                    const MAX_LISTED_ISSUES: usize = 5;
+                    for issue in issues.iter().take(MAX_LISTED_ISSUES) {
+                        let label = egui::Label::new(egui::RichText::new(format!("• {}", issue.describe())).color(egui::Color32::from_rgb(178, 34, 34)))
+                            .sense(egui::Sense::click());
+                        if ui.add(label)
+                            .on_hover_text("Click to select it and move the camera there.")
+                            .clicked() {
+                            edit_events.jump_to_issue.write(JumpToIssueEvent(*issue));
+                        }
+                    }
+                    if issues.len() > MAX_LISTED_ISSUES {
+                        ui.label(format!("... and {} more", issues.len() - MAX_LISTED_ISSUES));
+                    }
                 }
                 
+                ui.separator();
+
+                // whole skeleton (points, cells, road, boundary) to and from a RON file, keeps manual edits
+                ui.horizontal(|ui| {
+                    ui.label("Scene File:");
+                    ui.add(egui::TextEdit::singleline(&mut edit_events.scene_file.0)
+                        .hint_text("scene.ron")
+                        .desired_width(120.0));
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Save Scene")
+                        .on_hover_text("Save generators, circumcenters, cells, road and boundary, including manual edits.")
+                        .clicked() {
+                        edit_events.save_scene.write(SaveSceneEvent { filename: edit_events.scene_file.0.trim().to_string() });
+                    }
+                    if ui.button("Load Scene")
+                        .on_hover_text("Replace the skeleton with a saved one and rebuild the town from it.")
+                        .clicked() {
+                        edit_events.load_scene.write(LoadSceneEvent { filename: edit_events.scene_file.0.trim().to_string() });
+                    }
+                });
+
                 ui.separator();
                 
                 // export section
                 // ui.label("Export:");
+                ui.horizontal(|ui| {
+                    ui.label("File Name:")
+                        .on_hover_text("Prefix for exported files, \"slum\" when empty.");
+                    ui.add(egui::TextEdit::singleline(&mut export_events.settings.base_name)
+                        .hint_text("slum")
+                        .desired_width(120.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut export_events.settings.visible_only, "Visible Only")
+                        .on_hover_text("OBJ exports skip hidden meshes, so exporting in 2D view writes just the flat footprints.");
+                    ui.checkbox(&mut export_events.settings.include_footprints, "Footprints")
+                        .on_hover_text("Include the flat footprint meshes. They are there in 3D view too, underneath the buildings.");
+                });
+                ui.checkbox(&mut export_events.settings.deterministic, "Deterministic Names")
+                    .on_hover_text("Name exports after the seed instead of the current time, e.g. slum_<seed>.obj. Re-exporting a seed overwrites the same files.");
                 ui.horizontal(|ui| {
                     if ui.button("Export OBJ")
                         .on_hover_text("Export model as OBJ file, current directory")
                         .clicked() {
-                        // Generate filename with timestamp
-                        let timestamp = std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap()
-                            .as_secs();
-                        let filename = format!("slum_export_{}.obj", timestamp);
-                        export_events.write(ExportEvent { filename });
+                        let format = ExportFormat::Obj;
+                        let filename = format!("{}.{}", export_events.settings.file_stem(None, current_seed.0), format.extension());
+                        export_events.obj.write(ExportEvent { filename, format, precision: export_events.settings.precision, filter: export_events.settings.mesh_filter() });
+                    }
+                    if ui.button("Export STL")
+                        .on_hover_text("Export model as binary STL file for 3D printing (Z up), current directory")
+                        .clicked() {
+                        let format = ExportFormat::Stl;
+                        let filename = format!("{}.{}", export_events.settings.file_stem(None, current_seed.0), format.extension());
+                        export_events.obj.write(ExportEvent { filename, format, precision: export_events.settings.precision, filter: export_events.settings.mesh_filter() });
+                    }
+                    if ui.button("Export GLB")
+                        .on_hover_text("Export the town as binary glTF with its town / block / building hierarchy, current directory")
+                        .clicked() {
+                        let format = ExportFormat::Gltf;
+                        let filename = format!("{}.{}", export_events.settings.file_stem(None, current_seed.0), format.extension());
+                        export_events.obj.write(ExportEvent { filename, format, precision: export_events.settings.precision, filter: export_events.settings.mesh_filter() });
+                    }
+                    if ui.button("Export Centroids")
+                        .on_hover_text("Export building centroids (id, x, y, z, yaw) as CSV, current directory")
+                        .clicked() {
+                        let filename = format!("{}.csv", export_events.settings.file_stem(Some("centroids"), current_seed.0));
+                        export_events.centroids.write(ExportCentroidsEvent { filename, precision: export_events.settings.precision });
+                    }
+                    if ui.button("Export JSON")
+                        .on_hover_text("Export building footprints and adjacency graph as JSON, current directory")
+                        .clicked() {
+                        let filename = format!("{}.json", export_events.settings.file_stem(Some("buildings"), current_seed.0));
+                        export_events.json.write(ExportJsonEvent { filename, precision: export_events.settings.precision });
+                    }
+                    if ui.button("Export GeoJSON")
+                        .on_hover_text("Export building footprints and the boundary as GeoJSON polygons ([x, z] metres), current directory")
+                        .clicked() {
+                        let filename = format!("{}.geojson", export_events.settings.file_stem(Some("footprints"), current_seed.0));
+                        export_events.geojson.write(ExportGeoJsonEvent { filename, precision: export_events.settings.precision });
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Export Plan PNG")
+                        .on_hover_text("Render a top-down orthographic plan framed to the boundary, current directory")
+                        .clicked() {
+                        let filename = format!("{}.png", export_events.settings.file_stem(Some("plan"), current_seed.0));
+                        export_events.plan.write(ExportPlanEvent { filename, resolution: export_events.settings.plan_resolution });
+                    }
+                    ui.add(egui::Slider::new(&mut export_events.settings.plan_resolution, PLAN_RESOLUTION_RANGE)
+                        .suffix(" px")
+                        .logarithmic(true))
+                        .on_hover_text("Width and height of the plan image.");
+                });
+
+                // split into tiles for engines that stream by tile
+                ui.horizontal(|ui| {
+                    if ui.button("Export Tiles")
+                        .on_hover_text("Export one OBJ per tile (buildings binned by centroid) plus a manifest.json, into a new folder in the current directory")
+                        .clicked() {
+                        let directory = export_events.settings.file_stem(Some("tiles"), current_seed.0);
+                        export_events.tiles.write(ExportTilesEvent {
+                            directory,
+                            tile_size: export_events.settings.tile_size,
+                            precision: export_events.settings.precision,
+                            filter: export_events.settings.mesh_filter(),
+                        });
+                    }
+                    ui.add(egui::Slider::new(&mut export_events.settings.tile_size, EXPORT_TILE_SIZE_RANGE)
+                        .suffix(" m"))
+                        .on_hover_text("Edge length of the square tiles.");
+                });
+
+                // morph from the current seed to a target seed, one OBJ per step
+                ui.horizontal(|ui| {
+                    ui.label("Morph To:")
+                        .on_hover_text("Export an OBJ sequence morphing the current settlement into the one of this seed.");
+                    ui.add(egui::TextEdit::singleline(&mut export_events.settings.morph_target)
+                        .hint_text("target seed")
+                        .desired_width(120.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.add(egui::Slider::new(&mut export_events.settings.morph_steps, MORPH_STEPS_RANGE)
+                        .text("Steps"))
+                        .on_hover_text("Number of interpolation steps, writes steps + 1 files.");
+                    let target = parse_seed(&export_events.settings.morph_target);
+                    if ui.add_enabled(target.is_some(), egui::Button::new("Export Morph"))
+                        .on_hover_text("Export the morph frames as OBJ files, current directory")
+                        .clicked() {
+                        let prefix = export_events.settings.file_stem(Some("morph"), current_seed.0);
+                        export_events.morph.write(ExportMorphEvent {
+                            from_seed: current_seed.0,
+                            to_seed: target.unwrap_or(current_seed.0),
+                            steps: export_events.settings.morph_steps,
+                            prefix,
+                            precision: export_events.settings.precision,
+                        });
                     }
                 });
+                ui.horizontal(|ui| {
+                    ui.label("Decimals:")
+                        .on_hover_text("Decimal places written for exported coordinates. Fewer places give smaller files.");
+                    ui.add(egui::Slider::new(&mut export_events.settings.precision, EXPORT_PRECISION_RANGE));
+                });
                 // ui.label("Saves to current directory");
                 
                 ui.separator();
+                ui.label("F3 - Frame-time graph");
+                ui.label("Ctrl+Z / Ctrl+Y - Undo / redo point edits");
                 ui.label("ESC - Exit");
                 
                 // but only in Auto mode, manual mode preserves user points
@@ -449,16 +1442,23 @@ fn ui_main(
                 // }
 
                 // triggere regeneration on any parameter change
+                // or hold it back until Apply
                 if regenerate {
-                    regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
+                    if apply_mode.auto_apply {
+                        regen_events.write(RegenerateEvent { seed: current_seed.0, user_edit: false });
+                    } else {
+                        apply_mode.pending = true;
+                    }
                 }
             });
     }
 }
 
+#[allow(clippy::collapsible_if)]
 fn fps(
     mut contexts: EguiContexts,
     diagnostics: Res<DiagnosticsStore>,
+    mut show_frame_graph: ResMut<ShowFrameGraph>,
 ) {
     if let Ok(ctx) = contexts.ctx_mut() {
         egui::Area::new(egui::Id::new("fps_counter"))
@@ -467,12 +1467,74 @@ fn fps(
                 ui.with_layout(egui::Layout::top_down(egui::Align::RIGHT), |ui| {
                     if let Some(fps_diagnostic) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS) {
                         if let Some(fps) = fps_diagnostic.smoothed() {
-                            ui.label(egui::RichText::new(format!("{:.0}", fps))
+                            let label = egui::Label::new(egui::RichText::new(format!("{:.0}", fps))
                                 .size(26.0)
-                                .color(egui::Color32::WHITE));
+                                .color(egui::Color32::WHITE))
+                                .sense(egui::Sense::click());
+                            if ui.add(label)
+                                .on_hover_text("Click (or F3) to toggle the frame-time graph.")
+                                .clicked() {
+                                show_frame_graph.0 = !show_frame_graph.0;
+                            }
                         }
                     }
+
+                    if show_frame_graph.0
+                        && let Some(frame_time) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME) {
+                        frame_time_graph(ui, &frame_time.values().copied().collect::<Vec<f64>>());
+                    }
                 });
             });
     }
-}
\ No newline at end of file
+}
+
+// slider with a numeric field beside it, for typing exact values
+// the field clamps to the slider range, the returned response is changed when either one is
+fn slider_with_entry<Num: egui::emath::Numeric>(ui: &mut egui::Ui, value: &mut Num, range: std::ops::RangeInclusive<Num>, text: &str, suffix: &str) -> egui::Response {
+    ui.horizontal(|ui| {
+        let speed = (range.end().to_f64() - range.start().to_f64()) / 200.0;
+        let slider = ui.add(egui::Slider::new(value, range.clone()).show_value(false));
+        let entry = ui.add(egui::DragValue::new(value).range(range).speed(speed).suffix(suffix));
+        ui.label(text);
+        slider | entry
+    }).inner
+}
+
+// last frames as bars, oldest on the left, scaled to the slowest frame or the 60 fps budget
+// the line marks 60 fps so hitches stand out against it, the label gives the real slowest frame
+fn frame_time_graph(ui: &mut egui::Ui, frame_times: &[f64]) {
+    let (response, painter) = ui.allocate_painter(egui::vec2(180.0, 60.0), egui::Sense::hover());
+    let rect = response.rect;
+    painter.rect_filled(rect, 2.0, egui::Color32::from_rgba_unmultiplied(30, 33, 40, 200));
+    if frame_times.is_empty() {
+        return;
+    }
+
+    const TARGET_MS: f64 = 1000.0 / 60.0;
+    let slowest = frame_times.iter().copied().fold(0.0, f64::max);
+    // the chart never zooms in past the 60 fps line, so fast frames stay short bars
+    let scale = slowest.max(TARGET_MS);
+    let to_y = |ms: f64| rect.bottom() - rect.height() * (ms / scale) as f32;
+    let bar_width = rect.width() / frame_times.len() as f32;
+    for (i, &ms) in frame_times.iter().enumerate() {
+        let x = rect.left() + i as f32 * bar_width;
+        // red for frames at least twice the budget
+        let color = if ms > TARGET_MS * 2.0 {
+            egui::Color32::from_rgb(220, 80, 80)
+        } else {
+            egui::Color32::from_rgb(90, 130, 190)
+        };
+        painter.rect_filled(
+            egui::Rect::from_min_max(egui::pos2(x, to_y(ms)), egui::pos2(x + bar_width, rect.bottom())),
+            0.0,
+            color,
+        );
+    }
+
+    let target_y = to_y(TARGET_MS);
+    painter.line_segment([egui::pos2(rect.left(), target_y), egui::pos2(rect.right(), target_y)], egui::Stroke::new(1.0, egui::Color32::from_rgb(230, 200, 60)));
+
+    let latest = frame_times[frame_times.len() - 1];
+    ui.label(egui::RichText::new(format!("{:.1} ms (max {:.1} ms)", latest, slowest))
+        .color(egui::Color32::WHITE));
+}
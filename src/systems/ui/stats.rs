@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use crate::systems::mesh::poly;
+use crate::systems::mesh::town::{Block, Building};
+
+// summary numbers for the current town, recomputed whenever buildings or blocks change
+#[derive(Resource, Default)]
+pub struct TownStats {
+    pub buildings: usize,
+    pub blocks: usize,
+    pub footprint_area: f32, // summed building footprints (m²)
+    pub mean_area: f32,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_town_stats(
+    mut stats: ResMut<TownStats>,
+    buildings: Query<&Building>,
+    blocks: Query<&Block>,
+    changed: Query<(), Or<(Changed<Building>, Changed<Block>)>>,
+    mut removed_buildings: RemovedComponents<Building>,
+    mut removed_blocks: RemovedComponents<Block>,
+) {
+    // regeneration respawns everything, carving edits or despawns buildings
+    let removed_any = removed_buildings.read().count() + removed_blocks.read().count() > 0;
+    if changed.is_empty() && !removed_any {
+        return;
+    }
+
+    let footprint_area: f32 = buildings.iter()
+        .map(|building| poly::utils::polygon_area(&building.footprint).abs())
+        .sum();
+    let building_count = buildings.iter().len();
+    *stats = TownStats {
+        buildings: building_count,
+        blocks: blocks.iter().len(),
+        footprint_area,
+        mean_area: if building_count > 0 { footprint_area / building_count as f32 } else { 0.0 },
+    };
+}
@@ -0,0 +1,792 @@
+// unit tests
+// run with `cargo test`
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::config::*;
+use crate::systems::mesh::poly::point_gen::{pgen, poisson_points, prelax, generate_road_generators, smooth_road_path, enforce_min_spacing, generator_bounds, generate_boundary_polygon, generate_boundary_generators, generate_regular_polygon};
+use crate::systems::mesh::poly::subdivision::{bisect_poly, polygon_inset, subdivide_to_plots};
+use crate::systems::mesh::poly::utils::{obb_aspect_ratio, point_in_polygon, point_to_line_distance, polygon_area, polygon_centroid};
+use crate::systems::mesh::poly::ops;
+use crate::systems::mesh::poly::mesh_gen::{polygon_to_building, polygon_to_gable_roof, polygon_to_hip_roof, WallOpenings};
+use crate::systems::mesh::{SubdivisionStrategy, UvMode};
+use crate::systems::export::{export_gltf, export_obj, ExportMeshes, GltfHierarchy, MeshFilter};
+
+#[test]
+fn generators_respect_min_spacing() {
+    let spacing = 4.0;
+    let boundary = generate_boundary_polygon(6, 75.0, BOUNDARY_ASPECT, BOUNDARY_NOISE_FREQ, BOUNDARY_NOISE_AMP, INITIAL_SEED);
+    let fixed = generate_boundary_generators(&boundary, BOUNDARY_GENERATOR_SPACING, BOUNDARY_GENERATOR_INNER_OFFSET);
+
+    // spiral start packs the first points tightly, good stress case
+    let bounds = generator_bounds(&boundary, GENERATOR_BOUNDS_MARGIN);
+    let regular = pgen(60, bounds, SPIRAL_SPREAD, 0.0, 0.0, INITIAL_SEED);
+    let regular_count = regular.len();
+    let (relaxed, _) = prelax(regular, fixed.clone(), 4, 0.0, bounds);
+    let points = enforce_min_spacing(relaxed, regular_count, spacing);
+
+    for i in 0..regular_count {
+        for j in (i + 1)..regular_count {
+            let distance = points[i].distance(points[j]);
+            assert!(distance >= spacing, "generators {} and {} are {} apart", i, j, distance);
+        }
+    }
+
+    // fixed generators are left untouched
+    assert_eq!(&points[regular_count..], &fixed[..]);
+}
+
+#[test]
+fn relaxation_stops_once_converged() {
+    let boundary = generate_boundary_polygon(6, 75.0, BOUNDARY_ASPECT, BOUNDARY_NOISE_FREQ, BOUNDARY_NOISE_AMP, INITIAL_SEED);
+    let fixed = generate_boundary_generators(&boundary, BOUNDARY_GENERATOR_SPACING, BOUNDARY_GENERATOR_INNER_OFFSET);
+    let bounds = generator_bounds(&boundary, GENERATOR_BOUNDS_MARGIN);
+    let regular = pgen(40, bounds, SPIRAL_SPREAD, 0.0, 0.0, INITIAL_SEED);
+
+    let (full, steps) = prelax(regular.clone(), fixed.clone(), 12, 0.0, bounds);
+    assert_eq!(steps, 12);
+    let (early, early_steps) = prelax(regular.clone(), fixed.clone(), 12, 150.0, bounds);
+    assert!(early_steps < 12, "never converged");
+    // the steps that did run match the full relaxation
+    assert_eq!(early, prelax(regular, fixed, early_steps, 0.0, bounds).0);
+    assert_ne!(early, full);
+}
+
+#[test]
+fn poisson_points_keep_their_distance() {
+    let (width, height, min_dist) = (120.0, 80.0, 9.0);
+    let points = poisson_points(60, width, height, min_dist, 0.0, 0.0, INITIAL_SEED);
+    assert_eq!(points.len(), 60);
+    for (i, a) in points.iter().enumerate() {
+        assert!((0.0..width).contains(&a.x) && (0.0..height).contains(&a.z), "point {} outside the rect", a);
+        for b in &points[i + 1..] {
+            assert!(a.distance(*b) >= min_dist, "points {} and {} are {} apart", a, b, a.distance(*b));
+        }
+    }
+    assert_eq!(points, poisson_points(60, width, height, min_dist, 0.0, 0.0, INITIAL_SEED));
+}
+
+#[test]
+fn density_gradient_moves_generators_inward() {
+    // mean distance from the middle, for inward, uniform and outward gradients
+    let spread = |gradient: f32| -> [f32; 2] {
+        let poisson = poisson_points(60, 200.0, 200.0, 9.0, gradient, 75.0, INITIAL_SEED);
+        let spiral = pgen(60, Rect::new(-100.0, -100.0, 100.0, 100.0), SPIRAL_SPREAD, gradient, 75.0, INITIAL_SEED);
+        let mean = |points: &[Vec3], center: Vec2| points.iter().map(|p| p.xz().distance(center)).sum::<f32>() / points.len() as f32;
+        [mean(&poisson, Vec2::splat(100.0)), mean(&spiral, Vec2::ZERO)]
+    };
+    let (inward, uniform, outward) = (spread(1.0), spread(0.0), spread(-1.0));
+    for i in 0..2 {
+        assert!(inward[i] < uniform[i] && uniform[i] < outward[i], "{} < {} < {}", inward[i], uniform[i], outward[i]);
+    }
+}
+
+#[test]
+fn plots_respect_max_sq() {
+    let max_sq = 60.0;
+    let blocks = [
+        vec![Vec2::new(0.0, 0.0), Vec2::new(40.0, 0.0), Vec2::new(40.0, 25.0), Vec2::new(0.0, 25.0)],
+        vec![Vec2::new(0.0, 0.0), Vec2::new(80.0, 0.0), Vec2::new(80.0, 6.0), Vec2::new(0.0, 6.0)], // long thin strip
+        generate_regular_polygon(6, 20.0, 1.0),
+        generate_regular_polygon(9, 30.0, 2.5),
+    ];
+
+    for (i, block) in blocks.iter().enumerate() {
+        for (seed, strategy) in (0..20).flat_map(|seed| SubdivisionStrategy::ALL.map(|strategy| (seed, strategy))) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            // shallow recursion and high size chaos, so the forced cuts do the work
+            let plots = subdivide_to_plots(block, 15.0, max_sq, 0.8, 0.5, 1.0, 0.0, 0, &mut rng, 2, 0.5, 0.8, &mut Vec::new(), strategy, 0.0, 0.0);
+
+            assert!(!plots.is_empty());
+            for plot in &plots {
+                let area = polygon_area(plot).abs();
+                assert!(area <= max_sq, "block {} seed {} {:?}: plot area {} exceeds {}", i, seed, strategy, area, max_sq);
+            }
+        }
+    }
+}
+
+#[test]
+fn obb_cuts_keep_plots_rectangular() {
+    // rotated rectangle with a clipped corner, the longest edge is the diagonal cut
+    let frame = Vec2::from_angle(0.4);
+    let block: Vec<Vec2> = [Vec2::new(0.0, 0.0), Vec2::new(30.0, 0.0), Vec2::new(30.0, 8.0), Vec2::new(2.0, 20.0), Vec2::new(0.0, 20.0)]
+        .iter().map(|p| frame.rotate(*p)).collect();
+    let mut rng = StdRng::seed_from_u64(3);
+    let plots = subdivide_to_plots(&block, 15.0, 60.0, 0.0, 0.5, 0.0, 0.0, 0, &mut rng, 10, 0.0, 0.0, &mut Vec::new(), SubdivisionStrategy::ObbAxis, 0.0, 0.0);
+
+    let total: f32 = plots.iter().map(|plot| polygon_area(plot).abs()).sum();
+    assert!((total - polygon_area(&block).abs()).abs() < 1e-2);
+    // every cut is square to the box, only plots touching the clipped corner can be anything but a rectangle
+    let square_corners = |plot: &Vec<Vec2>| (0..plot.len()).all(|i| {
+        let (a, b, c) = (plot[i], plot[(i + 1) % plot.len()], plot[(i + 2) % plot.len()]);
+        (b - a).normalize().dot((c - b).normalize()).abs() < 1e-3
+    });
+    let rectangles = plots.iter().filter(|plot| square_corners(plot)).count();
+    assert!(rectangles * 2 > plots.len(), "{} of {} plots are rectangles", rectangles, plots.len());
+}
+
+#[test]
+fn simplified_footprints_have_no_micro_walls() {
+    let min_edge = 0.1;
+    let mut footprints = vec![
+        // square with a near-duplicate corner and a sub-centimetre notch
+        vec![
+            Vec2::new(0.0, 0.0), Vec2::new(0.004, 0.0), Vec2::new(8.0, 0.0),
+            Vec2::new(8.0, 5.0), Vec2::new(4.02, 5.0), Vec2::new(4.0, 5.01), Vec2::new(0.0, 5.0),
+        ],
+    ];
+    // real plots from subdivision
+    let block = generate_regular_polygon(7, 25.0, 1.5);
+    for seed in 0..10 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        footprints.extend(subdivide_to_plots(&block, 15.0, 60.0, 0.8, 0.5, 0.5, 0.0, 0, &mut rng, 10, 0.8, 0.8, &mut Vec::new(), SubdivisionStrategy::LongestEdge, 0.0, 0.0));
+    }
+
+    for footprint in &footprints {
+        let simplified = ops::simplify(footprint, min_edge);
+        if simplified.len() < 3 {
+            continue;
+        }
+
+        // the first 4 positions per edge are the wall quad, base edge is v1 -> v2
+        let mesh = polygon_to_building(&simplified, 3.0, 0.0, UvMode::BoundsFit, UV_TILE_SIZE, TEXTURE_METERS_PER_TILE, 0.0, None);
+        let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("building mesh has no positions");
+        };
+        for quad in positions.chunks(4).take(simplified.len()) {
+            let width = Vec3::from(quad[0]).distance(Vec3::from(quad[1]));
+            assert!(width >= min_edge, "wall quad {} wide, below {}", width, min_edge);
+        }
+    }
+}
+
+// axis aligned square, min corner at `origin`
+fn square(origin: Vec2, size: f32) -> Vec<Vec2> {
+    vec![origin, origin + Vec2::new(size, 0.0), origin + Vec2::splat(size), origin + Vec2::new(0.0, size)]
+}
+
+#[test]
+fn ops_booleans_keep_area_and_winding() {
+    let a = square(Vec2::ZERO, 4.0);
+    let total_area = |polygons: &[Vec<Vec2>]| polygons.iter().map(polygon_area).sum::<f32>();
+
+    // neighbours sharing an edge merge into one outline, either input winding
+    let neighbour: Vec<Vec2> = square(Vec2::new(4.0, 0.0), 4.0).into_iter().rev().collect();
+    let merged = ops::union(&a, &neighbour);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].len(), 4, "split points on straight runs are dropped");
+    assert!((polygon_area(&merged[0]) - 32.0).abs() < 1e-3);
+
+    // overlapping squares
+    let b = square(Vec2::splat(2.0), 4.0);
+    assert!((total_area(&ops::union(&a, &b)) - 28.0).abs() < 1e-3);
+    assert!((total_area(&ops::clip_to(&a, &b)) - 4.0).abs() < 1e-3);
+    let notched = ops::difference(&a, &b);
+    assert_eq!(notched.len(), 1);
+    assert_eq!(notched[0].len(), 6, "corner notch gives an L shape");
+    assert!((polygon_area(&notched[0]) - 12.0).abs() < 1e-3);
+
+    // hole: outer ccw plus inner cw, signed areas add up
+    let holed = ops::difference(&a, &square(Vec2::ONE, 1.0));
+    assert_eq!(holed.len(), 2);
+    assert!((total_area(&holed) - 15.0).abs() < 1e-3);
+
+    // disjoint
+    let far = square(Vec2::splat(10.0), 1.0);
+    assert_eq!(ops::union(&a, &far).len(), 2);
+    assert!(ops::clip_to(&a, &far).is_empty());
+    assert!(ops::difference(&far, &a).len() == 1);
+}
+
+#[test]
+fn ops_offsets_and_validation() {
+    let a = square(Vec2::ZERO, 4.0);
+
+    let grown = ops::offset(&a, 1.0);
+    assert!((polygon_area(&grown) - 36.0).abs() < 1e-3);
+    let shrunk = ops::inset(&a, 1.0).expect("inset of a 4 m square by 1 m");
+    assert!((polygon_area(&shrunk) - 4.0).abs() < 1e-3);
+    assert!(ops::inset(&a, 2.5).is_none(), "inset past the middle collapses");
+
+    let bowtie = vec![Vec2::ZERO, Vec2::new(2.0, 2.0), Vec2::new(2.0, 0.0), Vec2::new(0.0, 2.0)];
+    assert!(ops::is_simple(&a));
+    assert!(!ops::is_simple(&bowtie));
+    assert!(!ops::is_simple(&[Vec2::ZERO, Vec2::X, Vec2::new(2.0, 0.0)]), "collinear, no area");
+
+    let mut points = a.clone();
+    points.extend([Vec2::splat(2.0), Vec2::new(1.0, 3.0), Vec2::new(2.0, 0.0)]);
+    let hull = ops::convex_hull(&points);
+    assert_eq!(hull.len(), 4);
+    assert!(polygon_area(&hull) > 0.0);
+
+    // mitered right angle, area is center line length * width
+    let bend = ops::ribbon(&[Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)], 2.0);
+    assert_eq!(bend.len(), 6);
+    assert!((polygon_area(&bend) - 40.0).abs() < 1e-3);
+    assert!(ops::ribbon(&[Vec2::ZERO, Vec2::ZERO], 2.0).is_empty());
+}
+
+#[test]
+#[allow(clippy::type_complexity)]
+fn obj_export_applies_world_transforms() {
+    use bevy::ecs::system::SystemState;
+    use bevy::render::mesh::{Indices, PrimitiveTopology};
+    use bevy::render::render_asset::RenderAssetUsages;
+
+    let mut app = App::new();
+    app.add_plugins(TransformPlugin);
+    app.insert_resource(Assets::<Mesh>::default());
+    app.insert_resource(Assets::<StandardMaterial>::default());
+
+    let triangle = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]])
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2]));
+    let mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(triangle);
+
+    // parent building transform composed with the child mesh's own
+    let building = app.world_mut().spawn(Transform::from_xyz(10.0, 0.0, -5.0)).id();
+    app.world_mut().spawn((Mesh3d(mesh), Transform::from_xyz(0.0, 2.0, 0.0), InheritedVisibility::VISIBLE, ChildOf(building)));
+    app.update();
+
+    let mut state: SystemState<(Res<Assets<Mesh>>, Res<Assets<StandardMaterial>>, ExportMeshes)> = SystemState::new(app.world_mut());
+    let (meshes, materials, mesh_entities) = state.get(app.world());
+    let filename = std::env::temp_dir().join("slum_transform_test.obj");
+    let filename = filename.to_str().unwrap();
+    export_obj(&meshes, &materials, &mesh_entities, filename, 2, MeshFilter { visible_only: false, footprints: true }).unwrap();
+
+    let text = std::fs::read_to_string(filename).unwrap();
+    let _ = std::fs::remove_file(filename);
+    let vertices: Vec<&str> = text.lines().filter(|line| line.starts_with("v ")).collect();
+    assert_eq!(vertices, ["v 10.00 2.00 -5.00", "v 11.00 2.00 -5.00", "v 10.00 2.00 -4.00"]);
+    assert!(text.lines().any(|line| line == "f 1 2 3"));
+}
+
+#[test]
+#[allow(clippy::type_complexity)]
+fn gltf_export_keeps_the_town_hierarchy() {
+    use bevy::ecs::system::SystemState;
+    use bevy::render::mesh::{Indices, PrimitiveTopology};
+    use bevy::render::render_asset::RenderAssetUsages;
+    use crate::systems::mesh::town::{Block, Building, BuildingMesh, Town};
+
+    let mut app = App::new();
+    app.add_plugins(TransformPlugin);
+    app.insert_resource(Assets::<Mesh>::default());
+    app.insert_resource(Assets::<StandardMaterial>::default());
+
+    let triangle = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]])
+        .with_inserted_indices(Indices::U16(vec![0, 1, 2]));
+    let mesh = app.world_mut().resource_mut::<Assets<Mesh>>().add(triangle);
+    let footprint = vec![Vec2::ZERO, Vec2::X, Vec2::Y];
+
+    // spawned the way generate_town lays the entities out
+    let world = app.world_mut();
+    let town = world.spawn((Town { name: "My Town".to_string(), population: 1, seed: 0 }, Transform::default(), Visibility::default())).id();
+    let block = world.spawn((
+        Block { polygon: footprint.clone(), min_sq: 0.0, grid_chaos: 0.0, size_chaos: 0.0, empty_prob: 0.0, id: Some(0), generator: None, alleys: Vec::new() },
+        Transform::default(),
+        Visibility::default(),
+        ChildOf(town),
+    )).id();
+    let building = world.spawn((
+        Building { id: 7, footprint, wall_height: 3.0, roof_height: 0.0, floors: 1, population: 1, color: Color::WHITE, mesh: mesh.clone(), lod_mesh: mesh.clone() },
+        Transform::from_xyz(10.0, 0.0, -5.0),
+        ChildOf(block),
+    )).id();
+    world.spawn((BuildingMesh, Mesh3d(mesh), Transform::default(), InheritedVisibility::VISIBLE, ChildOf(building)));
+    app.update();
+
+    let mut state: SystemState<(Res<Assets<Mesh>>, Res<Assets<StandardMaterial>>, ExportMeshes, GltfHierarchy)> = SystemState::new(app.world_mut());
+    let (meshes, materials, mesh_entities, hierarchy) = state.get(app.world());
+    let filename = std::env::temp_dir().join("slum_hierarchy_test.glb");
+    let filename = filename.to_str().unwrap();
+    export_gltf(&meshes, &materials, &mesh_entities, &hierarchy, filename, MeshFilter { visible_only: false, footprints: true }).unwrap();
+
+    let bytes = std::fs::read(filename).unwrap();
+    let _ = std::fs::remove_file(filename);
+    assert_eq!(&bytes[0..4], b"glTF");
+    let json_length = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+    let json = std::str::from_utf8(&bytes[20..20 + json_length]).unwrap();
+
+    // children are written before their parents, so the town is the last node and the only root
+    let nodes: Vec<(&str, &str)> = json.split("{\"name\":\"").skip(1)
+        .map(|node| {
+            let name = &node[..node.find('"').unwrap()];
+            let children = node.split("\"children\":[").nth(1).map_or("", |rest| &rest[..rest.find(']').unwrap()]);
+            (name, children)
+        })
+        .collect();
+    assert_eq!(nodes, [("Walls", ""), ("Building_7", "0"), ("Block_0", "1"), ("My Town", "2")]);
+    assert!(json.contains("\"scenes\":[{\"nodes\":[3]}]"));
+    assert!(json.contains("\"translation\":[10,0,-5]"));
+}
+
+#[test]
+fn presets_load_clamped_to_slider_ranges() {
+    use crate::systems::mesh::Params;
+
+    // hand-edited preset, fields it leaves out fall back to their defaults
+    let mut params: Params = ron::from_str("(boundary_vertex_count: 40, grid_chaos: -2.0, min_wall_height: 7.9, max_wall_height: 3.0)").unwrap();
+    assert_eq!(params.generator_count, Params::default().generator_count);
+    params.clamp_to_ranges();
+
+    assert_eq!(params.boundary_vertex_count, *BOUNDARY_VERTEX_RANGE.end());
+    assert_eq!(params.grid_chaos, *GRID_CHAOS_RANGE.start());
+    assert!(params.min_wall_height + WALL_HEIGHT_MARGIN <= params.max_wall_height + 1e-6);
+    assert!(params.max_wall_height <= *WALL_HEIGHT_RANGE.end());
+}
+
+#[test]
+fn edit_history_undo_redo() {
+    use crate::systems::mesh::{EditHistory, SkeletonData};
+
+    let state = |x: f32| SkeletonData {
+        generator_points: vec![Vec3::new(x, 0.0, 0.0)],
+        generator_weights: Vec::new(),
+        points: Vec::new(),
+        cells: Vec::new(),
+        cell_generators: Vec::new(),
+        cell_adjacency: Vec::new(),
+        road_path: Vec::new(),
+        road_widths: Vec::new(),
+        road_generators: 0..0,
+        boundary_generators: 0..0,
+        relaxation_steps: 0,
+        boundary_polygon: Vec::new(),
+        boundary_vertex_offsets: Vec::new(),
+    };
+    let (a, b, c) = (state(0.0), state(1.0), state(2.0));
+
+    let mut history = EditHistory::default();
+    history.record(&a); // a -> b
+    history.record(&b); // b -> c
+    assert!(history.undo(&c) == Some(b.clone()));
+    assert!(history.undo(&b) == Some(a.clone()));
+    assert!(history.undo(&a).is_none());
+    assert!(history.redo() == Some(b.clone()));
+
+    // a new edit after an undo drops the redo branch
+    history.record(&b);
+    assert!(history.redo().is_none());
+
+    // an unmoved drag leaves no entry
+    let mut history = EditHistory::default();
+    history.begin_drag(&a);
+    history.end_drag(&a);
+    assert!(history.undo(&a).is_none());
+
+    for i in 0..EDIT_HISTORY_LIMIT + 10 {
+        history.record(&state(i as f32));
+    }
+    let mut steps = 0;
+    while history.undo(&c).is_some() {
+        steps += 1;
+    }
+    assert_eq!(steps, EDIT_HISTORY_LIMIT);
+}
+
+#[test]
+fn loaded_skeletons_keep_generator_ranges_in_bounds() {
+    use crate::systems::mesh::SkeletonData;
+    use crate::systems::persistence::{load_skeleton, save_skeleton};
+
+    let skeleton = SkeletonData {
+        generator_points: vec![Vec3::ZERO, Vec3::X, Vec3::Z],
+        generator_weights: Vec::new(),
+        points: Vec::new(),
+        cells: Vec::new(),
+        cell_generators: Vec::new(),
+        cell_adjacency: Vec::new(),
+        road_path: vec![Vec3::ZERO, Vec3::X],
+        road_widths: Vec::new(),
+        road_generators: 1..2,
+        boundary_generators: 2..3,
+        relaxation_steps: 0,
+        boundary_polygon: vec![Vec2::ZERO, Vec2::X, Vec2::Y],
+        boundary_vertex_offsets: Vec::new(),
+    };
+    let filename = std::env::temp_dir().join("slum_ranges_test.ron");
+    let filename = filename.to_str().unwrap();
+    let loads = |skeleton: &SkeletonData| {
+        save_skeleton(skeleton, filename).unwrap();
+        let loaded = load_skeleton(filename);
+        let _ = std::fs::remove_file(filename);
+        loaded.is_ok()
+    };
+
+    assert!(loads(&skeleton));
+    assert!(!loads(&SkeletonData { boundary_generators: 2..4, ..skeleton.clone() }));
+    assert!(!loads(&SkeletonData { road_path: Vec::new(), ..skeleton.clone() }));
+}
+
+#[test]
+fn gable_roof_ridge_follows_long_axis() {
+    let (wall, roof) = (3.0, 1.0);
+    let roof_positions = |footprint: &Vec<Vec2>| {
+        let mesh = polygon_to_gable_roof(footprint, wall, roof, false);
+        let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("roof mesh has no positions");
+        };
+        positions.iter().map(|p| Vec3::from(*p)).collect::<Vec<_>>()
+    };
+
+    // 10 x 4 plot, ridge along x at z = 2, running the full length
+    let positions = roof_positions(&vec![Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(10.0, 4.0), Vec2::new(0.0, 4.0)]);
+    assert!(positions.iter().all(|p| p.y >= wall - 1e-4 && p.y <= wall + roof + 1e-4));
+    let ridge: Vec<&Vec3> = positions.iter().filter(|p| p.y > wall + roof - 1e-4).collect();
+    assert!(ridge.iter().all(|p| (p.z - 2.0).abs() < 1e-4), "ridge off the long axis");
+    assert!(ridge.iter().any(|p| p.x < 1e-4) && ridge.iter().any(|p| p.x > 10.0 - 1e-4), "gable roof ridge should reach both ends");
+
+    // a square gets a hip roof, the peak only reached in the middle
+    let positions = roof_positions(&square(Vec2::ZERO, 4.0));
+    let peak: Vec<&Vec3> = positions.iter().filter(|p| p.y > wall + roof - 1e-4).collect();
+    assert!(!peak.is_empty());
+    assert!(peak.iter().all(|p| p.xz().distance(Vec2::splat(2.0)) < 1e-3), "hip roof peak off centre");
+}
+
+#[test]
+fn hip_roof_covers_concave_footprint() {
+    // L shape, the reflex corner makes the skeleton split
+    let footprint = vec![
+        Vec2::ZERO, Vec2::new(8.0, 0.0), Vec2::new(8.0, 3.0),
+        Vec2::new(3.0, 3.0), Vec2::new(3.0, 8.0), Vec2::new(0.0, 8.0),
+    ];
+    let (wall, roof) = (3.0, 1.0);
+    let mesh = polygon_to_hip_roof(&footprint, wall, roof, false);
+    let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        panic!("roof mesh has no positions");
+    };
+    let Some(bevy::render::mesh::Indices::U32(indices)) = mesh.indices() else {
+        panic!("roof mesh has no indices");
+    };
+
+    // the faces project back onto the footprint without gaps or overlaps, all facing up
+    let mut covered = 0.0;
+    for triangle in indices.chunks(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| Vec3::from(positions[i as usize]));
+        let normal = (b - a).cross(c - a);
+        assert!(normal.y >= -1e-4, "roof face points down");
+        covered += normal.y * 0.5;
+        assert!([a, b, c].iter().all(|p| p.y >= wall - 1e-4 && p.y <= wall + roof + 1e-4));
+    }
+    assert!((covered - polygon_area(&footprint)).abs() < 1e-2, "roof covers {} of {}", covered, polygon_area(&footprint));
+    assert!(positions.iter().any(|p| p[1] > wall + roof - 1e-4));
+
+    // smooth shading welds the faces' shared corners, normals still point up
+    let smooth = polygon_to_hip_roof(&footprint, wall, roof, true);
+    assert!(smooth.count_vertices() < positions.len());
+    let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(normals)) = smooth.attribute(Mesh::ATTRIBUTE_NORMAL) else {
+        panic!("roof mesh has no normals");
+    };
+    assert!(normals.iter().all(|n| n[1] > 0.0 && (Vec3::from(*n).length() - 1.0).abs() < 1e-4));
+}
+
+#[test]
+fn wall_openings_keep_buildings_manifold() {
+    let footprints = [
+        vec![Vec2::ZERO, Vec2::new(12.0, 0.0), Vec2::new(12.0, 6.0), Vec2::new(0.0, 6.0)],
+        // clockwise L shape
+        vec![Vec2::ZERO, Vec2::new(0.0, 9.0), Vec2::new(4.0, 9.0), Vec2::new(4.0, 4.0), Vec2::new(10.0, 4.0), Vec2::new(10.0, 0.0)],
+    ];
+    for footprint in &footprints {
+        let openings = WallOpenings { window_density: 1.0, seed: 7 };
+        let mesh = polygon_to_building(footprint, 6.0, 0.2, UvMode::BoundsFit, UV_TILE_SIZE, TEXTURE_METERS_PER_TILE, FLOOR_HEIGHT, Some(openings));
+        let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("building mesh has no positions");
+        };
+        let Some(bevy::render::mesh::Indices::U32(indices)) = mesh.indices() else {
+            panic!("building mesh has no indices");
+        };
+        // the plain box is 2 triangles per wall plus the caps, openings add more
+        assert!(indices.len() / 3 > 2 * footprint.len() + 2 * (footprint.len() - 2));
+
+        // closed and manifold: every directed edge is matched by exactly one reversed edge
+        let key = |i: u32| Vec3::from(positions[i as usize]).to_array().map(|c| (c * 1e4).round() as i64);
+        let mut edges = std::collections::HashMap::new();
+        for triangle in indices.chunks(3) {
+            for (a, b) in [(triangle[0], triangle[1]), (triangle[1], triangle[2]), (triangle[2], triangle[0])] {
+                *edges.entry((key(a), key(b))).or_insert(0) += 1;
+            }
+        }
+        for (&(a, b), &count) in &edges {
+            assert_eq!(count, 1, "edge {:?} -> {:?} used {} times", a, b, count);
+            assert_eq!(edges.get(&(b, a)), Some(&1), "edge {:?} -> {:?} has no twin", a, b);
+        }
+    }
+}
+
+#[test]
+fn block_inset_keeps_an_even_street_gap() {
+    // clockwise pentagon with a sharp corner, mitres would be capped there by ops::inset
+    let block = vec![Vec2::new(0.0, 0.0), Vec2::new(0.0, 20.0), Vec2::new(12.0, 26.0), Vec2::new(30.0, 0.0), Vec2::new(15.0, -2.0)];
+    let inset = polygon_inset(&block, 1.5).expect("block is large enough to inset");
+    assert!(polygon_area(&inset) > 0.0, "inset is counter-clockwise");
+    for corner in &inset {
+        let gap = (0..block.len())
+            .map(|i| point_to_line_distance(*corner, block[i], block[(i + 1) % block.len()]))
+            .fold(f32::INFINITY, f32::min);
+        assert!((gap - 1.5).abs() < 1e-3, "corner {} is {} from the block edge", corner, gap);
+    }
+
+    // a strip narrower than twice the setback disappears
+    let strip = vec![Vec2::ZERO, Vec2::new(20.0, 0.0), Vec2::new(20.0, 2.0), Vec2::new(0.0, 2.0)];
+    assert!(polygon_inset(&strip, 1.5).is_none());
+}
+
+#[test]
+fn concave_cut_splits_into_every_piece() {
+    // C opening to the right, a vertical cut through both arms leaves the spine and two arm ends
+    let c_shape = vec![
+        Vec2::new(0.0, 0.0), Vec2::new(30.0, 0.0), Vec2::new(30.0, 10.0), Vec2::new(10.0, 10.0),
+        Vec2::new(10.0, 20.0), Vec2::new(30.0, 20.0), Vec2::new(30.0, 30.0), Vec2::new(0.0, 30.0),
+    ];
+    let pieces = bisect_poly(&c_shape, 0, 2.0 / 3.0, 0.0, 0.0);
+    assert_eq!(pieces.len(), 3);
+
+    let mut areas: Vec<f32> = pieces.iter().map(polygon_area).collect();
+    areas.sort_by(f32::total_cmp);
+    for (area, expected) in areas.iter().zip([100.0, 100.0, 500.0]) {
+        assert!((area - expected).abs() < 1e-3, "piece area {} (expected {})", area, expected);
+    }
+    for piece in &pieces {
+        let right_of_cut = piece.iter().all(|v| v.x >= 20.0 - 1e-4);
+        let left_of_cut = piece.iter().all(|v| v.x <= 20.0 + 1e-4);
+        assert!(right_of_cut || left_of_cut, "piece straddles the cut: {:?}", piece);
+    }
+}
+
+#[test]
+fn aspect_limit_removes_slivers() {
+    let max_aspect = 3.0;
+    // long thin strip, every cut across the longest edge leaves thin pieces
+    let block = vec![Vec2::new(0.0, 0.0), Vec2::new(60.0, 0.0), Vec2::new(62.0, 5.0), Vec2::new(-1.0, 5.0)];
+    for seed in 0..10 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let plots = subdivide_to_plots(&block, 15.0, 60.0, 0.8, 0.5, 0.5, 0.0, 0, &mut rng, 10, 0.0, 0.0, &mut Vec::new(), SubdivisionStrategy::LongestEdge, max_aspect, 0.0);
+        assert!(!plots.is_empty(), "seed {}: every plot was dropped", seed);
+        for plot in &plots {
+            let aspect = obb_aspect_ratio(plot);
+            assert!(aspect <= max_aspect, "seed {}: plot aspect {} over {}", seed, aspect, max_aspect);
+        }
+    }
+}
+
+#[test]
+fn alleys_record_the_gap_between_plots() {
+    let block = vec![Vec2::new(0.0, 0.0), Vec2::new(40.0, 0.0), Vec2::new(40.0, 30.0), Vec2::new(0.0, 30.0)];
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut alleys = Vec::new();
+    let plots = subdivide_to_plots(&block, 40.0, 400.0, 0.0, 0.5, 0.0, 0.0, 0, &mut rng, 6, 1.0, 2.0, &mut alleys, SubdivisionStrategy::LongestEdge, 0.0, 0.0);
+    assert!(!alleys.is_empty());
+    for alley in &alleys {
+        assert_eq!(alley.quad.len(), 4);
+        assert!(polygon_area(&alley.quad) > 0.0, "alley quad is counter-clockwise");
+        // the middle of the gap is left open by the plots either side
+        let middle = polygon_centroid(&alley.quad, polygon_area(&alley.quad));
+        assert!(plots.iter().all(|plot| !point_in_polygon(&middle, plot)), "alley at {} is built over", middle);
+    }
+}
+
+#[test]
+fn courtyard_blocks_leave_the_middle_empty() {
+    let block = vec![Vec2::new(0.0, 0.0), Vec2::new(40.0, 0.0), Vec2::new(40.0, 40.0), Vec2::new(0.0, 40.0)];
+    let mut rng = StdRng::seed_from_u64(5);
+    let plots = subdivide_to_plots(&block, 15.0, 60.0, 0.5, 0.5, 0.5, 0.0, 0, &mut rng, 10, 0.0, 0.0, &mut Vec::new(), SubdivisionStrategy::LongestEdge, 0.0, 1.0);
+
+    // the ring is COURTYARD_DEPTH deep, nothing is built inside it
+    let yard = 40.0 - 2.0 * COURTYARD_DEPTH;
+    let built: f32 = plots.iter().map(|plot| polygon_area(plot).abs()).sum();
+    assert!((built - (1600.0 - yard * yard)).abs() < 1e-2, "built area {}", built);
+    assert!(plots.iter().all(|plot| !point_in_polygon(&Vec2::splat(20.0), plot)));
+}
+
+#[test]
+fn voronoi_cells_fill_the_boundary() {
+    use crate::systems::mesh::Params;
+    use crate::systems::mesh::town::generate_generators;
+
+    let params = Params::default();
+    let boundary = generate_boundary_polygon(params.boundary_vertex_count, params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, INITIAL_SEED);
+    let generators = generate_generators(&params, &boundary, &[], INITIAL_SEED);
+    let voronoi = crate::systems::mesh::poly::voronoi::vpoly(generators.points, None, &boundary, CIRCUMCENTER_MERGE_THRESHOLD);
+
+    // clipped cells tile the outline, only cells under MIN_CLIPPED_CELL_AREA are missing
+    let cell_polygon = |cell: &Vec<usize>| -> Vec<Vec2> { cell.iter().map(|&idx| voronoi.points[idx].xz()).collect() };
+    let covered: f32 = voronoi.cells.iter().map(|cell| polygon_area(&cell_polygon(cell))).sum();
+    let outline = polygon_area(&boundary).abs();
+    assert!((covered - outline).abs() < outline * 0.01, "cells cover {} of {}", covered, outline);
+    for cell in &voronoi.cells {
+        assert!(polygon_area(&cell_polygon(cell)) > 0.0, "cells are counter-clockwise");
+    }
+
+    // neighbours are mutual and share a Voronoi edge
+    assert_eq!(voronoi.cell_adjacency.len(), voronoi.cells.len());
+    for (a, neighbours) in voronoi.cell_adjacency.iter().enumerate() {
+        assert!(!neighbours.is_empty(), "cell {} has no neighbours", a);
+        for &b in neighbours {
+            assert!(voronoi.cell_adjacency[b].contains(&a));
+            let shared = voronoi.cells[a].iter().filter(|idx| voronoi.cells[b].contains(idx)).count();
+            assert!(shared >= 2, "cells {} and {} share {} corners", a, b, shared);
+        }
+    }
+}
+
+#[test]
+fn generator_weights_grow_their_cell() {
+    use crate::systems::mesh::poly::voronoi::vpoly;
+
+    let boundary = vec![Vec2::new(-30.0, -30.0), Vec2::new(30.0, -30.0), Vec2::new(30.0, 30.0), Vec2::new(-30.0, 30.0)];
+    let generators: Vec<Vec3> = (0..5).flat_map(|i| (0..5).map(move |j| Vec3::new(i as f32 * 12.0 - 24.0 + j as f32 * 0.7, 0.0, j as f32 * 12.0 - 24.0))).collect();
+    let plain = vpoly(generators.clone(), None, &boundary, CIRCUMCENTER_MERGE_THRESHOLD);
+
+    // equal weights are the plain diagram, whatever their value
+    let equal = vpoly(generators.clone(), Some(&[2.5; 25]), &boundary, CIRCUMCENTER_MERGE_THRESHOLD);
+    assert!(equal.points == plain.points && equal.cells == plain.cells);
+
+    let cell_area = |data: &crate::systems::mesh::SkeletonData, generator: usize| {
+        let cell = data.cell_generators.iter().position(|&g| g == generator).expect("generator owns a cell");
+        polygon_area(&data.cells[cell].iter().map(|&idx| data.points[idx].xz()).collect())
+    };
+    let mut weights = vec![GENERATOR_WEIGHT; 25];
+    weights[12] = 2.0;
+    let weighted = vpoly(generators, Some(&weights), &boundary, CIRCUMCENTER_MERGE_THRESHOLD);
+    assert!(cell_area(&weighted, 12) > cell_area(&plain, 12) * 1.3, "{} vs {}", cell_area(&weighted, 12), cell_area(&plain, 12));
+}
+
+#[test]
+fn block_layouts_survive_cell_reordering() {
+    use crate::systems::mesh::{BlockOverrides, BlockSeeds, Params, SkeletonData};
+    use crate::systems::mesh::town::{block_regions, generate_generators, plan_block};
+
+    let params = Params::default();
+    let boundary = generate_boundary_polygon(params.boundary_vertex_count, params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, INITIAL_SEED);
+    let generators = generate_generators(&params, &boundary, &[], INITIAL_SEED);
+    let voronoi = crate::systems::mesh::poly::voronoi::vpoly(generators.points.clone(), None, &boundary, CIRCUMCENTER_MERGE_THRESHOLD);
+    let mut data = SkeletonData {
+        generator_points: generators.points,
+        generator_weights: Vec::new(),
+        points: voronoi.points,
+        cells: voronoi.cells,
+        cell_generators: voronoi.cell_generators,
+        cell_adjacency: voronoi.cell_adjacency,
+        road_path: Vec::new(),
+        road_widths: Vec::new(),
+        road_generators: generators.road,
+        boundary_generators: generators.boundary,
+        relaxation_steps: generators.relaxation_steps,
+        boundary_polygon: boundary,
+        boundary_vertex_offsets: Vec::new(),
+    };
+
+    // plots per block, keyed by the block's centroid
+    let (overrides, rerolls) = (BlockOverrides::default(), BlockSeeds::default());
+    let layout = |data: &SkeletonData| -> std::collections::HashMap<[i64; 2], Vec<Vec<Vec2>>> {
+        block_regions(data, &params, INITIAL_SEED).into_iter()
+            .map(|(block_idx, polygon)| {
+                let mut rng = StdRng::seed_from_u64(rerolls.block_seed(INITIAL_SEED, block_idx, &polygon));
+                let plots = plan_block(block_idx, &polygon, &params, &overrides, &mut rng).map(|(plots, _)| plots).unwrap_or_default();
+                let centroid = polygon_centroid(&polygon, polygon_area(&polygon));
+                ([centroid.x, centroid.y].map(|c| (c * 1e3).round() as i64), plots)
+            })
+            .collect()
+    };
+
+    let before = layout(&data);
+    assert_eq!(before.len(), data.cells.len());
+    assert!(before.values().any(|plots| !plots.is_empty()));
+    data.cells.reverse();
+    data.cell_generators.reverse();
+    assert_eq!(layout(&data), before);
+}
+
+#[test]
+fn smoothed_roads_pass_through_their_points() {
+    let road = vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(20.0, 0.0, 0.0), Vec3::new(20.0, 0.0, 20.0), Vec3::new(40.0, 0.0, 20.0)];
+    assert_eq!(smooth_road_path(&road, 0), road);
+
+    let smoothed = smooth_road_path(&road, 4);
+    assert_eq!(smoothed.len(), (road.len() - 1) * 5 + 1);
+    for (idx, point) in road.iter().enumerate() {
+        assert!(smoothed[idx * 5].distance(*point) < 1e-4);
+    }
+    // the corner is rounded off, not cut through
+    let corner = smoothed[5 + 4];
+    assert!(corner.x < 20.0 && corner.z < 20.0 && corner.x > 10.0);
+}
+
+#[test]
+fn road_crossings_stay_open() {
+    // the path loops back over its first segment, crossing it at the origin
+    let road = vec![Vec3::new(-28.0, 0.0, 0.0), Vec3::new(30.0, 0.0, 0.0), Vec3::new(30.0, 0.0, 30.0), Vec3::new(0.0, 0.0, 30.0), Vec3::new(0.0, 0.0, -30.0)];
+    let generators = generate_road_generators(&road, 0);
+    assert!(!generators.is_empty());
+    for generator in &generators {
+        assert!(generator.x.abs() > CORNER_CONSTRAINT_DISTANCE * 0.7 || generator.z.abs() > CORNER_CONSTRAINT_DISTANCE * 0.7);
+    }
+}
+
+#[test]
+fn road_cells_follow_their_generators() {
+    use crate::systems::mesh::Params;
+    use crate::systems::mesh::town::generate_generators;
+
+    let params = Params::default();
+    let boundary = generate_boundary_polygon(params.boundary_vertex_count, params.boundary_scale, params.boundary_aspect, params.boundary_noise_freq, params.boundary_noise_amp, INITIAL_SEED);
+    let road = vec![Vec3::new(-30.0, 0.0, 0.0), Vec3::new(30.0, 0.0, 0.0)];
+    let generators = generate_generators(&params, &boundary, &road, INITIAL_SEED);
+    let mut data = crate::systems::mesh::poly::voronoi::vpoly(generators.points.clone(), None, &boundary, CIRCUMCENTER_MERGE_THRESHOLD);
+    data.set_generators(generators);
+    assert!(!data.road_generators.is_empty());
+    for cell_idx in 0..data.cells.len() {
+        assert_eq!(data.is_road_cell(cell_idx), data.road_generators.contains(&data.cell_generators[cell_idx]));
+    }
+
+    // removing a regular generator shifts the fixed ranges down with it
+    let (road, boundary) = (data.road_generators.clone(), data.boundary_generators.clone());
+    data.remove_generator(0);
+    assert_eq!(data.road_generators, road.start - 1..road.end - 1);
+    assert_eq!(data.boundary_generators, boundary.start - 1..boundary.end - 1);
+    data.remove_generator(data.road_generators.start);
+    assert_eq!(data.road_generators.len(), road.len() - 1);
+    assert_eq!(data.boundary_generators.start, data.road_generators.end);
+}
+
+#[test]
+fn boundary_generators_stay_inside_concave_boundaries() {
+    // thin spike narrower than the inner offset, its inner generators would cross to the other side
+    let boundary = vec![
+        Vec2::new(0.0, 0.0), Vec2::new(40.0, 0.0), Vec2::new(40.0, 20.0), Vec2::new(20.3, 20.0),
+        Vec2::new(20.3, 50.0), Vec2::new(19.7, 50.0), Vec2::new(19.7, 20.0), Vec2::new(0.0, 20.0),
+    ];
+    let generators = generate_boundary_generators(&boundary, BOUNDARY_GENERATOR_SPACING, 1.5);
+    let inside = generators.iter().filter(|g| point_in_polygon(&g.xz(), &boundary)).count();
+    assert!(inside > 0);
+
+    // everything outside is an outer generator, at the outer offset from the boundary
+    for generator in generators.iter().filter(|g| !point_in_polygon(&g.xz(), &boundary)) {
+        let distance = (0..boundary.len())
+            .map(|i| point_to_line_distance(generator.xz(), boundary[i], boundary[(i + 1) % boundary.len()]))
+            .fold(f32::INFINITY, f32::min);
+        assert!((distance - BOUNDARY_GENERATOR_OUTER_OFFSET).abs() < 1e-3, "generator {:?} is {} from the boundary", generator, distance);
+    }
+}
+
+#[test]
+fn limited_outlines_fit_the_vertex_range() {
+    let circle = generate_regular_polygon(20, 30.0, 1.0);
+    let reduced = ops::limit_vertices(&circle, BOUNDARY_VERTEX_RANGE);
+    assert_eq!(reduced.len(), *BOUNDARY_VERTEX_RANGE.end());
+    assert!(ops::is_simple(&reduced));
+    assert!(polygon_area(&reduced) > polygon_area(&circle) * 0.9);
+
+    // a triangle already fits, below a larger minimum its longest edges are split
+    let triangle = generate_regular_polygon(3, 30.0, 1.0);
+    assert_eq!(ops::limit_vertices(&triangle, BOUNDARY_VERTEX_RANGE).len(), *BOUNDARY_VERTEX_RANGE.start());
+    let split = ops::limit_vertices(&triangle, 5..=12);
+    assert_eq!(split.len(), 5);
+    assert!((polygon_area(&split) - polygon_area(&triangle)).abs() < 1e-3);
+}